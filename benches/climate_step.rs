@@ -0,0 +1,53 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use physics_types::{Duration, FluxDensity, Temperature};
+use planetary_dynamics::adjacency::Adjacency;
+use planetary_dynamics::climate::{ClimateConfig, ClimateModel};
+use planetary_dynamics::climate_arrays::ClimateArrays;
+use planetary_dynamics::terrain::Terrain;
+
+criterion_main! {
+    climate,
+}
+
+criterion_group! {
+    climate,
+    climate_model_step,
+    climate_arrays_step,
+}
+
+const N: usize = 256;
+
+fn inputs() -> (Adjacency, Vec<Terrain>, Vec<f64>) {
+    let mut adjacency = Adjacency::default();
+    adjacency.register(N);
+
+    let terrain = vec![Terrain::new_fraction(0.7, 0.24, 0.03); N];
+    let intensity = (0..N)
+        .map(|i| (i as f64 / N as f64 * 40.0).sin().max(0.0))
+        .collect();
+
+    (adjacency, terrain, intensity)
+}
+
+pub fn climate_model_step(c: &mut Criterion) {
+    let (adjacency, terrain, intensity) = inputs();
+    let adjacency = adjacency.get(N);
+    let flux = FluxDensity::in_w_per_m2(1361.0);
+    let mut model = ClimateModel::new(ClimateConfig::earth(), N, Temperature::in_c(15.0));
+
+    c.bench_function("climate_model_step", |b| {
+        b.iter(|| model.step(&terrain, adjacency, flux, &intensity, Duration::in_hr(0.2)))
+    });
+}
+
+pub fn climate_arrays_step(c: &mut Criterion) {
+    let (adjacency, terrain, intensity) = inputs();
+    let adjacency = adjacency.get(N);
+    let flux = FluxDensity::in_w_per_m2(1361.0);
+    let model = ClimateModel::new(ClimateConfig::earth(), N, Temperature::in_c(15.0));
+    let mut arrays = ClimateArrays::new(&model, &terrain);
+
+    c.bench_function("climate_arrays_step", |b| {
+        b.iter(|| arrays.step(adjacency, flux, &intensity, Duration::in_hr(0.2)))
+    });
+}
@@ -1,33 +1,100 @@
 #![allow(non_snake_case)]
 
-pub use crate::adjacency::adj_array::AdjArray;
+pub use crate::adjacency::adj_array::{AdjArray, AdjArrayN};
 use crate::adjacency::units::*;
 use fxhash::FxHashMap as HashMap;
 use physics_types::{Area, Length};
 
+/// Tiles for a body of the given radius at the default `TileDensity`
 pub fn get_tile_count(radius: Length) -> usize {
-    let size = (radius / Length::in_m(6350e3) * 96.0) as usize;
-    (size / STEP_SIZE * STEP_SIZE).min(MAX_SIZE)
+    TileDensity::default().tile_count(radius)
 }
 
+/// How finely a body's surface is divided into tiles, trading detail for the time to simulate it
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TileDensity {
+    /// Tiles in proportion to the radius, with `earth` tiles for a body the size of Earth,
+    /// clamped to between 4 and 256 tiles
+    Scaled { earth: f64 },
+    /// A tile for each area of the surface, clamped to between 4 and 256 tiles,
+    /// so very small areas give coarser tiles than asked for
+    Area(#[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))] Area),
+    /// Tiles whose centres are about this far apart, see `get_tile_spacing`,
+    /// clamped to between 4 and 256 tiles, so very short spacings give coarser tiles than asked for
+    Spacing(#[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))] Length),
+}
+
+impl Default for TileDensity {
+    fn default() -> Self {
+        TileDensity::Scaled { earth: 96.0 }
+    }
+}
+
+impl TileDensity {
+    /// The number of tiles for a body of the given radius, rounded down to a multiple of 4
+    /// and clamped to the sizes registered by `Adjacency::initialize`, at most `MAX_SIZE` (256)
+    pub fn tile_count(self, radius: Length) -> usize {
+        let surface = Area::of_sphere(radius);
+
+        let size = match self {
+            TileDensity::Scaled { earth } => (radius / Length::in_m(6350e3) * earth) as usize,
+            TileDensity::Area(area) => (surface / area).round() as usize,
+            TileDensity::Spacing(spacing) => (surface / (spacing * spacing)).round() as usize,
+        };
+
+        (size / STEP_SIZE * STEP_SIZE).clamp(STEP_SIZE, MAX_SIZE)
+    }
+}
+
+/// The mean area of a tile at the default `TileDensity`, see `get_tile_areas` for unequal tiles
 pub fn get_tile_area(radius: Length) -> Area {
     let tiles = get_tile_count(radius);
     let area = Area::of_sphere(radius);
     area / tiles as f64
 }
 
+/// The area of each tile, from the fraction of the sphere each covers,
+/// see `Adjacency::relative_areas`
+pub fn get_tile_areas(radius: Length, relative_areas: &[f64]) -> Vec<Area> {
+    let area = Area::of_sphere(radius);
+    relative_areas
+        .iter()
+        .map(|fraction| area * *fraction)
+        .collect()
+}
+
+/// Typical distance between the centres of neighbouring tiles
+pub fn get_tile_spacing(radius: Length, nodes: usize) -> Length {
+    radius * (4.0 * std::f64::consts::PI / nodes as f64).sqrt()
+}
+
 const STEP_SIZE: usize = 4;
 const MAX_SIZE: usize = 256;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Adjacency {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sorted_map"))]
     map: HashMap<usize, Vec<AdjArray>>,
+    /// The centre of each tile, from the layout each size was registered with
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sorted_map"))]
+    positions: HashMap<usize, Vec<Position3>>,
+    /// The fraction of the sphere covered by each tile, see `Tiling::relative_areas`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::sorted_map"))]
+    areas: HashMap<usize, Vec<f64>>,
 }
 
 impl Default for Adjacency {
     fn default() -> Self {
         let map = HashMap::default();
-        Adjacency { map }
+        let positions = HashMap::default();
+        let areas = HashMap::default();
+        Adjacency {
+            map,
+            positions,
+            areas,
+        }
     }
 }
 
@@ -44,21 +111,67 @@ impl Adjacency {
 
     pub fn clear(&mut self) {
         self.map.clear();
+        self.positions.clear();
+        self.areas.clear();
     }
 
     pub fn register(&mut self, nodes: usize) {
-        self.map
-            .entry(nodes)
-            .or_insert_with(|| Self::create_min_edges(nodes));
+        self.register_tiling(&Spiral, nodes);
+    }
+
+    /// Registers the neighbours, positions, and areas of a size using an alternative layout.
+    /// Each size can only be registered with one layout, and registering it with another panics.
+    #[track_caller]
+    pub fn register_tiling<T: Tiling>(&mut self, tiling: &T, nodes: usize) {
+        if let Some(positions) = self.positions.get(&nodes) {
+            assert!(
+                positions == &tiling.positions(nodes),
+                "size registered with another tiling: {}",
+                nodes
+            );
+            return;
+        }
+
+        self.map.insert(nodes, tiling.adjacency(nodes));
+        self.positions.insert(nodes, tiling.positions(nodes));
+        self.areas.insert(nodes, tiling.relative_areas(nodes));
     }
 
     #[track_caller]
-    pub fn get(&self, nodes: usize) -> &Vec<AdjArray> {
-        self.map
+    pub fn get(&self, nodes: usize) -> &[AdjArray] {
+        self.try_get(nodes)
+            .unwrap_or_else(|| panic!("unregisted size: {}", nodes))
+    }
+
+    pub fn try_get(&self, nodes: usize) -> Option<&[AdjArray]> {
+        self.map.get(&nodes).map(Vec::as_slice)
+    }
+
+    /// The centre of each tile of a registered size as a unit vector, with the rotation axis along z
+    #[track_caller]
+    pub fn positions(&self, nodes: usize) -> &[Position3] {
+        self.positions
+            .get(&nodes)
+            .map(Vec::as_slice)
+            .unwrap_or_else(|| panic!("unregisted size: {}", nodes))
+    }
+
+    /// The fraction of the sphere covered by each tile of a registered size
+    #[track_caller]
+    pub fn relative_areas(&self, nodes: usize) -> &[f64] {
+        self.areas
             .get(&nodes)
+            .map(Vec::as_slice)
             .unwrap_or_else(|| panic!("unregisted size: {}", nodes))
     }
 
+    /// Neighbour counts across all registered sizes, to check whether `AdjArray` has enough capacity.
+    /// Computed on demand and only built with debug assertions, so release builds carry no cost.
+    #[cfg(debug_assertions)]
+    pub fn stats(&self) -> AdjacencyStats {
+        AdjacencyStats::new(self.map.values().flatten())
+    }
+
     fn create_min_edges(nodes: usize) -> Vec<AdjArray> {
         let rotations = rotations(nodes);
 
@@ -95,22 +208,634 @@ impl Adjacency {
     }
 }
 
+/// A layout of tiles on the unit sphere
+pub trait Tiling {
+    /// The supported tile count nearest to `requested`
+    fn tile_count(&self, requested: usize) -> usize;
+
+    /// The centre of each tile
+    fn positions(&self, nodes: usize) -> Vec<Position3>;
+
+    fn adjacency(&self, nodes: usize) -> Vec<AdjArray>;
+
+    /// The fraction of the sphere covered by each tile
+    fn relative_areas(&self, nodes: usize) -> Vec<f64> {
+        vec![1.0 / nodes as f64; nodes]
+    }
+
+    /// The polygon bounding each tile, see `voronoi_cells`
+    fn cells(&self, nodes: usize) -> Vec<Vec<Position3>> {
+        voronoi_cells(&self.positions(nodes))
+    }
+}
+
+/// Tiles placed along a Fibonacci spiral, which supports any tile count but gives irregular neighbours
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spiral;
+
+impl Tiling for Spiral {
+    fn tile_count(&self, requested: usize) -> usize {
+        requested.max(1)
+    }
+
+    fn positions(&self, nodes: usize) -> Vec<Position3> {
+        let rotations = rotations(nodes);
+        (0..nodes)
+            .map(|index| Node { index, nodes }.position(rotations))
+            .collect()
+    }
+
+    fn adjacency(&self, nodes: usize) -> Vec<AdjArray> {
+        Adjacency::create_min_edges(nodes)
+    }
+}
+
+/// The spiral layout with a single large tile covering each pole, for cleaner polar climate and ice caps.
+/// The first and last tiles are the north and south caps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PolarCaps {
+    /// Fraction of the sphere covered by each cap
+    pub cap_area: f64,
+}
+
+impl Default for PolarCaps {
+    fn default() -> Self {
+        Self { cap_area: 0.03 }
+    }
+}
+
+impl PolarCaps {
+    pub fn new(cap_area: f64) -> Self {
+        assert!(
+            cap_area > 0.0 && cap_area < 0.5,
+            "cap area must be between 0 and 0.5: {}",
+            cap_area
+        );
+        Self { cap_area }
+    }
+}
+
+impl Tiling for PolarCaps {
+    fn tile_count(&self, requested: usize) -> usize {
+        requested.max(3)
+    }
+
+    fn positions(&self, nodes: usize) -> Vec<Position3> {
+        assert!(nodes >= 3);
+
+        let band = nodes - 2;
+        let edge = 1.0 - 2.0 * self.cap_area;
+        let golden_angle = std::f64::consts::PI * (3.0 - 5f64.sqrt());
+
+        let pole = |z: f64| Position3 { x: 0.0, y: 0.0, z };
+
+        let band = (0..band).map(|k| {
+            let z = edge * (1.0 - 2.0 * (k as f64 + 0.5) / band as f64);
+            let r = (1.0 - z * z).sqrt();
+            let theta = golden_angle * k as f64;
+            Position3 {
+                x: r * theta.cos(),
+                y: r * theta.sin(),
+                z,
+            }
+        });
+
+        std::iter::once(pole(1.0))
+            .chain(band)
+            .chain(std::iter::once(pole(-1.0)))
+            .collect()
+    }
+
+    /// Links the closest tiles, measuring from the edges of the tiles so that the caps reach their whole rim
+    fn adjacency(&self, nodes: usize) -> Vec<AdjArray> {
+        let positions = self.positions(nodes);
+
+        // the angular radius of a circle with the same area as the tile
+        let radius = self
+            .relative_areas(nodes)
+            .into_iter()
+            .map(|area| (1.0 - 2.0 * area).acos())
+            .collect::<Vec<_>>();
+
+        let mut edges = vec![];
+        for (i, p) in positions.iter().enumerate() {
+            for (j, q) in positions.iter().enumerate().skip(i + 1) {
+                let cos = (p.x * q.x + p.y * q.y + p.z * q.z).clamp(-1.0, 1.0);
+                let gap = cos.acos() - radius[i] - radius[j];
+                edges.push((gap, i, j));
+            }
+        }
+
+        edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut count = (nodes as f64 * 3.05) as usize;
+        let mut adjacency = vec![AdjArray::default(); nodes];
+
+        for (_, i, j) in edges {
+            if count == 0 {
+                break;
+            }
+
+            let full = |n: usize| adjacency[n].len() == AdjArray::CAPACITY;
+            if !full(i) && !full(j) {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+                count -= 1;
+            }
+        }
+
+        adjacency
+    }
+
+    fn relative_areas(&self, nodes: usize) -> Vec<f64> {
+        let band = (1.0 - 2.0 * self.cap_area) / (nodes - 2) as f64;
+        let mut areas = vec![band; nodes];
+        areas[0] = self.cap_area;
+        areas[nodes - 1] = self.cap_area;
+        areas
+    }
+}
+
+/// Hexagonal tiles with twelve pentagons, from a subdivided icosahedron.
+/// Supports 10m² + 2 tiles, where m is the subdivision frequency.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Goldberg;
+
+impl Goldberg {
+    /// The highest frequency whose tiles can be indexed by `AdjArray`
+    const MAX_FREQUENCY: usize = 5;
+
+    fn tile_count_for(frequency: usize) -> usize {
+        10 * frequency * frequency + 2
+    }
+
+    fn frequency(nodes: usize) -> usize {
+        (1..=Self::MAX_FREQUENCY)
+            .find(|m| Self::tile_count_for(*m) == nodes)
+            .unwrap_or_else(|| panic!("not a Goldberg tile count: {}", nodes))
+    }
+
+    /// Subdivides each face of an icosahedron, returning the unique points and the edges between them
+    fn mesh(nodes: usize) -> (Vec<Position3>, Vec<(usize, usize)>) {
+        let m = Self::frequency(nodes);
+
+        let phi = (1.0 + 5f64.sqrt()) / 2.0;
+        let mut vertices = vec![];
+        for a in [-1.0, 1.0] {
+            for b in [-phi, phi] {
+                vertices.push([0.0, a, b]);
+                vertices.push([a, b, 0.0]);
+                vertices.push([b, 0.0, a]);
+            }
+        }
+
+        // the faces are the triangles of vertices separated by the edge length, 2
+        let is_edge = |p: &[f64; 3], q: &[f64; 3]| {
+            let d2 = (0..3).map(|i| (p[i] - q[i]).powi(2)).sum::<f64>();
+            (d2 - 4.0).abs() < 1e-9
+        };
+
+        let mut faces = vec![];
+        for i in 0..vertices.len() {
+            for j in i + 1..vertices.len() {
+                for k in j + 1..vertices.len() {
+                    let (a, b, c) = (&vertices[i], &vertices[j], &vertices[k]);
+                    if is_edge(a, b) && is_edge(b, c) && is_edge(a, c) {
+                        faces.push([*a, *b, *c]);
+                    }
+                }
+            }
+        }
+
+        let mut points = vec![];
+        let mut index = HashMap::<[i64; 3], usize>::default();
+        let mut edges = vec![];
+
+        for [a, b, c] in faces {
+            let mut point = |i: usize, j: usize| {
+                let k = m - i - j;
+                let p = (0..3)
+                    .map(|n| (i as f64 * a[n] + j as f64 * b[n] + k as f64 * c[n]) / m as f64)
+                    .collect::<Vec<_>>();
+                let length = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+                let p = Position3 {
+                    x: p[0] / length,
+                    y: p[1] / length,
+                    z: p[2] / length,
+                };
+
+                let key = [p.x, p.y, p.z].map(|v| (v * 1e6).round() as i64);
+                *index.entry(key).or_insert_with(|| {
+                    points.push(p);
+                    points.len() - 1
+                })
+            };
+
+            for i in 0..=m {
+                for j in 0..=m - i {
+                    let p = point(i, j);
+                    if i + j < m {
+                        edges.push((p, point(i + 1, j)));
+                        edges.push((p, point(i, j + 1)));
+                    }
+                    if j > 0 {
+                        edges.push((p, point(i + 1, j - 1)));
+                    }
+                }
+            }
+        }
+
+        assert_eq!(nodes, points.len());
+        (points, edges)
+    }
+}
+
+impl Tiling for Goldberg {
+    fn tile_count(&self, requested: usize) -> usize {
+        (1..=Self::MAX_FREQUENCY)
+            .map(Self::tile_count_for)
+            .min_by_key(|n| (*n as isize - requested as isize).abs())
+            .unwrap()
+    }
+
+    fn positions(&self, nodes: usize) -> Vec<Position3> {
+        Self::mesh(nodes).0
+    }
+
+    fn adjacency(&self, nodes: usize) -> Vec<AdjArray> {
+        let mut adjacency = vec![AdjArray::default(); nodes];
+
+        for (i, j) in Self::mesh(nodes).1 {
+            if !adjacency[i].contains(j) {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+            }
+        }
+
+        adjacency
+    }
+}
+
+/// The shortest path from `a` to `b` by number of steps, including both ends.
+/// Returns an empty path if `b` can't be reached.
+pub fn path(a: usize, b: usize, adjacency: &[AdjArray]) -> Vec<usize> {
+    let mut previous = vec![None; adjacency.len()];
+    let mut queue = std::collections::VecDeque::new();
+
+    previous[a] = Some(a);
+    queue.push_back(a);
+
+    while let Some(tile) = queue.pop_front() {
+        if tile == b {
+            return trace_path(a, b, &previous);
+        }
+
+        for n in adjacency[tile].iter() {
+            if previous[n].is_none() {
+                previous[n] = Some(tile);
+                queue.push_back(n);
+            }
+        }
+    }
+
+    vec![]
+}
+
+/// The cheapest path from `a` to `b` using A* with great-circle distance as the heuristic.
+/// Returns an empty path if `b` can't be reached.
+///
+/// # Arguments
+///
+/// * `positions`: the centre of each tile, see `Tiling::positions`
+/// * `cost`: the cost per unit distance of crossing each tile, or `None` if impassable
+pub fn path_with_cost<F: Fn(usize) -> Option<f64>>(
+    a: usize,
+    b: usize,
+    adjacency: &[AdjArray],
+    positions: &[Position3],
+    cost: F,
+) -> Vec<usize> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let costs = (0..adjacency.len()).map(cost).collect::<Vec<_>>();
+    if costs[a].is_none() || costs[b].is_none() {
+        return vec![];
+    }
+
+    let min_cost = costs
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let distance = |i: usize, j: usize| {
+        let (p, q) = (positions[i], positions[j]);
+        (p.x * q.x + p.y * q.y + p.z * q.z).clamp(-1.0, 1.0).acos()
+    };
+    let heuristic = |i: usize| distance(i, b) * min_cost;
+
+    let mut best = vec![f64::INFINITY; adjacency.len()];
+    let mut previous = vec![None; adjacency.len()];
+    let mut open = BinaryHeap::new();
+
+    best[a] = 0.0;
+    previous[a] = Some(a);
+    open.push(Reverse(Priority(heuristic(a), a)));
+
+    while let Some(Reverse(Priority(_, tile))) = open.pop() {
+        if tile == b {
+            return trace_path(a, b, &previous);
+        }
+
+        for n in adjacency[tile].iter() {
+            if let Some(cost) = costs[n] {
+                // half of each tile is crossed on the way between their centres
+                let step = distance(tile, n) * 0.5 * (costs[tile].unwrap_or(cost) + cost);
+                let total = best[tile] + step;
+
+                if total < best[n] {
+                    best[n] = total;
+                    previous[n] = Some(tile);
+                    open.push(Reverse(Priority(total + heuristic(n), n)));
+                }
+            }
+        }
+    }
+
+    vec![]
+}
+
+/// Groups the tiles matching `predicate` into connected components, such as continents or lakes.
+/// Components are ordered by their lowest tile, and tiles within a component are in ascending order.
+pub fn components<F: Fn(usize) -> bool>(adjacency: &[AdjArray], predicate: F) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; adjacency.len()];
+    let mut components = vec![];
+    let mut stack = vec![];
+
+    for start in 0..adjacency.len() {
+        if visited[start] || !predicate(start) {
+            continue;
+        }
+
+        let mut component = vec![];
+        visited[start] = true;
+        stack.push(start);
+
+        while let Some(tile) = stack.pop() {
+            component.push(tile);
+
+            for n in adjacency[tile].iter() {
+                if !visited[n] && predicate(n) {
+                    visited[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+
+        component.sort_unstable();
+        components.push(component);
+    }
+
+    components
+}
+
+/// The spherical Voronoi cell of each tile as a list of unit vertices,
+/// counter-clockwise when viewed from outside the sphere.
+///
+/// Each cell is clipped from a square on the plane tangent to the tile centre by the bisectors
+/// of every tile in the same hemisphere, then projected back onto the sphere.
+/// Cells wider than about 80° are truncated, so very coarse tilings are approximate.
+pub fn voronoi_cells(positions: &[Position3]) -> Vec<Vec<Position3>> {
+    fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    }
+
+    fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    fn unit(a: [f64; 3]) -> [f64; 3] {
+        let length = dot(a, a).sqrt();
+        [a[0] / length, a[1] / length, a[2] / length]
+    }
+
+    const EXTENT: f64 = 10.0;
+
+    let vector = |p: Position3| [p.x, p.y, p.z];
+
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let p = vector(*p);
+            let reference = if p[0].abs() < 0.9 {
+                [1.0, 0.0, 0.0]
+            } else {
+                [0.0, 1.0, 0.0]
+            };
+            let e1 = unit(cross(p, reference));
+            let e2 = cross(p, e1);
+
+            // coordinates on the tangent plane, whose lines are great circles on the sphere
+            let mut polygon = vec![
+                (-EXTENT, -EXTENT),
+                (EXTENT, -EXTENT),
+                (EXTENT, EXTENT),
+                (-EXTENT, EXTENT),
+            ];
+            let mut clipped = vec![];
+
+            for (j, q) in positions.iter().enumerate() {
+                let q = vector(*q);
+                if i == j || dot(p, q) <= 0.0 {
+                    continue;
+                }
+
+                // points closer to p than to q
+                let d = [p[0] - q[0], p[1] - q[1], p[2] - q[2]];
+                let (c, a, b) = (dot(p, d), dot(e1, d), dot(e2, d));
+                let side = |(u, v): (f64, f64)| c + a * u + b * v;
+
+                clipped.clear();
+                for k in 0..polygon.len() {
+                    let s = polygon[k];
+                    let t = polygon[(k + 1) % polygon.len()];
+                    let (fs, ft) = (side(s), side(t));
+
+                    if fs >= 0.0 {
+                        clipped.push(s);
+                    }
+                    if (fs >= 0.0) != (ft >= 0.0) {
+                        let r = fs / (fs - ft);
+                        clipped.push((s.0 + r * (t.0 - s.0), s.1 + r * (t.1 - s.1)));
+                    }
+                }
+                std::mem::swap(&mut polygon, &mut clipped);
+            }
+
+            polygon
+                .into_iter()
+                .map(|(u, v)| {
+                    let x = unit([
+                        p[0] + u * e1[0] + v * e2[0],
+                        p[1] + u * e1[1] + v * e2[1],
+                        p[2] + u * e1[2] + v * e2[2],
+                    ]);
+                    Position3 {
+                        x: x[0],
+                        y: x[1],
+                        z: x[2],
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn trace_path(a: usize, b: usize, previous: &[Option<usize>]) -> Vec<usize> {
+    let mut path = vec![b];
+    let mut tile = b;
+
+    while tile != a {
+        tile = previous[tile].expect("tile not reached");
+        path.push(tile);
+    }
+
+    path.reverse();
+    path
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Priority(f64, usize);
+
+impl Eq for Priority {}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// Neighbours and neighbours of neighbours for each node, for wider neighbourhoods than `AdjArray` holds.
+/// Panics if a node has more than `N` of them.
+pub fn second_ring<const N: usize>(adjacency: &[AdjArray]) -> Vec<AdjArrayN<N>> {
+    adjacency
+        .iter()
+        .enumerate()
+        .map(|(i, neighbours)| {
+            let mut ring = AdjArrayN::<N>::default();
+            let wider = neighbours.iter().flat_map(|n| adjacency[n].iter());
+
+            for n in neighbours.iter().chain(wider) {
+                if n != i && !ring.contains(n) {
+                    ring.push(n);
+                }
+            }
+
+            ring
+        })
+        .collect()
+}
+
+#[cfg(debug_assertions)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdjacencyStats {
+    pub nodes: usize,
+    pub min_neighbours: usize,
+    pub max_neighbours: usize,
+    pub mean_neighbours: f64,
+    /// Nodes with full neighbour arrays, for which one more neighbour would panic
+    pub near_misses: usize,
+    /// Node count indexed by number of neighbours
+    pub histogram: [usize; AdjArray::CAPACITY + 1],
+}
+
+#[cfg(debug_assertions)]
+impl AdjacencyStats {
+    pub fn new<'a, I: IntoIterator<Item = &'a AdjArray>>(arrays: I) -> Self {
+        let mut histogram = [0usize; AdjArray::CAPACITY + 1];
+
+        for array in arrays {
+            histogram[array.len()] += 1;
+        }
+
+        let nodes = histogram.iter().sum::<usize>();
+        let total = histogram
+            .iter()
+            .enumerate()
+            .map(|(len, count)| len * count)
+            .sum::<usize>();
+
+        let mut non_zero = histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(len, _)| len);
+
+        let min_neighbours = non_zero.next().unwrap_or_default();
+        let max_neighbours = non_zero.last().unwrap_or(min_neighbours);
+
+        Self {
+            nodes,
+            min_neighbours,
+            max_neighbours,
+            mean_neighbours: if nodes == 0 {
+                0.0
+            } else {
+                total as f64 / nodes as f64
+            },
+            near_misses: histogram[AdjArray::CAPACITY],
+            histogram,
+        }
+    }
+}
+
 mod adj_array {
     use std::convert::TryFrom;
     use std::fmt::{Display, Formatter};
     use std::iter::FromIterator;
 
-    #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
-    pub struct AdjArray([u8; Self::LEN]);
+    /// The neighbours of a node for the default layouts
+    pub type AdjArray = AdjArrayN<7>;
 
-    impl FromIterator<usize> for AdjArray {
+    /// Neighbour indices stored inline, holding up to `N` neighbours
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub struct AdjArrayN<const N: usize> {
+        len: u8,
+        array: [u8; N],
+    }
+
+    impl<const N: usize> Default for AdjArrayN<N> {
+        fn default() -> Self {
+            Self {
+                len: 0,
+                array: [0; N],
+            }
+        }
+    }
+
+    impl<const N: usize> FromIterator<usize> for AdjArrayN<N> {
         fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
             // this isn't optimal, but it's only done at startup
-            let mut array = <[u8; Self::LEN]>::default();
+            let mut array = [0u8; N];
             let mut len = 0usize;
             let mut iter = iter.into_iter();
 
-            array[1..].iter_mut().zip(&mut iter).for_each(|(v, item)| {
+            array.iter_mut().zip(&mut iter).for_each(|(v, item)| {
                 let item = u8::try_from(item).unwrap();
                 *v = item;
                 len += 1;
@@ -118,13 +843,14 @@ mod adj_array {
 
             assert_eq!(None, iter.next());
 
-            array[0] = len as u8;
-
-            Self(array)
+            Self {
+                len: len as u8,
+                array,
+            }
         }
     }
 
-    impl Display for AdjArray {
+    impl<const N: usize> Display for AdjArrayN<N> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             if self.is_empty() {
                 write!(f, "[]")
@@ -143,16 +869,49 @@ mod adj_array {
         }
     }
 
-    impl AdjArray {
-        const LEN: usize = 8;
-        const MAX: usize = Self::LEN - 1;
+    /// Serialized as the list of neighbour indices, independent of the inline layout
+    #[cfg(feature = "serde")]
+    impl<const N: usize> serde::Serialize for AdjArrayN<N> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de, const N: usize> serde::Deserialize<'de> for AdjArrayN<N> {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            use serde::de::Error;
+
+            let values = Vec::<usize>::deserialize(deserializer)?;
+
+            if values.len() > N {
+                return Err(D::Error::invalid_length(
+                    values.len(),
+                    &"no more neighbours than AdjArray can hold",
+                ));
+            }
+
+            if let Some(v) = values.iter().find(|v| **v > u8::MAX as usize) {
+                return Err(D::Error::custom(format!(
+                    "neighbour index out of range: {}",
+                    v
+                )));
+            }
+
+            Ok(values.into_iter().collect())
+        }
+    }
+
+    impl<const N: usize> AdjArrayN<N> {
+        /// The most neighbours that a node can have
+        pub const CAPACITY: usize = N;
 
         pub fn len(&self) -> usize {
-            self.0[0] as usize
+            self.len as usize
         }
 
         pub fn is_empty(&self) -> bool {
-            self.0[0] == 0
+            self.len == 0
         }
 
         pub fn iter(&self) -> Iter {
@@ -170,10 +929,10 @@ mod adj_array {
         }
 
         pub fn push(&mut self, value: usize) {
-            assert!(self.len() < Self::MAX);
+            assert!(self.len() < N);
             let value = u8::try_from(value).unwrap();
-            self.0[self.len() + 1] = value;
-            self.0[0] += 1;
+            self.array[self.len()] = value;
+            self.len += 1;
         }
 
         pub fn and(self, rhs: Self) -> Self {
@@ -181,13 +940,12 @@ mod adj_array {
         }
     }
 
-    impl<'a> IntoIterator for &'a AdjArray {
+    impl<'a, const N: usize> IntoIterator for &'a AdjArrayN<N> {
         type Item = usize;
         type IntoIter = Iter<'a>;
 
         fn into_iter(self) -> Self::IntoIter {
-            let end = self.len() + 1;
-            Iter(self.0[1..end].iter())
+            Iter(self.array[..self.len()].iter())
         }
     }
 
@@ -230,10 +988,45 @@ mod adj_array {
         fn display_values() {
             assert_eq!("[1, 2, 3]", AdjArray::from_iter(vec![1, 2, 3]).to_string());
         }
+
+        #[test]
+        fn custom_capacity() {
+            let mut array = AdjArrayN::<12>::from_iter(0..10);
+            array.push(10);
+            array.push(11);
+
+            assert_eq!(12, array.len());
+            assert_eq!(12, AdjArrayN::<12>::CAPACITY);
+        }
+
+        #[test]
+        #[should_panic]
+        fn push_beyond_capacity() {
+            let mut array = AdjArray::from_iter(0..AdjArray::CAPACITY);
+            array.push(0);
+        }
+
+        #[test]
+        #[cfg(feature = "serde")]
+        fn serde_round_trip() {
+            let array = AdjArray::from_iter(vec![1, 2, 3]);
+
+            let json = serde_json::to_string(&array).unwrap();
+            assert_eq!("[1,2,3]", json);
+
+            assert_eq!(array, serde_json::from_str(&json).unwrap());
+        }
+
+        #[test]
+        #[cfg(feature = "serde")]
+        fn serde_too_many_neighbours() {
+            assert!(serde_json::from_str::<AdjArray>("[1,2,3,4,5,6,7,8]").is_err());
+        }
     }
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     index: usize,
     nodes: usize,
@@ -273,6 +1066,7 @@ pub mod units {
 
     /// Represents a number on the interval [0..1]
     #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ClosedUnitInterval(f64);
 
     impl ClosedUnitInterval {
@@ -288,7 +1082,10 @@ pub mod units {
 
     /// The angle φ is in the range [0..π], and represents the angle relative to the poles
     #[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-    pub struct Phi(Angle);
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Phi(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))] Angle,
+    );
 
     impl From<ClosedUnitInterval> for Phi {
         fn from(fraction: ClosedUnitInterval) -> Self {
@@ -299,7 +1096,10 @@ pub mod units {
     /// The angle θ represents the rotation of the spiral in the interval [0..Rτ]
     /// Where R is the number of rotations, as calculated from the number of nodes by the `rotations` function
     #[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-    pub struct Theta(Angle);
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Theta(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))] Angle,
+    );
 
     impl Theta {
         pub(crate) fn fraction(fraction: ClosedUnitInterval, rotations: f64) -> Self {
@@ -329,6 +1129,7 @@ pub mod units {
 
     /// Represents a point on a sphere of arbitrary radius
     #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SphericalCoordinate {
         pub phi: Phi,
         pub theta: Theta,
@@ -345,6 +1146,7 @@ pub mod units {
     }
 
     #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Position3 {
         pub x: f64,
         pub y: f64,
@@ -364,6 +1166,7 @@ pub mod units {
     }
 
     #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Distance3 {
         pub x: f64,
         pub y: f64,
@@ -389,6 +1192,7 @@ pub mod units {
     }
 
     #[derive(Debug, Default, Copy, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct AreaFactor(f64);
 
     impl Eq for AreaFactor {}
@@ -428,6 +1232,7 @@ pub mod units {
     }
 
     #[derive(Debug, Default, Copy, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct LengthFactor(f64);
 
     impl Eq for LengthFactor {}
@@ -468,9 +1273,54 @@ pub mod units {
     }
 }
 
+/// Tiles in a row, each adjacent to the tiles before and after it, for tests that need a simple layout
+#[cfg(test)]
+pub(crate) fn line(nodes: usize) -> Vec<AdjArray> {
+    use std::iter::FromIterator;
+
+    (0..nodes)
+        .map(|i| {
+            let previous = i.checked_sub(1);
+            let next = Some(i + 1).filter(|n| *n < nodes);
+            AdjArray::from_iter(previous.into_iter().chain(next))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::constants::EARTH_RADIUS;
+    use std::iter::FromIterator;
+
+    #[test]
+    fn tile_density() {
+        let earth = Length::in_m(EARTH_RADIUS);
+        let default = TileDensity::default().tile_count(earth);
+        assert_eq!(96, default);
+
+        let coarse = TileDensity::Spacing(Length::in_m(3000e3)).tile_count(earth);
+        assert!(coarse < default);
+        assert_eq!(0, coarse % STEP_SIZE);
+
+        let spacing = get_tile_spacing(earth, coarse);
+        assert!((spacing.value / 3000e3 - 1.0).abs() < 0.1, "{:?}", spacing);
+
+        let area = TileDensity::Area(Area::of_sphere(earth) / 100.0).tile_count(earth);
+        assert_eq!(100, area);
+
+        let fine = TileDensity::Spacing(Length::in_m(200e3)).tile_count(earth);
+        assert_eq!(MAX_SIZE, fine);
+
+        let tiny = TileDensity::default().tile_count(Length::in_m(1e3));
+        assert_eq!(STEP_SIZE, tiny);
+
+        let mut adjacency = Adjacency::default();
+        adjacency.register(fine);
+        adjacency.register(tiny);
+        assert_eq!(fine, adjacency.get(fine).len());
+        assert_eq!(tiny, adjacency.get(tiny).len());
+    }
 
     #[test]
     fn closed_unit_interval() {
@@ -497,7 +1347,7 @@ mod test {
         use super::get_tile_count;
 
         // earth
-        assert_eq!(96, get_tile_count(Length::in_m(6371e3)));
+        assert_eq!(96, get_tile_count(Length::in_m(EARTH_RADIUS)));
 
         // moon
         assert_eq!(24, get_tile_count(Length::in_m(1737.4e3)));
@@ -509,6 +1359,227 @@ mod test {
         assert_eq!(48, get_tile_count(Length::in_m(3389.5e3)));
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn stats() {
+        let mut adj = Adjacency::default();
+        adj.register(64);
+        adj.register(128);
+
+        let stats = adj.stats();
+
+        assert_eq!(192, stats.nodes);
+        assert!(stats.min_neighbours > 0);
+        assert!(stats.max_neighbours <= AdjArray::CAPACITY);
+        assert!(stats.mean_neighbours > 6.0 && stats.mean_neighbours < 6.2);
+        assert_eq!(stats.nodes, stats.histogram.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn goldberg_tiles() {
+        let adjacency = Goldberg.adjacency(42);
+        let count = |len: usize| adjacency.iter().filter(|a| a.len() == len).count();
+
+        assert_eq!(12, count(5));
+        assert_eq!(30, count(6));
+
+        for (i, neighbours) in adjacency.iter().enumerate() {
+            for n in neighbours.iter() {
+                assert!(adjacency[n].contains(i));
+            }
+        }
+    }
+
+    #[test]
+    fn goldberg_tile_count() {
+        assert_eq!(12, Goldberg.tile_count(0));
+        assert_eq!(92, Goldberg.tile_count(96));
+        assert_eq!(252, Goldberg.tile_count(1000));
+        assert_eq!(96, Spiral.tile_count(96));
+    }
+
+    #[test]
+    fn register_tiling() {
+        let mut adj = Adjacency::default();
+        adj.register_tiling(&Goldberg, 92);
+        adj.register(96);
+
+        assert_eq!(Goldberg.adjacency(92).as_slice(), adj.get(92));
+        assert_eq!(Goldberg.positions(92).as_slice(), adj.positions(92));
+        assert_eq!(Spiral.positions(96).as_slice(), adj.positions(96));
+
+        adj.register(96);
+        assert_eq!(Spiral.positions(96).as_slice(), adj.positions(96));
+    }
+
+    #[test]
+    #[should_panic(expected = "size registered with another tiling: 92")]
+    fn register_tiling_conflict() {
+        let mut adj = Adjacency::default();
+        adj.register(92);
+        adj.register_tiling(&Goldberg, 92);
+    }
+
+    #[test]
+    fn second_ring_neighbours() {
+        let adjacency = Goldberg.adjacency(42);
+        let rings = second_ring::<18>(&adjacency);
+
+        for (i, ring) in rings.iter().enumerate() {
+            assert!(!ring.contains(i));
+            for n in adjacency[i].iter() {
+                assert!(ring.contains(n));
+            }
+            assert!(ring.len() > adjacency[i].len());
+        }
+    }
+
+    #[test]
+    fn polar_caps() {
+        let caps = PolarCaps::default();
+        let adjacency = caps.adjacency(96);
+        let areas = caps.relative_areas(96);
+
+        assert!(adjacency[0].len() >= 4);
+        assert!(adjacency[95].len() >= 4);
+        assert!(areas[0] > areas[1]);
+        assert!((areas.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        for (i, neighbours) in adjacency.iter().enumerate() {
+            assert!(!neighbours.is_empty());
+            for n in neighbours.iter() {
+                assert!(adjacency[n].contains(i));
+            }
+        }
+
+        let positions = caps.positions(96);
+        assert_eq!(1.0, positions[0].z);
+        assert_eq!(-1.0, positions[95].z);
+    }
+
+    #[test]
+    fn path_along_line() {
+        let adjacency = line(5);
+
+        assert_eq!(vec![1, 2, 3, 4], path(1, 4, &adjacency));
+        assert_eq!(vec![2], path(2, 2, &adjacency));
+    }
+
+    #[test]
+    fn path_unreachable() {
+        let mut adjacency = line(5);
+        adjacency[4] = AdjArray::default();
+        adjacency[3] = AdjArray::from_iter(vec![2]);
+
+        assert!(path(0, 4, &adjacency).is_empty());
+    }
+
+    #[test]
+    fn path_with_cost_avoids_impassable_tiles() {
+        let nodes = 96;
+        let adjacency = Spiral.adjacency(nodes);
+        let positions = Spiral.positions(nodes);
+
+        let direct = path(0, nodes - 1, &adjacency);
+        let blocked = direct[direct.len() / 2];
+
+        let cheap = path_with_cost(0, nodes - 1, &adjacency, &positions, |_| Some(1.0));
+        let detour = path_with_cost(0, nodes - 1, &adjacency, &positions, |i| {
+            (i != blocked).then_some(1.0)
+        });
+
+        assert_eq!(Some(&0), cheap.first());
+        assert_eq!(Some(&(nodes - 1)), cheap.last());
+        assert!(!detour.is_empty());
+        assert!(!detour.contains(&blocked));
+        for pair in detour.windows(2) {
+            assert!(adjacency[pair[0]].contains(pair[1]));
+        }
+    }
+
+    #[test]
+    fn path_with_cost_unreachable() {
+        let adjacency = line(4);
+        let positions = Spiral.positions(4);
+        let path = path_with_cost(0, 3, &adjacency, &positions, |i| (i != 2).then_some(1.0));
+
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn components_split_by_predicate() {
+        let adjacency = line(7);
+        let land = |i: usize| i != 2 && i != 5;
+
+        let components = components(&adjacency, land);
+
+        assert_eq!(vec![vec![0, 1], vec![3, 4], vec![6]], components);
+    }
+
+    #[test]
+    fn components_of_whole_sphere() {
+        let adjacency = Spiral.adjacency(96);
+
+        assert_eq!(1, components(&adjacency, |_| true).len());
+        assert!(components(&adjacency, |_| false).is_empty());
+    }
+
+    #[test]
+    fn voronoi_cells_tile_the_sphere() {
+        let nodes = 96;
+        let positions = Spiral.positions(nodes);
+        let cells = Spiral.cells(nodes);
+        let dot = |a: Position3, b: Position3| a.x * b.x + a.y * b.y + a.z * b.z;
+
+        assert_eq!(nodes, cells.len());
+
+        let mut area = 0.0;
+        for (p, cell) in positions.iter().copied().zip(&cells) {
+            assert!((5..=7).contains(&cell.len()));
+
+            for (k, v) in cell.iter().copied().enumerate() {
+                // each vertex is nearest to its own tile
+                let nearest = positions.iter().map(|q| dot(v, *q)).fold(-1.0, f64::max);
+                assert!(nearest - dot(v, p) < 1e-9);
+
+                // triangle fan, with positive area when counter-clockwise
+                let w = cell[(k + 1) % cell.len()];
+                let triple = p.x * (v.y * w.z - v.z * w.y)
+                    + p.y * (v.z * w.x - v.x * w.z)
+                    + p.z * (v.x * w.y - v.y * w.x);
+                assert!(triple > 0.0);
+                area += 2.0 * triple.atan2(1.0 + dot(p, v) + dot(v, w) + dot(p, w));
+            }
+        }
+
+        assert!((area / (4.0 * std::f64::consts::PI) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn stats_empty() {
+        let stats = Adjacency::default().stats();
+
+        assert_eq!(0, stats.nodes);
+        assert_eq!(0, stats.max_neighbours);
+        assert_eq!(0.0, stats.mean_neighbours);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn adjacency_serde_is_stable() {
+        let mut adj = Adjacency::default();
+        adj.register(12);
+        adj.register(8);
+        adj.register(4);
+
+        let json = serde_json::to_string(&adj).unwrap();
+        let actual = serde_json::from_str::<Adjacency>(&json).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&actual).unwrap());
+        assert_eq!(adj.get(12), actual.get(12));
+    }
+
     #[test]
     #[cfg(not(debug_assertions))]
     fn adj_size() {
@@ -0,0 +1,280 @@
+use crate::climate::WaterVapourFeedback;
+use crate::constants::CO2_FORCING;
+use crate::solar_radiation::{Gas, GasArray, InfraredTransparency};
+use physics_types::{FluxDensity, Pressure, Temperature};
+
+/// Surface pressure and composition of a planet's atmosphere
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Atmosphere {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub pressure: Pressure,
+    /// Mole fraction of each gas
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::gas_array"))]
+    pub composition: GasArray<f64>,
+}
+
+impl Atmosphere {
+    pub fn new(pressure: Pressure, composition: GasArray<f64>) -> Self {
+        Self {
+            pressure,
+            composition,
+        }
+    }
+
+    pub fn vacuum() -> Self {
+        Self::new(Pressure::zero(), GasArray::default())
+    }
+
+    pub fn earth() -> Self {
+        let mut composition = GasArray::default();
+        composition[Gas::Nitrogen] = 0.7808;
+        composition[Gas::Oxygen] = 0.2095;
+        composition[Gas::Water] = 0.004;
+        composition[Gas::CarbonDioxide] = 0.000415;
+        composition[Gas::Methane] = 0.0000019;
+        composition[Gas::Argon] = 0.00934;
+        composition[Gas::Ozone] = 0.0000004;
+        Self::new(Pressure::in_atm(1.0), composition)
+    }
+
+    pub fn mars() -> Self {
+        let mut composition = GasArray::default();
+        composition[Gas::CarbonDioxide] = 0.9532;
+        composition[Gas::Nitrogen] = 0.027;
+        composition[Gas::Oxygen] = 0.0013;
+        composition[Gas::Argon] = 0.016;
+        Self::new(Pressure::in_atm(0.00628), composition)
+    }
+
+    pub fn venus() -> Self {
+        let mut composition = GasArray::default();
+        composition[Gas::CarbonDioxide] = 0.965;
+        composition[Gas::Nitrogen] = 0.035;
+        composition[Gas::SulfurDioxide] = 0.00015;
+        Self::new(Pressure::in_atm(92.0), composition)
+    }
+
+    pub fn titan() -> Self {
+        let mut composition = GasArray::default();
+        composition[Gas::Nitrogen] = 0.95;
+        composition[Gas::Methane] = 0.049;
+        composition[Gas::Hydrogen] = 0.001;
+        Self::new(Pressure::in_atm(1.45), composition)
+    }
+
+    /// The pressure exerted by a single gas
+    pub fn partial_pressure(&self, gas: Gas) -> Pressure {
+        let total = self.composition.iter().sum::<f64>();
+        if total == 0.0 {
+            Pressure::zero()
+        } else {
+            self.pressure * (self.composition[gas] / total)
+        }
+    }
+
+    /// Adds to or removes from the partial pressure of a gas, changing the total pressure with it
+    pub fn add_partial_pressure(&mut self, gas: Gas, change: Pressure) {
+        let mut partial = GasArray::<f64>::default();
+        for (p, g) in partial.iter_mut().zip(Gas::iter()) {
+            *p = self.partial_pressure(g).value;
+        }
+        partial[gas] = (partial[gas] + change.value).max(0.0);
+
+        let total = partial.iter().sum::<f64>();
+        self.pressure = Pressure::in_pa(total);
+        if total > 0.0 {
+            for (fraction, p) in self.composition.iter_mut().zip(partial.iter()) {
+                *fraction = p / total;
+            }
+        }
+    }
+
+    /// Fraction of UV-B sunlight that reaches the surface, see `Gas::uv_optical_depth`
+    pub fn uv_transmission(&self) -> f64 {
+        let depth = Gas::iter()
+            .map(|gas| {
+                let atm = self.partial_pressure(gas) / Pressure::in_atm(1.0);
+                atm * gas.uv_optical_depth()
+            })
+            .sum::<f64>();
+
+        (-depth).exp()
+    }
+
+    /// Fraction of outgoing infrared trapped by Earth's water vapour at 15 °C
+    const WATER_GREENHOUSE: f64 = 0.28;
+    /// The broadened CO2 pressure in atm² at which its band centre saturates,
+    /// set so that the gases other than water trap 0.12 of Earth's outgoing infrared
+    const CO2_SATURATION: f64 = 3.5e-7;
+    /// Forcing in W/m² per square root of methane's broadened pressure in ppb
+    /// https://en.wikipedia.org/wiki/Radiative_forcing#Forcing_due_to_changes_in_atmospheric_gas
+    const METHANE_FORCING: f64 = 0.036;
+    /// Forcing in W/m² per atm² of broadened ozone, giving Earth's ozone layer 7.5 W/m²
+    const OZONE_FORCING: f64 = 2.0e7;
+    /// The forcing in W/m² beyond which the weaker bands of methane and ozone saturate
+    /// and grow logarithmically, as for the methane of Titan
+    const BAND_SATURATION: f64 = 46.0;
+    /// The broadened CO2 pressure in atm² over which pressure-induced absorption
+    /// closes the remaining infrared windows, as in the deep atmosphere of Venus
+    const COLLISION_INDUCED: f64 = 100.0;
+
+    /// Radiative forcing in W/m² of a gas's infrared bands,
+    /// given its partial pressure broadened by the total pressure in atm²
+    fn band_forcing(gas: Gas, broadened: f64) -> f64 {
+        let saturating =
+            |forcing: f64| Self::BAND_SATURATION * (forcing / Self::BAND_SATURATION).ln_1p();
+
+        match gas {
+            Gas::Water => 0.0,
+            Gas::CarbonDioxide => CO2_FORCING * (broadened / Self::CO2_SATURATION).ln_1p(),
+            Gas::Ozone => saturating(Self::OZONE_FORCING * broadened),
+            // the square root of the concentration, scaled from methane by `Gas::co2_equivalence`
+            gas => {
+                let scale = gas.co2_equivalence() / Gas::Methane.co2_equivalence();
+                saturating(Self::METHANE_FORCING * scale * (broadened * 1e9).sqrt())
+            }
+        }
+    }
+
+    /// The fraction of the surface's infrared that escapes to space, for `ClimateConfig::heat_trapping`.
+    ///
+    /// CO2 traps heat logarithmically with its partial pressure broadened by the total pressure,
+    /// giving 5.35 W/m² per e-fold near Earth's composition, while methane and ozone have
+    /// their own forcing, see `band_forcing`. Water vapour adds Earth's water vapour feedback
+    /// at the given surface temperature if the atmosphere holds any water.
+    pub fn infrared_transparency(&self, surface: Temperature) -> InfraredTransparency {
+        let feedback = WaterVapourFeedback::earth();
+        let emission = FluxDensity::blackbody(feedback.reference).value;
+
+        let atm = |p: Pressure| p / Pressure::in_atm(1.0);
+        let broadened = |gas: Gas| atm(self.partial_pressure(gas)) * atm(self.pressure);
+        let gases = Gas::iter()
+            .map(|gas| Self::band_forcing(gas, broadened(gas)))
+            .sum::<f64>()
+            / emission;
+
+        let water = if self.composition[Gas::Water] > 0.0 {
+            Self::WATER_GREENHOUSE + feedback.equilibrium_forcing(surface) / emission
+        } else {
+            0.0
+        };
+
+        let windows = (-broadened(Gas::CarbonDioxide) / Self::COLLISION_INDUCED).exp();
+        let transparency = (1.0 - gases - water) * windows;
+        InfraredTransparency::new(transparency.clamp(0.01, 1.0))
+    }
+
+    /// Whether people could breathe the air unassisted
+    pub fn is_breathable(&self) -> bool {
+        let atm = |p: Pressure| p / Pressure::in_atm(1.0);
+
+        let pressure = atm(self.pressure);
+        let oxygen = atm(self.partial_pressure(Gas::Oxygen));
+        let carbon_dioxide = atm(self.partial_pressure(Gas::CarbonDioxide));
+
+        (0.5..3.0).contains(&pressure) && (0.16..0.5).contains(&oxygen) && carbon_dioxide < 0.005
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn partial_pressure() {
+        let earth = Atmosphere::earth();
+        let oxygen = earth.partial_pressure(Gas::Oxygen) / Pressure::in_atm(1.0);
+        assert!((oxygen - 0.21).abs() < 0.01);
+
+        let vacuum = Atmosphere::vacuum();
+        assert_eq!(Pressure::zero(), vacuum.partial_pressure(Gas::Oxygen));
+    }
+
+    #[test]
+    fn add_partial_pressure() {
+        let mut mars = Atmosphere::mars();
+        let before = mars.pressure - mars.partial_pressure(Gas::CarbonDioxide);
+        mars.add_partial_pressure(
+            Gas::CarbonDioxide,
+            -mars.partial_pressure(Gas::CarbonDioxide),
+        );
+
+        assert!((mars.pressure / before - 1.0).abs() < 1e-12);
+        assert_eq!(0.0, mars.composition[Gas::CarbonDioxide]);
+    }
+
+    #[test]
+    fn ozone_layer() {
+        let earth = Atmosphere::earth().uv_transmission();
+        assert!(earth > 0.01 && earth < 0.1, "{}", earth);
+        assert_eq!(1.0, Atmosphere::mars().uv_transmission());
+    }
+
+    #[test]
+    fn infrared_transparency() {
+        let reference = Temperature::in_c(15.0);
+        let transparency = |atmosphere: &Atmosphere| atmosphere.infrared_transparency(reference).0;
+        let earth = Atmosphere::earth();
+
+        assert!(
+            (transparency(&earth) - 0.6).abs() < 0.01,
+            "{}",
+            transparency(&earth)
+        );
+        assert!(transparency(&Atmosphere::mars()) > 0.85);
+        assert_eq!(0.01, transparency(&Atmosphere::venus()));
+        assert!((transparency(&Atmosphere::titan()) - 0.74).abs() < 0.01);
+        assert_eq!(1.0, transparency(&Atmosphere::vacuum()));
+
+        let warmer = earth.infrared_transparency(Temperature::in_c(20.0)).0;
+        assert!(warmer < transparency(&earth));
+    }
+
+    #[test]
+    fn doubling_co2() {
+        let reference = Temperature::in_c(15.0);
+        let mut doubled = Atmosphere::earth();
+        doubled.add_partial_pressure(
+            Gas::CarbonDioxide,
+            doubled.partial_pressure(Gas::CarbonDioxide),
+        );
+
+        let change = Atmosphere::earth().infrared_transparency(reference).0
+            - doubled.infrared_transparency(reference).0;
+        let forcing = change * FluxDensity::blackbody(reference).value;
+
+        assert!((forcing - 3.7).abs() < 0.3, "{}", forcing);
+    }
+
+    #[test]
+    fn methane_and_ozone() {
+        let reference = Temperature::in_c(15.0);
+        let forcing = |atmosphere: &Atmosphere, gas: Gas| {
+            let mut without = atmosphere.clone();
+            without.add_partial_pressure(gas, -atmosphere.partial_pressure(gas));
+
+            let change = without.infrared_transparency(reference).0
+                - atmosphere.infrared_transparency(reference).0;
+            change * FluxDensity::blackbody(reference).value
+        };
+
+        let ozone = forcing(&Atmosphere::earth(), Gas::Ozone);
+        assert!(ozone > 5.0 && ozone < 10.0, "{}", ozone);
+
+        let methane = forcing(&Atmosphere::earth(), Gas::Methane);
+        assert!(methane > 1.0 && methane < 2.0, "{}", methane);
+
+        let titan = forcing(&Atmosphere::titan(), Gas::Methane);
+        assert!(titan > 90.0 && titan < 110.0, "{}", titan);
+    }
+
+    #[test]
+    fn breathable() {
+        assert!(Atmosphere::earth().is_breathable());
+        assert!(!Atmosphere::mars().is_breathable());
+        assert!(!Atmosphere::venus().is_breathable());
+        assert!(!Atmosphere::titan().is_breathable());
+        assert!(!Atmosphere::vacuum().is_breathable());
+    }
+}
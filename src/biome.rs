@@ -0,0 +1,144 @@
+use crate::climate::ClimateSummary;
+use crate::terrain::Terrain;
+use physics_types::Temperature;
+
+/// The dominant ecosystem of a tile, from a simplified Whittaker classification
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Biome {
+    Ocean,
+    IceSheet,
+    Alpine,
+    Tundra,
+    Taiga,
+    Desert,
+    Grassland,
+    TemperateForest,
+    Savanna,
+    Rainforest,
+}
+
+impl Biome {
+    /// Elevation in metres above which land is alpine
+    pub const TREE_LINE: f64 = 3000.0;
+
+    /// Classifies a tile from its terrain and recorded climate
+    ///
+    /// # Arguments
+    ///
+    /// * `max`: the warmest recorded temperature
+    /// * `mean`: the mean temperature
+    /// * `precipitation`: annual precipitation in kg/m²/yr
+    pub fn classify(
+        terrain: &Terrain,
+        max: Temperature,
+        mean: Temperature,
+        precipitation: f64,
+    ) -> Self {
+        if terrain.glacier.f64() >= 0.5 {
+            return Biome::IceSheet;
+        }
+
+        if terrain.ocean.f64() >= 0.5 {
+            return Biome::Ocean;
+        }
+
+        if terrain.elevation.m() > Self::TREE_LINE || terrain.mountains.f64() > 0.6 {
+            return Biome::Alpine;
+        }
+
+        // no month warm enough for trees
+        if max < Temperature::in_c(10.0) {
+            return Biome::Tundra;
+        }
+
+        if mean < Temperature::in_c(5.0) {
+            return if precipitation > 400.0 {
+                Biome::Taiga
+            } else {
+                Biome::Tundra
+            };
+        }
+
+        if mean < Temperature::in_c(20.0) {
+            match precipitation {
+                p if p < 250.0 => Biome::Desert,
+                p if p < 750.0 => Biome::Grassland,
+                _ => Biome::TemperateForest,
+            }
+        } else {
+            match precipitation {
+                p if p < 250.0 => Biome::Desert,
+                p if p < 1500.0 => Biome::Savanna,
+                _ => Biome::Rainforest,
+            }
+        }
+    }
+}
+
+/// Classifies each tile from the climate recorded over at least a year of simulation
+pub fn classify_biomes(climate: &ClimateSummary, terrain: &[Terrain]) -> Vec<Biome> {
+    terrain
+        .iter()
+        .enumerate()
+        .map(|(i, terrain)| {
+            Biome::classify(
+                terrain,
+                climate.max[i],
+                climate.mean(i),
+                climate.annual_precipitation(i),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terrain::Elevation;
+    use physics_types::Duration;
+
+    fn land() -> Terrain {
+        Terrain::new(0, 20, 0).with_elevation(Elevation::in_m(200.0))
+    }
+
+    fn classify(mean: f64, precipitation: f64) -> Biome {
+        let mean = Temperature::in_c(mean);
+        Biome::classify(&land(), mean + Temperature::in_k(10.0), mean, precipitation)
+    }
+
+    #[test]
+    fn whittaker() {
+        assert_eq!(Biome::Rainforest, classify(26.0, 2500.0));
+        assert_eq!(Biome::Savanna, classify(26.0, 1000.0));
+        assert_eq!(Biome::Desert, classify(26.0, 100.0));
+        assert_eq!(Biome::TemperateForest, classify(12.0, 1000.0));
+        assert_eq!(Biome::Grassland, classify(12.0, 500.0));
+        assert_eq!(Biome::Taiga, classify(0.0, 500.0));
+        assert_eq!(Biome::Tundra, classify(-10.0, 300.0));
+    }
+
+    #[test]
+    fn terrain_overrides_climate() {
+        let max = Temperature::in_c(30.0);
+        let mean = Temperature::in_c(25.0);
+
+        let ocean = Terrain::new(255, 0, 0);
+        let ice = Terrain::new(0, 20, 200);
+        let peak = land().with_elevation(Elevation::in_m(4000.0));
+
+        assert_eq!(Biome::Ocean, Biome::classify(&ocean, max, mean, 2000.0));
+        assert_eq!(Biome::IceSheet, Biome::classify(&ice, max, mean, 2000.0));
+        assert_eq!(Biome::Alpine, Biome::classify(&peak, max, mean, 2000.0));
+    }
+
+    #[test]
+    fn classify_biomes_from_summary() {
+        let mut summary = ClimateSummary::new(2);
+        let temperature = [Temperature::in_c(26.0), Temperature::in_c(26.0)];
+        summary.record(&temperature, &[3000.0, 50.0], Duration::in_yr(1.0));
+
+        let biomes = classify_biomes(&summary, &[land(), land()]);
+        assert_eq!(vec![Biome::Rainforest, Biome::Desert], biomes);
+    }
+}
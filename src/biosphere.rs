@@ -0,0 +1,195 @@
+use crate::atmosphere::Atmosphere;
+use crate::climate::ClimateModel;
+use crate::solar_radiation::Gas;
+use crate::terrain::Terrain;
+use fractional_int::FractionalU8;
+use physics_types::{Acceleration, Duration, Pressure, Temperature};
+
+/// Plant life spreading over land where it's warm, moist, and has CO2 to grow on.
+///
+/// Plants darken the ground toward `RadiativeAbsorption::FOREST`, see `GroundCover`, and the carbon they take up
+/// as they spread, along with a small fraction of their growth that is buried each year,
+/// is drawn from the atmosphere's CO2 and returned as oxygen.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Biosphere {
+    /// Fraction of each tile's ice-free land covered by plants
+    pub vegetation: Vec<f64>,
+}
+
+impl Biosphere {
+    /// Carbon held by fully vegetated land in kg/m²
+    pub const BIOMASS: f64 = 4.0;
+    /// Carbon fixed each year by fully vegetated land in ideal conditions in kg/m²
+    pub const PRODUCTIVITY: f64 = 1.0;
+    /// Fraction of the carbon fixed each year that is buried instead of decaying back to CO2
+    pub const BURIAL: f64 = 0.001;
+    /// Time for vegetation to spread into or die back from a tile
+    pub const RESPONSE_YEARS: f64 = 20.0;
+    /// CO2 partial pressure in atm at which growth is halved
+    pub const CO2_HALF_SATURATION: f64 = 2.0e-4;
+    /// Vegetation cover planted by `seed` when starting a biosphere
+    pub const SEED: f64 = 0.01;
+
+    /// A lifeless planet with the tiles of the climate model, whose vegetation cover is then managed by `step`
+    pub fn new(climate: &ClimateModel) -> Self {
+        Self {
+            vegetation: vec![0.0; climate.temperature.len()],
+        }
+    }
+
+    /// Starts plants growing on a tile
+    pub fn seed(&mut self, tile: usize, cover: f64) {
+        self.vegetation[tile] = self.vegetation[tile].max(cover.clamp(0.0, 1.0));
+    }
+
+    /// Fraction of the tile's land where plants could grow, from 0 to 1
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature`: surface temperature of the tile
+    /// * `relative_humidity`: ratio of water vapour to what would saturate the air
+    /// * `co2`: partial pressure of CO2
+    pub fn suitability(temperature: Temperature, relative_humidity: f64, co2: Pressure) -> f64 {
+        // growth peaks at 25 °C and stops below freezing or above 45 °C
+        let c = (temperature - Temperature::in_c(0.0)).value;
+        let warmth = if c > 0.0 {
+            (1.0 - ((c - 25.0) / 20.0).powi(2)).max(0.0)
+        } else {
+            0.0
+        };
+
+        let moisture = (relative_humidity / 0.5).clamp(0.0, 1.0);
+
+        let co2 = co2 / Pressure::in_atm(1.0);
+        let carbon = co2 / (co2 + Self::CO2_HALF_SATURATION);
+
+        warmth * moisture * carbon
+    }
+
+    /// Grows or shrinks the vegetation of each tile toward what its climate supports,
+    /// exchanging the carbon with the atmosphere
+    ///
+    /// # Arguments
+    ///
+    /// * `surface_gravity`: relates the atmosphere's mass to its pressure
+    pub fn step(
+        &mut self,
+        climate: &mut ClimateModel,
+        terrain: &[Terrain],
+        atmosphere: &mut Atmosphere,
+        surface_gravity: Acceleration,
+        dt: Duration,
+    ) {
+        let years = dt / Duration::in_yr(1.0);
+        let co2 = atmosphere.partial_pressure(Gas::CarbonDioxide);
+        let rate = 1.0 - (-years / Self::RESPONSE_YEARS).exp();
+
+        // carbon taken from the air in kg/m² of the planet's surface
+        let mut fixed = 0.0;
+
+        let iter = self
+            .vegetation
+            .iter_mut()
+            .zip(climate.cover.iter_mut())
+            .zip(&climate.area)
+            .zip(terrain)
+            .zip(&climate.temperature)
+            .zip(&climate.humidity);
+
+        for (((((vegetation, cover), area), terrain), temp), humidity) in iter {
+            let land = (terrain.plains + terrain.mountains - terrain.glacier).f64();
+            let surface = terrain.surface_temperature(*temp);
+            let suitability = Self::suitability(surface, *humidity, co2);
+
+            // plants only spread from where they already grow
+            let target = if *vegetation > 0.0 { suitability } else { 0.0 };
+            let change = (target - *vegetation) * rate;
+            let productivity = Self::PRODUCTIVITY * *vegetation * suitability * years;
+
+            *vegetation += change;
+            fixed += area * land * (change * Self::BIOMASS + productivity * Self::BURIAL);
+
+            cover.vegetation = FractionalU8::new_f64(*vegetation);
+        }
+
+        climate.update_ground();
+
+        // CO2 + H2O -> CH2O + O2, by mass of the carbon
+        let removed = Pressure::in_pa(fixed * surface_gravity.value * 44.0 / 12.0).min(co2);
+        let released = removed * (32.0 / 44.0);
+
+        atmosphere.add_partial_pressure(Gas::CarbonDioxide, -removed);
+        atmosphere.add_partial_pressure(Gas::Oxygen, released);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::climate::ClimateConfig;
+
+    fn gravity() -> Acceleration {
+        Acceleration::in_m_per_s2(9.81)
+    }
+
+    const TILES: usize = 10;
+
+    fn model(temperature: Temperature) -> (ClimateModel, Biosphere) {
+        let climate = ClimateModel::new(ClimateConfig::earth(), TILES, temperature);
+        let biosphere = Biosphere::new(&climate);
+        (climate, biosphere)
+    }
+
+    #[test]
+    fn suitability() {
+        let co2 = Pressure::in_atm(0.0004);
+        let suitability = |c: f64| Biosphere::suitability(Temperature::in_c(c), 0.6, co2);
+
+        assert!(suitability(25.0) > suitability(10.0));
+        assert_eq!(0.0, suitability(-5.0));
+        assert_eq!(0.0, suitability(50.0));
+        assert_eq!(
+            0.0,
+            Biosphere::suitability(Temperature::in_c(25.0), 0.6, Pressure::zero())
+        );
+    }
+
+    #[test]
+    fn plants_spread_and_make_oxygen() {
+        let land = [Terrain::new(0, 50, 0); TILES];
+        let mut atmosphere = Atmosphere::earth();
+        let (mut climate, mut biosphere) = model(Temperature::in_c(20.0));
+        let co2 = atmosphere.partial_pressure(Gas::CarbonDioxide);
+        let oxygen = atmosphere.partial_pressure(Gas::Oxygen);
+        let bare = climate.ground[0];
+
+        biosphere.seed(0, 0.01);
+        for _ in 0..100 {
+            let dt = Duration::in_yr(1.0);
+            biosphere.step(&mut climate, &land, &mut atmosphere, gravity(), dt);
+        }
+
+        assert!(biosphere.vegetation[0] > 0.5);
+        assert_eq!(0.0, biosphere.vegetation[1]);
+        assert!(climate.ground[0] > bare);
+        assert_eq!(bare, climate.ground[1]);
+        assert!(atmosphere.partial_pressure(Gas::CarbonDioxide) < co2);
+        assert!(atmosphere.partial_pressure(Gas::Oxygen) > oxygen);
+    }
+
+    #[test]
+    fn frozen_plants_die_back() {
+        let land = [Terrain::new(0, 50, 0); TILES];
+        let mut atmosphere = Atmosphere::earth();
+        let (mut climate, mut biosphere) = model(Temperature::in_c(-10.0));
+
+        biosphere.seed(0, 0.8);
+        for _ in 0..100 {
+            let dt = Duration::in_yr(1.0);
+            biosphere.step(&mut climate, &land, &mut atmosphere, gravity(), dt);
+        }
+
+        assert!(biosphere.vegetation[0] < 0.01);
+    }
+}
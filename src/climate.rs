@@ -0,0 +1,2393 @@
+use crate::adjacency::units::Position3;
+use crate::adjacency::AdjArray;
+use crate::atmosphere::Atmosphere;
+use crate::constants::{CO2_FORCING, SIGMA};
+use crate::currents::OceanCurrents;
+use crate::hydrology::Hydrology;
+use crate::insolation::annual_mean_intensity;
+use crate::solar_radiation::{InfraredTransparency, RadiativeAbsorption};
+use crate::statistics::WeightedStats;
+use crate::terrain::Terrain;
+use fractional_int::FractionalU8;
+use physics_types::{Angle, Area, Duration, FluxDensity, Temperature};
+
+/// Parameters of the per-tile energy balance
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClimateConfig {
+    /// Absorption of bare land, before ice, water, and clouds are accounted for.
+    /// Used for every tile unless overridden with `ClimateModel::ground`.
+    pub ground_absorption: RadiativeAbsorption,
+    pub clouds: CloudModel,
+    pub heat_trapping: InfraredTransparency,
+    pub emissivity: f64,
+    /// Heat conducted between neighbouring tiles through the atmosphere in W/m²/K
+    pub atmospheric_conductance: f64,
+    /// Additional heat conducted between neighbouring tiles through shared ocean in W/m²/K
+    pub ocean_conductance: f64,
+    /// Greenhouse warming from water vapour evaporated by warmer oceans, if enabled
+    pub water_vapour: Option<WaterVapourFeedback>,
+    /// A layer beneath the surface that stores heat between day and night, if enabled
+    pub subsurface: Option<SubsurfaceConfig>,
+    /// Dust lifted into the air during storm seasons, if enabled
+    pub dust: Option<DustConfig>,
+}
+
+impl ClimateConfig {
+    /// Calibrated to a Bond albedo of 0.30, see `ClimateModel::bond_albedo`
+    pub fn earth() -> Self {
+        Self {
+            ground_absorption: RadiativeAbsorption::new(0.77),
+            clouds: CloudModel::Fixed(FractionalU8::new_f64(0.24)),
+            heat_trapping: InfraredTransparency::new(0.6),
+            emissivity: 0.93643,
+            atmospheric_conductance: 2.0,
+            ocean_conductance: 10.0,
+            water_vapour: None,
+            subsurface: None,
+            dust: None,
+        }
+    }
+
+    pub fn mars() -> Self {
+        Self {
+            ground_absorption: RadiativeAbsorption::new(0.75),
+            clouds: CloudModel::Fixed(FractionalU8::default()),
+            heat_trapping: InfraredTransparency::new(0.91),
+            emissivity: 0.9,
+            atmospheric_conductance: 0.3,
+            ocean_conductance: 0.0,
+            water_vapour: None,
+            subsurface: Some(SubsurfaceConfig::regolith()),
+            dust: Some(DustConfig::mars()),
+        }
+    }
+
+    /// Sulfuric acid clouds cover the whole planet, and the thick CO2 closes the infrared windows,
+    /// see `Atmosphere::venus`
+    pub fn venus() -> Self {
+        Self {
+            ground_absorption: RadiativeAbsorption::new(0.9),
+            clouds: CloudModel::Fixed(FractionalU8::new(u8::MAX)),
+            heat_trapping: InfraredTransparency::new(0.01),
+            emissivity: 0.97,
+            atmospheric_conductance: 5.0,
+            ocean_conductance: 0.0,
+            water_vapour: None,
+            subsurface: None,
+            dust: None,
+        }
+    }
+
+    /// Dark organic sediment under a thick, cold atmosphere warmed by methane, see `Atmosphere::titan`
+    pub fn titan() -> Self {
+        Self {
+            ground_absorption: RadiativeAbsorption::new(0.9),
+            clouds: CloudModel::Fixed(FractionalU8::default()),
+            heat_trapping: InfraredTransparency::new(0.74),
+            emissivity: 0.85,
+            atmospheric_conductance: 2.0,
+            ocean_conductance: 0.0,
+            water_vapour: None,
+            subsurface: None,
+            dust: None,
+        }
+    }
+
+    /// Bare regolith radiating straight to space, as on the Moon or Mercury
+    pub fn airless() -> Self {
+        Self {
+            ground_absorption: RadiativeAbsorption::new(0.9),
+            clouds: CloudModel::Fixed(FractionalU8::default()),
+            heat_trapping: InfraredTransparency::new(1.0),
+            emissivity: 0.95,
+            atmospheric_conductance: 0.0,
+            ocean_conductance: 0.0,
+            water_vapour: None,
+            subsurface: Some(SubsurfaceConfig::regolith()),
+            dust: None,
+        }
+    }
+
+    /// Adjusts the heat trapping for a change in CO2 concentration,
+    /// converting the logarithmic forcing to a change in emission at the reference temperature
+    pub fn with_co2_ratio(mut self, ratio: f64, reference: Temperature) -> Self {
+        assert!(ratio > 0.0, "CO2 ratio must be positive: {}", ratio);
+
+        let forcing = CO2_FORCING * ratio.ln();
+        let emission = (FluxDensity::blackbody(reference) * self.emissivity).value;
+        let trapping = (self.heat_trapping.0 - forcing / emission).clamp(0.01, 1.0);
+
+        self.heat_trapping = InfraredTransparency::new(trapping);
+        self
+    }
+
+    /// Traps heat according to the gases of the atmosphere, see `Atmosphere::infrared_transparency`
+    pub fn with_atmosphere(mut self, atmosphere: &Atmosphere, surface: Temperature) -> Self {
+        self.heat_trapping = atmosphere.infrared_transparency(surface);
+        self
+    }
+
+    pub fn with_water_vapour(mut self, feedback: WaterVapourFeedback) -> Self {
+        self.water_vapour = Some(feedback);
+        self
+    }
+
+    pub fn with_subsurface(mut self, subsurface: SubsurfaceConfig) -> Self {
+        self.subsurface = Some(subsurface);
+        self
+    }
+
+    pub fn with_dust(mut self, dust: DustConfig) -> Self {
+        self.dust = Some(dust);
+        self
+    }
+}
+
+/// Heat storage and conduction of the layer beneath one kind of surface
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layer {
+    /// Heat capacity of the layer in J/K/m²
+    pub heat_capacity: f64,
+    /// Heat conducted between the surface and the layer in W/m²/K
+    pub conductance: f64,
+}
+
+/// A second layer beneath each tile, mixed from the land and ocean layers by the tile's ocean fraction.
+/// Heat soaked up during the day is returned at night, which slows night-side cooling on rocky bodies.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubsurfaceConfig {
+    pub land: Layer,
+    pub ocean: Layer,
+}
+
+impl SubsurfaceConfig {
+    /// Loose, insulating dust over rock, as on Mars
+    pub fn regolith() -> Self {
+        Self {
+            land: Layer {
+                heat_capacity: 2.0e6,
+                conductance: 0.3,
+            },
+            ocean: Self::DEEP_OCEAN,
+        }
+    }
+
+    /// Bare or thinly covered rock, which conducts heat well
+    pub fn rock() -> Self {
+        Self {
+            land: Layer {
+                heat_capacity: 4.0e6,
+                conductance: 2.0,
+            },
+            ocean: Self::DEEP_OCEAN,
+        }
+    }
+
+    /// Ocean below the mixed layer, mixed slowly with the surface
+    pub const DEEP_OCEAN: Layer = Layer {
+        heat_capacity: 1.0e8,
+        conductance: 0.5,
+    };
+
+    /// The heat capacity and conductance of the layer under a tile
+    pub fn layer(&self, terrain: &Terrain) -> Layer {
+        let ocean = terrain.ocean.f64();
+        let land = 1.0 - ocean;
+        Layer {
+            heat_capacity: land * self.land.heat_capacity + ocean * self.ocean.heat_capacity,
+            conductance: land * self.land.conductance + ocean * self.ocean.conductance,
+        }
+    }
+}
+
+/// Water vapour follows the mean ocean temperature by Clausius–Clapeyron scaling,
+/// and traps heat logarithmically with its concentration
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaterVapourFeedback {
+    /// Mean ocean temperature at which the configured heat trapping applies
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub reference: Temperature,
+    /// Radiative forcing from an e-fold increase in water vapour in W/m²
+    pub forcing: f64,
+    /// Largest forcing in either direction in W/m², which keeps the feedback from running away
+    pub limit: f64,
+    /// Time for the vapour to adjust to a change in ocean temperature
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub response: Duration,
+}
+
+impl WaterVapourFeedback {
+    /// About 1.8 W/m²/K of feedback, adjusting over the residence time of water vapour
+    pub fn earth() -> Self {
+        Self {
+            reference: Temperature::in_c(15.0),
+            forcing: 25.0,
+            limit: 30.0,
+            response: Duration::in_d(Hydrology::RESIDENCE_TIME),
+        }
+    }
+
+    /// The forcing that vapour in equilibrium with the ocean would provide, in W/m²
+    pub fn equilibrium_forcing(&self, ocean_temperature: Temperature) -> f64 {
+        let delta = (ocean_temperature - self.reference).value;
+        let vapour = Hydrology::CLAUSIUS_CLAPEYRON * delta;
+        (self.forcing * vapour).clamp(-self.limit, self.limit)
+    }
+}
+
+/// Dust lifted by the wind when sunlight is strongest, as during the Martian storm season near perihelion.
+/// Dust beyond the background loading reflects sunlight and blocks the infrared escaping to space,
+/// cooling the days and warming the nights, and settles out over a few weeks.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DustConfig {
+    /// Visible optical depth of the dust that is always in the air, already counted in the configured absorption
+    pub background: f64,
+    /// Stellar flux above which the wind lifts dust
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub storm_flux: FluxDensity,
+    /// Optical depth lifted per day per fraction by which the flux exceeds `storm_flux`
+    pub lifting: f64,
+    /// Time for the dust beyond the background to settle out by a factor of e
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub settling: Duration,
+    /// Fraction of the sunlight intercepted by the dust that never warms the ground,
+    /// being reflected to space or absorbed and re-emitted high in the atmosphere
+    pub albedo: f64,
+    /// Ratio of the dust's infrared optical depth to its visible optical depth
+    pub infrared_ratio: f64,
+}
+
+impl DustConfig {
+    /// Storms near perihelion raise the optical depth from about 0.5 to several
+    pub fn mars() -> Self {
+        Self {
+            background: 0.5,
+            storm_flux: FluxDensity::in_w_per_m2(650.0),
+            lifting: 0.8,
+            settling: Duration::in_d(20.0),
+            albedo: 0.6,
+            infrared_ratio: 0.2,
+        }
+    }
+
+    /// Lifts dust while the flux is above `storm_flux`, and lets it settle toward the background,
+    /// returning the new optical depth
+    pub fn update(&self, dust: f64, flux: FluxDensity, dt: Duration) -> f64 {
+        let excess = (flux.value / self.storm_flux.value - 1.0).max(0.0);
+        let dust = dust + self.lifting * excess * (dt / Duration::in_d(1.0));
+
+        let settled = (-(dt / self.settling)).exp();
+        self.background + (dust - self.background) * settled
+    }
+
+    /// Optical depth of the dust beyond the background loading
+    fn excess(&self, dust: f64) -> f64 {
+        (dust - self.background).max(0.0)
+    }
+
+    /// Fraction of sunlight not reflected to space by dust beyond the background loading
+    pub fn transmission(&self, dust: f64) -> f64 {
+        1.0 - self.albedo * (1.0 - (-self.excess(dust)).exp())
+    }
+
+    /// The heat trapping with the infrared blocked by dust beyond the background loading
+    pub fn heat_trapping(&self, dust: f64, trapping: InfraredTransparency) -> InfraredTransparency {
+        let excess = self.excess(dust);
+        if excess > 0.0 {
+            let blocked = (-self.infrared_ratio * excess).exp();
+            InfraredTransparency::new((trapping.0 * blocked).clamp(0.01, 1.0))
+        } else {
+            trapping
+        }
+    }
+}
+
+/// How cloud cover is found for each tile, from cheapest to most responsive
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CloudModel {
+    /// The same cover everywhere, e.g., 0.24 for Earth
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
+    Fixed(FractionalU8),
+    /// Thinner cover over cold tiles than over warm ones, tuned on Earth's dry polar and moist tropical air
+    Threshold {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+        threshold: Temperature,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
+        cold: FractionalU8,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
+        warm: FractionalU8,
+    },
+    /// Cover proportional to relative humidity, tuned on Earth where 0.5 relative humidity gives 0.24 cover
+    Humidity { scale: f64 },
+    /// Cover from relative humidity, thickened over open ocean where evaporation feeds rising moist air,
+    /// and thinned over cold tiles whose air holds little water
+    Evaporation {
+        scale: f64,
+        /// Additional cover over open ocean relative to land
+        ocean: f64,
+    },
+}
+
+impl CloudModel {
+    pub fn earth_threshold() -> Self {
+        CloudModel::Threshold {
+            threshold: Temperature::in_c(0.0),
+            cold: FractionalU8::new_f64(0.17),
+            warm: FractionalU8::new_f64(0.27),
+        }
+    }
+
+    pub fn earth_humidity() -> Self {
+        CloudModel::Humidity { scale: 0.48 }
+    }
+
+    /// Tuned on Earth where 0.5 relative humidity at 15 °C over 70% ocean gives 0.24 cover
+    pub fn earth_evaporation() -> Self {
+        CloudModel::Evaporation {
+            scale: 0.36,
+            ocean: 0.5,
+        }
+    }
+
+    /// Cloud cover over a tile
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature`: sea-level temperature of the tile
+    /// * `relative_humidity`: ratio of water vapour to what would saturate the air
+    pub fn cover(
+        &self,
+        terrain: &Terrain,
+        temperature: Temperature,
+        relative_humidity: f64,
+    ) -> FractionalU8 {
+        match *self {
+            CloudModel::Fixed(cover) => cover,
+            CloudModel::Threshold {
+                threshold,
+                cold,
+                warm,
+            } => {
+                if temperature < threshold {
+                    cold
+                } else {
+                    warm
+                }
+            }
+            CloudModel::Evaporation { scale, ocean } => {
+                let open_ocean = (!terrain.glacier).min(terrain.ocean).f64();
+
+                // saturation rises by about 7% per kelvin, so warm air has more water to condense
+                let capacity = (0.07 * (temperature - Temperature::in_c(15.0)).value).exp();
+                let warmth = 2.0 * capacity / (1.0 + capacity);
+
+                let cover = scale * relative_humidity * (1.0 + ocean * open_ocean) * warmth;
+                FractionalU8::new_f64(cover.clamp(0.0, 1.0))
+            }
+            CloudModel::Humidity { scale } => {
+                FractionalU8::new_f64((scale * relative_humidity).clamp(0.0, 1.0))
+            }
+        }
+    }
+}
+
+/// Plants and frost laid over the bare ground of a tile, with frost on top
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroundCover {
+    /// Fraction of the ground covered by plants, see `Biosphere`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
+    pub vegetation: FractionalU8,
+    /// Fraction of the ground covered by frost, see `FrostModel`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
+    pub frost: FractionalU8,
+}
+
+impl GroundCover {
+    /// The absorption of the bare ground seen through the cover
+    pub fn over(&self, bare: RadiativeAbsorption) -> RadiativeAbsorption {
+        let plants = (RadiativeAbsorption::FOREST * self.vegetation).add(bare * !self.vegetation);
+        (RadiativeAbsorption::ICE * self.frost).add(plants * !self.frost)
+    }
+}
+
+/// Surface temperature of each tile, advanced by balancing absorbed sunlight against emission
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClimateModel {
+    pub config: ClimateConfig,
+    /// Sea-level temperature of each tile, see `Terrain::surface_temperature`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub temperature: Vec<Temperature>,
+    /// Absorption of the land on each tile, the bare ground seen through its cover
+    pub ground: Vec<RadiativeAbsorption>,
+    /// Absorption of bare land on each tile, e.g., basalt or sand
+    pub bare: Vec<RadiativeAbsorption>,
+    /// Plants and frost over the bare land of each tile, see `update_ground`
+    pub cover: Vec<GroundCover>,
+    /// Relative humidity of each tile, used by `CloudModel::Humidity`, see `Hydrology::relative_humidity`
+    pub humidity: Vec<f64>,
+    /// Sunlight absorbed by each tile during the last step
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub shortwave: Vec<FluxDensity>,
+    /// Infrared emitted to space by each tile during the last step
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub longwave: Vec<FluxDensity>,
+    /// Current forcing from water vapour in W/m², see `ClimateConfig::water_vapour`
+    pub vapour_forcing: f64,
+    /// Current visible optical depth of dust in the air, see `ClimateConfig::dust`
+    pub dust: f64,
+    /// Heat rising through the surface of each tile from the interior, e.g., from `TidalHeating`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub internal_heat: Vec<FluxDensity>,
+    /// Temperature of the layer beneath each tile, see `ClimateConfig::subsurface`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub subsurface: Vec<Temperature>,
+    /// Energy flows during the last step if auditing is enabled, see `with_audit`
+    pub audit: Option<EnergyAudit>,
+    /// Separate temperatures for the land and ocean of each tile if enabled, see `with_split`
+    pub split: Option<SplitTemperature>,
+    /// Carries heat along the coasts if set, see `with_currents`
+    pub currents: Option<OceanCurrents>,
+    /// Fraction of the planet's surface covered by each tile, for area-weighted totals such as
+    /// `bond_albedo`. Equal for every tile, as in `get_tile_area`, unless replaced with `with_area`.
+    pub area: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    transfer: Vec<Temperature>,
+    /// Heat carried by the currents in J/m², see `OceanCurrents::advect_heat`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    current_buffer: Vec<f64>,
+    /// Sunlight arriving at and absorbed by the planet during the last step,
+    /// averaged over its surface in W/m²
+    incident: f64,
+    absorbed: f64,
+}
+
+impl ClimateModel {
+    /// Tile count from which steps are spread across threads with the `rayon` feature
+    pub const PARALLEL_TILES: usize = 128;
+
+    pub fn new(config: ClimateConfig, tiles: usize, initial: Temperature) -> Self {
+        Self {
+            config,
+            temperature: vec![initial; tiles],
+            ground: vec![config.ground_absorption; tiles],
+            bare: vec![config.ground_absorption; tiles],
+            cover: vec![GroundCover::default(); tiles],
+            humidity: vec![0.5; tiles],
+            shortwave: vec![FluxDensity::in_w_per_m2(0.0); tiles],
+            longwave: vec![FluxDensity::in_w_per_m2(0.0); tiles],
+            vapour_forcing: 0.0,
+            dust: config.dust.map_or(0.0, |dust| dust.background),
+            internal_heat: vec![FluxDensity::in_w_per_m2(0.0); tiles],
+            subsurface: vec![initial; tiles],
+            audit: None,
+            split: None,
+            currents: None,
+            area: vec![1.0 / tiles as f64; tiles],
+            transfer: vec![Temperature::default(); tiles],
+            current_buffer: vec![],
+            incident: 0.0,
+            absorbed: 0.0,
+        }
+    }
+
+    /// Replaces the bare ground absorption of each tile
+    pub fn with_ground(mut self, ground: Vec<RadiativeAbsorption>) -> Self {
+        assert_eq!(self.temperature.len(), ground.len());
+        self.bare = ground;
+        self.update_ground();
+        self
+    }
+
+    /// Lays the cover of each tile over its bare ground, after changing either
+    pub fn update_ground(&mut self) {
+        let iter = self.ground.iter_mut().zip(&self.bare).zip(&self.cover);
+        for ((ground, bare), cover) in iter {
+            *ground = cover.over(*bare);
+        }
+    }
+
+    /// Replaces the fraction of the surface covered by each tile, e.g., from `Tiling::relative_areas`
+    pub fn with_area(mut self, area: Vec<f64>) -> Self {
+        assert_eq!(self.temperature.len(), area.len());
+        self.area = area;
+        self
+    }
+
+    /// Replaces the heat rising through each tile from the interior
+    pub fn with_internal_heat(mut self, internal_heat: Vec<FluxDensity>) -> Self {
+        assert_eq!(self.temperature.len(), internal_heat.len());
+        self.internal_heat = internal_heat;
+        self
+    }
+
+    /// Fraction of the difference from the global mean absorbed sunlight evened out by heat transport
+    /// in `initialize_analytic`, similar to Earth's
+    pub const ANALYTIC_TRANSPORT: f64 = 0.5;
+
+    /// Sets each tile to a first guess at its annual mean temperature, which is much closer to the
+    /// simulated climate than a uniform temperature and so needs less time to spin up.
+    /// Balances the annual mean sunlight at each latitude against emission,
+    /// with part of the difference between latitudes evened out as if by heat transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `positions`: unit vector of each tile, with the rotation axis along z
+    /// * `flux`: stellar flux density at the planet's distance
+    pub fn initialize_analytic(
+        &mut self,
+        terrain: &[Terrain],
+        positions: &[Position3],
+        axial_tilt: Angle,
+        flux: FluxDensity,
+    ) {
+        let config = &self.config;
+        let reference = Temperature::in_c(15.0);
+
+        let absorbed = terrain
+            .iter()
+            .zip(positions)
+            .zip(&self.ground)
+            .zip(&self.humidity)
+            .map(|(((terrain, position), ground), humidity)| {
+                let latitude = position.z.clamp(-1.0, 1.0).asin();
+                let clouds = config.clouds.cover(terrain, reference, *humidity);
+                let absorption = terrain.absorption(*ground, clouds);
+                flux.value * annual_mean_intensity(latitude, axial_tilt) * absorption.0
+            })
+            .collect::<Vec<_>>();
+
+        let mean = absorbed
+            .iter()
+            .zip(&self.area)
+            .map(|(a, area)| a * area)
+            .sum::<f64>();
+        let emission = SIGMA * self.heat_trapping().0 * config.emissivity;
+
+        let iter = self
+            .temperature
+            .iter_mut()
+            .zip(absorbed)
+            .zip(&self.internal_heat);
+
+        for ((temp, absorbed), internal) in iter {
+            let absorbed = absorbed + (mean - absorbed) * Self::ANALYTIC_TRANSPORT + internal.value;
+            *temp = Temperature::in_k((absorbed / emission).powf(0.25));
+        }
+
+        self.subsurface.clone_from(&self.temperature);
+    }
+
+    /// Largest number of sweeps over the tiles made by `solve_equilibrium`
+    pub const EQUILIBRIUM_SWEEPS: usize = 1000;
+
+    /// Sets each tile to the temperature at which absorbed sunlight and heat from its neighbours
+    /// balance emission, so that `step` leaves it unchanged for the same sunlight.
+    /// Water vapour forcing is set to its equilibrium with the resulting ocean.
+    ///
+    /// Each sweep takes a Newton step on the flux balance of every tile in turn,
+    /// stopping when no tile changes by more than a microkelvin.
+    /// Returns the number of sweeps, or `None` if the balance hadn't converged after `EQUILIBRIUM_SWEEPS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `flux`: stellar flux density at the planet's distance
+    /// * `intensity`: the mean intensity of sunlight on each tile, e.g., from `insolation::annual_mean_intensity`
+    pub fn solve_equilibrium(
+        &mut self,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        flux: FluxDensity,
+        intensity: &[f64],
+    ) -> Option<usize> {
+        for sweep in 1..=Self::EQUILIBRIUM_SWEEPS {
+            if let (Some(feedback), Some(ocean)) =
+                (self.config.water_vapour, self.ocean_temperature(terrain))
+            {
+                self.vapour_forcing = feedback.equilibrium_forcing(ocean);
+            }
+
+            let emission = SIGMA * self.heat_trapping().0 * self.config.emissivity;
+            let dust = self.dust_transmission();
+            let config = &self.config;
+            let mut largest = 0.0f64;
+
+            for i in 0..self.temperature.len() {
+                let temp = self.temperature[i];
+                let clouds = config.clouds.cover(&terrain[i], temp, self.humidity[i]);
+                let ra = terrain[i].absorption(self.ground[i], clouds);
+                let absorbed =
+                    flux.value * intensity[i] * dust * ra.0.powf(intensity[i].recip().powf(0.678));
+
+                // heat shared along an edge spreads over the whole tile
+                let scale = self.relative_area(i).recip();
+                let mut transfer = 0.0;
+                let mut conductance = 0.0;
+                for n in adjacency[i].iter() {
+                    let c = edge_conductance(config, terrain, adjacency, i, n) * scale;
+                    transfer += c * (self.temperature[n] - temp).value;
+                    conductance += c;
+                }
+
+                let t = temp.value;
+                let heating = absorbed + self.internal_heat[i].value;
+                let balance = heating + transfer - emission * t.powi(4);
+                let slope = conductance + 4.0 * emission * t.powi(3);
+
+                let next = (t + balance / slope).max(1.0);
+                largest = largest.max((next - t).abs());
+                self.temperature[i] = Temperature::in_k(next);
+            }
+
+            if largest < 1e-6 {
+                self.subsurface.clone_from(&self.temperature);
+                return Some(sweep);
+            }
+        }
+
+        None
+    }
+
+    /// Records the energy flows of each step in `audit`, at the cost of two extra passes over the tiles
+    pub fn with_audit(mut self) -> Self {
+        self.audit = Some(EnergyAudit::default());
+        self
+    }
+
+    /// Tracks the land and ocean of mixed tiles separately in `split`, starting from each tile's temperature
+    pub fn with_split(mut self) -> Self {
+        self.split = Some(SplitTemperature {
+            land_temp: self.temperature.clone(),
+            ocean_temp: self.temperature.clone(),
+        });
+        self
+    }
+
+    /// Carries heat downstream along the given ocean currents during each step
+    pub fn with_currents(mut self, currents: OceanCurrents) -> Self {
+        self.currents = Some(currents);
+        self
+    }
+
+    /// Advances the temperature of each tile
+    ///
+    /// # Arguments
+    ///
+    /// * `flux`: stellar flux density at the planet's distance
+    /// * `intensity`: the cosine of the sun's zenith angle for each tile, zero at night
+    pub fn step(
+        &mut self,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        flux: FluxDensity,
+        intensity: &[f64],
+        dt: Duration,
+    ) {
+        let parallel = self.temperature.len() >= Self::PARALLEL_TILES;
+        self.step_with(terrain, adjacency, flux, intensity, dt, parallel);
+    }
+
+    /// Advances the temperature of each tile as in `step`,
+    /// spreading the tiles across threads if `parallel` and the `rayon` feature is enabled
+    fn step_with(
+        &mut self,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        flux: FluxDensity,
+        intensity: &[f64],
+        dt: Duration,
+        #[cfg_attr(not(feature = "rayon"), allow(unused_variables))] parallel: bool,
+    ) {
+        let initial = self.audit.map(|_| self.stored_energy(terrain));
+        let before = self.split.as_ref().map(|_| self.temperature.clone());
+
+        self.update_water_vapour(terrain, dt);
+        self.update_dust(flux, dt);
+        let heat_trapping = self.heat_trapping();
+
+        let radiation = Radiation {
+            config: &self.config,
+            heat_trapping,
+            dust_transmission: self.dust_transmission(),
+            flux,
+            dt,
+        };
+
+        let temperature = &mut self.temperature;
+        let ground = &self.ground;
+        let humidity = &self.humidity;
+        let shortwave = &mut self.shortwave;
+        let longwave = &mut self.longwave;
+
+        macro_rules! tiles {
+            ($iter:ident, $iter_mut:ident) => {
+                temperature
+                    .$iter_mut()
+                    .zip(terrain.$iter())
+                    .zip(intensity.$iter())
+                    .zip(ground.$iter())
+                    .zip(humidity.$iter())
+                    .zip(shortwave.$iter_mut().zip(longwave.$iter_mut()))
+                    .for_each(
+                        |(((((temp, terrain), intensity), ground), humidity), budget)| {
+                            radiation.apply(temp, terrain, *intensity, *ground, *humidity, budget)
+                        },
+                    )
+            };
+        }
+
+        #[cfg(feature = "rayon")]
+        if parallel {
+            use rayon::prelude::*;
+            tiles!(par_iter, par_iter_mut);
+        } else {
+            tiles!(iter, iter_mut);
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        tiles!(iter, iter_mut);
+
+        let area = &self.area;
+        self.incident = (intensity.iter().zip(area))
+            .map(|(intensity, area)| flux.value * intensity * area)
+            .sum();
+        self.absorbed = (self.shortwave.iter().zip(area))
+            .map(|(absorbed, area)| absorbed.value * area)
+            .sum();
+
+        let iter = self
+            .temperature
+            .iter_mut()
+            .zip(&self.internal_heat)
+            .zip(terrain);
+
+        for ((temp, internal), terrain) in iter {
+            *temp += *internal * Area::in_m2(1.0) * dt / terrain.heat_capacity();
+        }
+
+        self.exchange_subsurface(terrain, dt);
+
+        let radiated = initial.map(|_| self.stored_energy(terrain));
+
+        self.transfer_heat(terrain, adjacency, dt);
+
+        if let Some(currents) = &self.currents {
+            let buffer = &mut self.current_buffer;
+            currents.advect_heat(&mut self.temperature, buffer, terrain, adjacency, dt);
+        }
+
+        if let (Some(split), Some(before)) = (&mut self.split, before) {
+            split.step(&self.temperature, &before, terrain, dt);
+        }
+
+        if let (Some(initial), Some(radiated)) = (initial, radiated) {
+            let seconds = dt / Duration::in_s(1.0);
+            let sum = |flux: &[FluxDensity]| {
+                let tiles = flux.len() as f64;
+                let weighted = flux.iter().zip(&self.area).map(|(f, a)| f.value * a);
+                weighted.sum::<f64>() * tiles * seconds
+            };
+
+            self.audit = Some(EnergyAudit {
+                absorbed: sum(&self.shortwave),
+                internal: sum(&self.internal_heat),
+                emitted: sum(&self.longwave),
+                transported: self.stored_energy(terrain) - radiated,
+                stored: self.stored_energy(terrain) - initial,
+            });
+        }
+    }
+
+    /// Conducts heat between the surface and the layer beneath,
+    /// relaxing both toward their shared temperature so that long steps remain stable
+    fn exchange_subsurface(&mut self, terrain: &[Terrain], dt: Duration) {
+        let config = match self.config.subsurface {
+            Some(config) => config,
+            None => return,
+        };
+        let seconds = dt / Duration::in_s(1.0);
+
+        let iter = self
+            .temperature
+            .iter_mut()
+            .zip(self.subsurface.iter_mut())
+            .zip(terrain);
+
+        for ((surface, subsurface), terrain) in iter {
+            let layer = config.layer(terrain);
+            let surface_capacity = terrain.heat_capacity().value;
+
+            let total = surface_capacity + layer.heat_capacity;
+            let shared =
+                (surface.value * surface_capacity + subsurface.value * layer.heat_capacity) / total;
+            let rate =
+                layer.conductance * seconds * total / (surface_capacity * layer.heat_capacity);
+            let relaxation = 1.0 - (-rate).exp();
+
+            *surface += Temperature::in_k((shared - surface.value) * relaxation);
+            *subsurface += Temperature::in_k((shared - subsurface.value) * relaxation);
+        }
+    }
+
+    /// Heat held by all tiles relative to absolute zero, including any subsurface layer,
+    /// in J per m² of each tile, weighted by `relative_area`
+    fn stored_energy(&self, terrain: &[Terrain]) -> f64 {
+        let surface = self
+            .temperature
+            .iter()
+            .zip(terrain)
+            .enumerate()
+            .map(|(i, (temp, terrain))| {
+                temp.value * terrain.heat_capacity().value * self.relative_area(i)
+            })
+            .sum::<f64>();
+
+        let subsurface = match self.config.subsurface {
+            Some(config) => self
+                .subsurface
+                .iter()
+                .zip(terrain)
+                .enumerate()
+                .map(|(i, (temp, terrain))| {
+                    temp.value * config.layer(terrain).heat_capacity * self.relative_area(i)
+                })
+                .sum(),
+            None => 0.0,
+        };
+
+        surface + subsurface
+    }
+
+    /// Area of the tile relative to the mean tile, one unless replaced with `with_area`
+    fn relative_area(&self, tile: usize) -> f64 {
+        self.area[tile] * self.area.len() as f64
+    }
+
+    /// Advances the temperature of each tile by the step chosen by `stepper`, returning the step taken.
+    /// Steps that change any tile by more than the threshold are repeated with a shorter step
+    /// until they don't or the step reaches its lower bound.
+    pub fn step_adaptive(
+        &mut self,
+        stepper: &mut AdaptiveStep,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        flux: FluxDensity,
+        intensity: &[f64],
+    ) -> Duration {
+        let initial = self.temperature.clone();
+        let subsurface = self.subsurface.clone();
+        let split = self.split.clone();
+        let vapour_forcing = self.vapour_forcing;
+        let dust = self.dust;
+
+        loop {
+            let dt = stepper.dt;
+            self.step(terrain, adjacency, flux, intensity, dt);
+
+            let change = self
+                .temperature
+                .iter()
+                .zip(&initial)
+                .map(|(temp, initial)| (*temp - *initial).value.abs())
+                .fold(0.0, f64::max);
+
+            stepper.adapt(change);
+
+            if change <= stepper.threshold.value || dt <= stepper.min {
+                return dt;
+            }
+
+            self.temperature.copy_from_slice(&initial);
+            self.subsurface.copy_from_slice(&subsurface);
+            self.split.clone_from(&split);
+            self.vapour_forcing = vapour_forcing;
+            self.dust = dust;
+        }
+    }
+
+    /// The fraction of sunlight reflected by the planet during the last step,
+    /// weighting each tile by its `area`, or `None` if no tile was lit
+    pub fn bond_albedo(&self) -> Option<f64> {
+        if self.incident > 0.0 {
+            Some(1.0 - self.absorbed / self.incident)
+        } else {
+            None
+        }
+    }
+
+    /// Relaxes the water vapour forcing toward equilibrium with the mean ocean temperature
+    fn update_water_vapour(&mut self, terrain: &[Terrain], dt: Duration) {
+        let feedback = match self.config.water_vapour {
+            Some(feedback) => feedback,
+            None => return,
+        };
+
+        if let Some(mean) = self.ocean_temperature(terrain) {
+            let target = feedback.equilibrium_forcing(mean);
+            let rate = 1.0 - (-(dt / feedback.response)).exp();
+            self.vapour_forcing += (target - self.vapour_forcing) * rate;
+        }
+    }
+
+    /// Lifts dust while the flux is above the storm threshold, and lets it settle toward the background
+    fn update_dust(&mut self, flux: FluxDensity, dt: Duration) {
+        if let Some(config) = self.config.dust {
+            self.dust = config.update(self.dust, flux, dt);
+        }
+    }
+
+    /// Adds dust to the air, e.g., for a `crate::storms::StormKind::GlobalDustStorm`.
+    /// Has no effect on the climate unless `ClimateConfig::dust` is set.
+    pub fn raise_dust(&mut self, optical_depth: f64) {
+        self.dust += optical_depth.max(0.0);
+    }
+
+    /// Fraction of sunlight not reflected to space by dust, see `DustConfig::transmission`
+    fn dust_transmission(&self) -> f64 {
+        self.config
+            .dust
+            .map_or(1.0, |config| config.transmission(self.dust))
+    }
+
+    /// Mean temperature of the ocean, or `None` without any ocean
+    pub fn ocean_temperature(&self, terrain: &[Terrain]) -> Option<Temperature> {
+        let mut ocean = WeightedStats::default();
+        for (temp, terrain) in self.temperature.iter().zip(terrain) {
+            ocean.add(temp.value, terrain.ocean.f64());
+        }
+        ocean.mean().map(Temperature::in_k)
+    }
+
+    /// The configured heat trapping, reduced by any water vapour forcing and dust beyond the background
+    pub fn heat_trapping(&self) -> InfraredTransparency {
+        let trapping = self.vapour_trapping();
+        match self.config.dust {
+            Some(config) => config.heat_trapping(self.dust, trapping),
+            None => trapping,
+        }
+    }
+
+    /// The configured heat trapping, reduced by any water vapour forcing
+    pub(crate) fn vapour_trapping(&self) -> InfraredTransparency {
+        match self.config.water_vapour {
+            Some(feedback) if self.vapour_forcing != 0.0 => {
+                let emission = FluxDensity::blackbody(feedback.reference) * self.config.emissivity;
+                let trapping = self.config.heat_trapping.0 - self.vapour_forcing / emission.value;
+                InfraredTransparency::new(trapping.clamp(0.01, 1.0))
+            }
+            _ => self.config.heat_trapping,
+        }
+    }
+
+    /// Absorbed sunlight less emitted infrared for the tile during the last step,
+    /// positive when the tile is warming before heat is exchanged with its neighbours
+    pub fn net_radiation(&self, tile: usize) -> FluxDensity {
+        self.shortwave[tile] - self.longwave[tile]
+    }
+
+    /// Exchanges heat with neighbouring tiles, with oceans carrying more heat than the atmosphere.
+    /// Computes one flux for each edge that leaves one tile and enters the other,
+    /// so that no heat is gained or lost. Assumes the adjacency is symmetric.
+    /// The heat is spread over each tile, so it changes the temperature of larger tiles less.
+    fn transfer_heat(&mut self, terrain: &[Terrain], adjacency: &[AdjArray], dt: Duration) {
+        let seconds = dt / Duration::in_s(1.0);
+
+        self.transfer.clear();
+        self.transfer
+            .resize(self.temperature.len(), Temperature::default());
+
+        for (i, neighbours) in adjacency.iter().enumerate() {
+            for n in neighbours.iter().filter(|n| *n > i) {
+                let conductance = edge_conductance(&self.config, terrain, adjacency, i, n);
+                let difference = (self.temperature[n] - self.temperature[i]).value;
+
+                // J/m² of the mean tile carried from n to i
+                let energy = conductance * difference * seconds;
+                let capacity_i = terrain[i].heat_capacity().value * self.relative_area(i);
+                let capacity_n = terrain[n].heat_capacity().value * self.relative_area(n);
+                self.transfer[i] += Temperature::in_k(energy / capacity_i);
+                self.transfer[n] += Temperature::in_k(-energy / capacity_n);
+            }
+        }
+
+        for (temp, transfer) in self.temperature.iter_mut().zip(self.transfer.iter()) {
+            *temp += *transfer;
+        }
+    }
+}
+
+/// Conductance in W/m²/K along the edge between neighbouring tiles `i` and `n`, the same from either side.
+/// Each tile's conductance is shared among its neighbours, using the mean neighbour count of the two tiles.
+fn edge_conductance(
+    config: &ClimateConfig,
+    terrain: &[Terrain],
+    adjacency: &[AdjArray],
+    i: usize,
+    n: usize,
+) -> f64 {
+    let ocean = 0.5 * (terrain[i].ocean.f64() + terrain[n].ocean.f64());
+    let edges = 0.5 * (adjacency[i].len() + adjacency[n].len()) as f64;
+    (config.atmospheric_conductance + config.ocean_conductance * ocean) / edges
+}
+
+/// Sea-level temperatures of the land and ocean within each tile, which differ on coasts
+/// because the ocean holds far more heat, see `Terrain::heat_capacity`.
+/// Their mean weighted by heat capacity is always the tile's temperature.
+/// A tile without land or ocean gives that part the tile's temperature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitTemperature {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub land_temp: Vec<Temperature>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub ocean_temp: Vec<Temperature>,
+}
+
+impl SplitTemperature {
+    /// Heat exchanged between the land and ocean of a tile in W/m²/K, as by sea breezes
+    pub const EXCHANGE: f64 = 5.0;
+
+    /// Shares the heat each tile gained during a step equally between each square metre of land and ocean,
+    /// then exchanges heat between them
+    fn step(
+        &mut self,
+        temperature: &[Temperature],
+        before: &[Temperature],
+        terrain: &[Terrain],
+        dt: Duration,
+    ) {
+        let seconds = dt / Duration::in_s(1.0);
+        let land_capacity = Terrain::LAND_HEAT_CAPACITY;
+        let ocean_capacity = Terrain::OCEAN_HEAT_CAPACITY;
+
+        let iter = self
+            .land_temp
+            .iter_mut()
+            .zip(self.ocean_temp.iter_mut())
+            .zip(temperature.iter().zip(before))
+            .zip(terrain);
+
+        for (((land, ocean), (temp, before)), terrain) in iter {
+            let ocean_fraction = terrain.ocean.f64();
+            let land_fraction = 1.0 - ocean_fraction;
+            let capacity = terrain.heat_capacity().value;
+
+            // J/m² gained by the tile
+            let gained = (*temp - *before).value * capacity;
+            let mut land_value = land.value + gained / land_capacity;
+            let mut ocean_value = ocean.value + gained / ocean_capacity;
+
+            let land_heat = land_fraction * land_capacity;
+            let ocean_heat = ocean_fraction * ocean_capacity;
+            if land_heat > 0.0 && ocean_heat > 0.0 {
+                let shared = (land_value * land_heat + ocean_value * ocean_heat) / capacity;
+                let rate = Self::EXCHANGE * seconds * capacity / (land_heat * ocean_heat);
+                let relaxation = 1.0 - (-rate).exp();
+                land_value += (shared - land_value) * relaxation;
+                ocean_value += (shared - ocean_value) * relaxation;
+            }
+
+            // keep the parts consistent with the tile, which other processes may have changed
+            let mean = (land_value * land_heat + ocean_value * ocean_heat) / capacity;
+            let offset = temp.value - mean;
+
+            *land = Temperature::in_k(if land_heat > 0.0 {
+                land_value + offset
+            } else {
+                temp.value
+            });
+            *ocean = Temperature::in_k(if ocean_heat > 0.0 {
+                ocean_value + offset
+            } else {
+                temp.value
+            });
+        }
+    }
+}
+
+/// Energy flows of all tiles during one step in J per m² of each tile,
+/// summed over the tiles weighted by their area relative to the mean tile
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnergyAudit {
+    /// Sunlight absorbed by the surface
+    pub absorbed: f64,
+    /// Heat rising from the interior
+    pub internal: f64,
+    /// Infrared emitted to space
+    pub emitted: f64,
+    /// Net heat gained through exchange between tiles, which is zero if the exchange conserves energy
+    pub transported: f64,
+    /// Change in heat held by the tiles
+    pub stored: f64,
+}
+
+impl EnergyAudit {
+    /// Heat gained or lost beyond what was absorbed and emitted, zero if the model conserves energy
+    pub fn imbalance(&self) -> f64 {
+        self.stored - (self.absorbed + self.internal - self.emitted)
+    }
+
+    /// The imbalance as a fraction of the energy gained and lost
+    pub fn relative_imbalance(&self) -> f64 {
+        let exchanged = self.absorbed + self.internal + self.emitted;
+        self.imbalance() / exchanged.max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Chooses the time step of `ClimateModel::step_adaptive` from how quickly temperatures are changing,
+/// taking long steps while the climate is settled and short steps while it is changing quickly
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveStep {
+    /// The next step to try
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub dt: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub min: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub max: Duration,
+    /// The largest change in any tile's temperature accepted in one step
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub threshold: Temperature,
+}
+
+impl AdaptiveStep {
+    /// Starts from the shortest step
+    pub fn new(min: Duration, max: Duration, threshold: Temperature) -> Self {
+        assert!(min > Duration::in_s(0.0) && min <= max);
+        assert!(threshold > Temperature::in_k(0.0));
+
+        Self {
+            dt: min,
+            min,
+            max,
+            threshold,
+        }
+    }
+
+    /// Scales the next step toward the threshold after a step that changed temperatures by `change` K,
+    /// by at most a factor of two either way
+    fn adapt(&mut self, change: f64) {
+        let ratio = self.threshold.value / change.max(f64::MIN_POSITIVE);
+        let factor = (0.9 * ratio).clamp(0.5, 2.0);
+        let dt = (self.dt.value * factor).clamp(self.min.value, self.max.value);
+        self.dt = Duration::in_s(dt);
+    }
+}
+
+/// The inputs shared by every tile during a step
+struct Radiation<'a> {
+    config: &'a ClimateConfig,
+    heat_trapping: InfraredTransparency,
+    /// Fraction of sunlight passing the dust, see `ClimateModel::dust_transmission`
+    dust_transmission: f64,
+    flux: FluxDensity,
+    dt: Duration,
+}
+
+impl Radiation<'_> {
+    /// Balances absorbed sunlight against emission for one tile
+    fn apply(
+        &self,
+        temp: &mut Temperature,
+        terrain: &Terrain,
+        intensity: f64,
+        ground: RadiativeAbsorption,
+        humidity: f64,
+        (shortwave, longwave): (&mut FluxDensity, &mut FluxDensity),
+    ) {
+        let clouds = self.config.clouds.cover(terrain, *temp, humidity);
+        let ra = terrain.absorption(ground, clouds);
+
+        // oblique light passes through more atmosphere and is reflected more
+        let absorbed = self.flux
+            * (intensity * self.dust_transmission)
+            * ra.0.powf(intensity.recip().powf(0.678));
+        let emitted = FluxDensity::blackbody(*temp) * self.heat_trapping * self.config.emissivity;
+
+        *shortwave = absorbed;
+        *longwave = emitted;
+
+        let d_energy = (absorbed - emitted) * Area::in_m2(1.0) * self.dt;
+        *temp += d_energy / terrain.heat_capacity();
+    }
+}
+
+/// The change in mean equilibrium temperature when CO2 is multiplied by `co2_ratio`,
+/// e.g., a ratio of 2 gives the equilibrium climate sensitivity
+///
+/// # Arguments
+///
+/// * `intensity`: the mean intensity of sunlight on each tile, held constant while equilibrating
+pub fn climate_sensitivity(
+    config: ClimateConfig,
+    terrain: &[Terrain],
+    adjacency: &[AdjArray],
+    flux: FluxDensity,
+    intensity: &[f64],
+    co2_ratio: f64,
+) -> Temperature {
+    let equilibrium = |config: ClimateConfig, initial: Temperature| {
+        let mut model = ClimateModel::new(config, terrain.len(), initial);
+        let dt = Duration::in_hr(6.0);
+        let mut mean = initial;
+
+        // 20 years is enough for the ocean's mixed layer to settle
+        for _ in 0..20 * 365 * 4 {
+            model.step(terrain, adjacency, flux, intensity, dt);
+
+            let previous = mean;
+            mean = model.temperature.iter().copied().sum::<Temperature>() / terrain.len() as f64;
+
+            if (mean - previous).value.abs() < 1e-7 {
+                break;
+            }
+        }
+
+        mean
+    };
+
+    let baseline = equilibrium(config, Temperature::in_c(15.0));
+    let forced = equilibrium(config.with_co2_ratio(co2_ratio, baseline), baseline);
+
+    forced - baseline
+}
+
+/// Climate along a path of tiles, weighted by tile area
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathClimate {
+    /// The coldest temperature recorded on any tile
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub min: Temperature,
+    /// The hottest temperature recorded on any tile
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub max: Temperature,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mean: Temperature,
+    /// Mean precipitation in kg/m²/yr
+    pub precipitation: f64,
+    /// Mean sunlight at the top of the atmosphere
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub insolation: FluxDensity,
+}
+
+/// Per-tile temperature extremes and precipitation recorded over a simulation
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClimateSummary {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub min: Vec<Temperature>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantities"))]
+    pub max: Vec<Temperature>,
+    /// Total precipitation in kg/m²
+    pub precipitation: Vec<f64>,
+    /// Sum of the recorded temperatures in K, weighted by time step
+    total: Vec<f64>,
+    /// Total sunlight reaching the top of the atmosphere in J/m²
+    insolation: Vec<f64>,
+    /// Sums of the land and ocean temperatures in K like `total`, empty unless `record_split` is called
+    land_total: Vec<f64>,
+    ocean_total: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    duration: Duration,
+}
+
+impl ClimateSummary {
+    pub fn new(tiles: usize) -> Self {
+        Self {
+            min: vec![Temperature::in_k(f64::INFINITY); tiles],
+            max: vec![Temperature::in_k(f64::NEG_INFINITY); tiles],
+            precipitation: vec![0.0; tiles],
+            total: vec![0.0; tiles],
+            insolation: vec![0.0; tiles],
+            land_total: vec![],
+            ocean_total: vec![],
+            duration: Duration::default(),
+        }
+    }
+
+    /// Adds a step of surface temperatures and the precipitation that fell during it
+    pub fn record(&mut self, temperature: &[Temperature], precipitation: &[f64], dt: Duration) {
+        let seconds = dt / Duration::in_s(1.0);
+
+        for (i, temp) in temperature.iter().enumerate() {
+            self.min[i] = self.min[i].min(*temp);
+            self.max[i] = self.max[i].max(*temp);
+            self.total[i] += temp.value * seconds;
+        }
+
+        for (total, precipitation) in self.precipitation.iter_mut().zip(precipitation) {
+            *total += precipitation;
+        }
+
+        self.duration += dt;
+    }
+
+    /// Adds the sunlight falling on each tile during a step, see `ClimateModel::step`.
+    /// Call alongside `record` so the mean is taken over the same duration.
+    pub fn record_insolation(&mut self, flux: FluxDensity, intensity: &[f64], dt: Duration) {
+        let seconds = dt / Duration::in_s(1.0);
+
+        for (total, intensity) in self.insolation.iter_mut().zip(intensity) {
+            *total += flux.value * intensity * seconds;
+        }
+    }
+
+    /// Adds the land and ocean temperatures of each tile during a step, see `SplitTemperature`.
+    /// Call alongside `record` so the mean is taken over the same duration.
+    pub fn record_split(&mut self, land: &[Temperature], ocean: &[Temperature], dt: Duration) {
+        let seconds = dt / Duration::in_s(1.0);
+        let add = |totals: &mut Vec<f64>, temperature: &[Temperature]| {
+            totals.resize(temperature.len(), 0.0);
+            for (total, temp) in totals.iter_mut().zip(temperature) {
+                *total += temp.value * seconds;
+            }
+        };
+
+        add(&mut self.land_total, land);
+        add(&mut self.ocean_total, ocean);
+    }
+
+    /// Time-weighted mean temperature of the tile's land, or `None` if it wasn't recorded
+    pub fn land_mean(&self, tile: usize) -> Option<Temperature> {
+        let total = self.land_total.get(tile)?;
+        Some(Temperature::in_k(
+            total / (self.duration / Duration::in_s(1.0)),
+        ))
+    }
+
+    /// Time-weighted mean temperature of the tile's ocean, or `None` if it wasn't recorded
+    pub fn ocean_mean(&self, tile: usize) -> Option<Temperature> {
+        let total = self.ocean_total.get(tile)?;
+        Some(Temperature::in_k(
+            total / (self.duration / Duration::in_s(1.0)),
+        ))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Time-averaged sunlight on the tile
+    pub fn mean_insolation(&self, tile: usize) -> FluxDensity {
+        FluxDensity::in_w_per_m2(self.insolation[tile] / (self.duration / Duration::in_s(1.0)))
+    }
+
+    /// Aggregates the climate of the tiles along a path, e.g., for a structure spanning several tiles
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: the tiles covered, with repeated tiles counted each time
+    /// * `area`: the relative area of each tile, all equal for the spiral layout
+    pub fn along_path(&self, path: &[usize], area: &[f64]) -> Option<PathClimate> {
+        let mut temperature = WeightedStats::default();
+        let mut min = WeightedStats::default();
+        let mut max = WeightedStats::default();
+        let mut precipitation = WeightedStats::default();
+        let mut insolation = WeightedStats::default();
+
+        for &tile in path {
+            let weight = area[tile];
+            temperature.add(self.mean(tile).value, weight);
+            min.add(self.min[tile].value, weight);
+            max.add(self.max[tile].value, weight);
+            precipitation.add(self.annual_precipitation(tile), weight);
+            insolation.add(self.mean_insolation(tile).value, weight);
+        }
+
+        Some(PathClimate {
+            min: Temperature::in_k(min.min()?),
+            max: Temperature::in_k(max.max()?),
+            mean: Temperature::in_k(temperature.mean()?),
+            precipitation: precipitation.mean()?,
+            insolation: FluxDensity::in_w_per_m2(insolation.mean()?),
+        })
+    }
+
+    /// Time-weighted mean temperature of the tile
+    pub fn mean(&self, tile: usize) -> Temperature {
+        Temperature::in_k(self.total[tile] / (self.duration / Duration::in_s(1.0)))
+    }
+
+    /// Mean precipitation of the tile in kg/m²/yr
+    pub fn annual_precipitation(&self, tile: usize) -> f64 {
+        self.precipitation[tile] * (Duration::in_yr(1.0) / self.duration)
+    }
+
+    /// Mean precipitation of every tile in kg/m²/yr, e.g., for `Biome::classify` or `export::precipitation_color`
+    pub fn precipitation_map(&self) -> Vec<f64> {
+        (0..self.precipitation.len())
+            .map(|i| self.annual_precipitation(i))
+            .collect()
+    }
+}
+
+/// A year of surface temperatures on each tile, see `ClimateModel::annual_summary`
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnualSummary {
+    /// Temperature extremes and means, without precipitation as the water cycle isn't simulated
+    pub climate: ClimateSummary,
+    /// Days with a minimum temperature below freezing
+    pub frost_days: Vec<u32>,
+    /// Days with a mean temperature of at least `GROWING_TEMPERATURE`
+    pub growing_season: Vec<u32>,
+}
+
+impl AnnualSummary {
+    /// Daily mean temperature at which plants grow
+    pub const GROWING_TEMPERATURE: Temperature = Temperature::in_c(5.0);
+}
+
+impl ClimateModel {
+    /// Simulates a year and summarizes the surface temperature of each tile
+    ///
+    /// # Arguments
+    ///
+    /// * `year`: the length of the year, divided into days of 24 hours
+    /// * `dt`: the time step, which should divide a day
+    /// * `sunlight`: fills in the intensity of sunlight on each tile at a time since the start of the year,
+    ///   returning the stellar flux density, e.g., from `insolation::intensity`
+    pub fn annual_summary(
+        &mut self,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        year: Duration,
+        dt: Duration,
+        mut sunlight: impl FnMut(Duration, &mut [f64]) -> FluxDensity,
+    ) -> AnnualSummary {
+        let tiles = self.temperature.len();
+        let steps = (year / dt).round().max(1.0) as usize;
+        let steps_per_day = (Duration::in_d(1.0) / dt).round().max(1.0) as usize;
+        let freezing = Temperature::in_c(0.0);
+
+        let mut summary = AnnualSummary {
+            climate: ClimateSummary::new(tiles),
+            frost_days: vec![0; tiles],
+            growing_season: vec![0; tiles],
+        };
+
+        let mut intensity = vec![0.0; tiles];
+        let mut surface = vec![Temperature::default(); tiles];
+        let no_precipitation = vec![0.0; tiles];
+        let mut day_min = vec![Temperature::in_k(f64::INFINITY); tiles];
+        let mut day_total = vec![0.0; tiles];
+        let mut day_steps = 0;
+
+        for step in 0..steps {
+            let flux = sunlight(dt * step as f64, &mut intensity);
+            self.step(terrain, adjacency, flux, &intensity, dt);
+
+            for (i, surface) in surface.iter_mut().enumerate() {
+                *surface = terrain[i].surface_temperature(self.temperature[i]);
+                day_min[i] = day_min[i].min(*surface);
+                day_total[i] += surface.value;
+            }
+            day_steps += 1;
+
+            summary.climate.record(&surface, &no_precipitation, dt);
+            summary.climate.record_insolation(flux, &intensity, dt);
+
+            if day_steps == steps_per_day || step + 1 == steps {
+                for i in 0..tiles {
+                    let mean = Temperature::in_k(day_total[i] / day_steps as f64);
+                    summary.frost_days[i] += (day_min[i] < freezing) as u32;
+                    summary.growing_season[i] +=
+                        (mean >= AnnualSummary::GROWING_TEMPERATURE) as u32;
+
+                    day_min[i] = Temperature::in_k(f64::INFINITY);
+                    day_total[i] = 0.0;
+                }
+                day_steps = 0;
+            }
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Only heat transfer between tiles
+    fn insulated() -> ClimateConfig {
+        ClimateConfig {
+            emissivity: 0.0,
+            ..ClimateConfig::earth()
+        }
+    }
+
+    fn pair() -> Vec<AdjArray> {
+        use std::iter::FromIterator;
+        vec![AdjArray::from_iter(vec![1]), AdjArray::from_iter(vec![0])]
+    }
+
+    fn spread_after_a_day(config: ClimateConfig, terrain: &[Terrain]) -> Temperature {
+        let mut model = ClimateModel::new(config, 2, Temperature::in_c(0.0));
+        model.temperature[1] = Temperature::in_c(20.0);
+
+        for _ in 0..24 {
+            let flux = FluxDensity::in_w_per_m2(0.0);
+            model.step(terrain, &pair(), flux, &[0.0, 0.0], Duration::in_hr(1.0));
+        }
+
+        model.temperature[1] - model.temperature[0]
+    }
+
+    #[test]
+    fn neighbours_converge() {
+        let land = Terrain::new(0, 50, 0);
+        let spread = spread_after_a_day(insulated(), &[land, land]);
+
+        assert!(spread < Temperature::in_k(20.0));
+        assert!(spread > Temperature::in_k(0.0));
+    }
+
+    #[test]
+    fn ocean_conductance() {
+        let coast = Terrain::new(200, 20, 0);
+        let still_ocean = ClimateConfig {
+            ocean_conductance: 0.0,
+            ..insulated()
+        };
+
+        let with_currents = spread_after_a_day(insulated(), &[coast, coast]);
+        let without_currents = spread_after_a_day(still_ocean, &[coast, coast]);
+
+        assert!(with_currents < without_currents);
+    }
+
+    #[test]
+    fn maritime_moderation() {
+        let land = Terrain::new(0, 50, 0);
+        let ocean = Terrain::new(255, 0, 0);
+
+        let mut model = ClimateModel::new(insulated(), 2, Temperature::in_c(0.0));
+        model.temperature[0] = Temperature::in_c(20.0);
+        model.step(
+            &[land, ocean],
+            &pair(),
+            FluxDensity::in_w_per_m2(0.0),
+            &[0.0, 0.0],
+            Duration::in_hr(1.0),
+        );
+
+        let land_change = Temperature::in_c(20.0) - model.temperature[0];
+        let ocean_change = model.temperature[1] - Temperature::in_c(0.0);
+
+        assert!(land_change > ocean_change * 5.0);
+    }
+
+    #[test]
+    fn darker_ground_is_warmer() {
+        let land = Terrain::new(0, 50, 0);
+        let ground = vec![RadiativeAbsorption::FOREST, RadiativeAbsorption::CONCRETE];
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 2, Temperature::in_c(15.0))
+            .with_ground(ground);
+
+        for _ in 0..24 {
+            let flux = FluxDensity::in_w_per_m2(1361.0);
+            let adjacency = [AdjArray::default(), AdjArray::default()];
+            model.step(
+                &[land, land],
+                &adjacency,
+                flux,
+                &[1.0, 1.0],
+                Duration::in_hr(1.0),
+            );
+        }
+
+        assert!(model.temperature[0] > model.temperature[1]);
+    }
+
+    #[test]
+    fn energy_budget_per_tile() {
+        let land = Terrain::new(0, 50, 0);
+        let adjacency = [AdjArray::default(), AdjArray::default()];
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 2, Temperature::in_c(15.0));
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        model.step(
+            &[land, land],
+            &adjacency,
+            flux,
+            &[1.0, 0.0],
+            Duration::in_hr(1.0),
+        );
+
+        assert!(model.shortwave[0] > model.longwave[0]);
+        assert_eq!(0.0, model.shortwave[1].value);
+        assert!(model.longwave[1].value > 0.0);
+        assert!(model.net_radiation(0).value > 0.0);
+        assert!(model.net_radiation(1).value < 0.0);
+    }
+
+    fn warm_ocean(config: ClimateConfig) -> ClimateModel {
+        let ocean = Terrain::new(255, 0, 0);
+        let mut model = ClimateModel::new(config, 1, Temperature::in_c(25.0));
+
+        // enough sunlight to hold the ocean above the feedback's reference temperature
+        for _ in 0..30 {
+            let flux = FluxDensity::in_w_per_m2(1361.0);
+            model.step(
+                &[ocean],
+                &[AdjArray::default()],
+                flux,
+                &[0.3],
+                Duration::in_d(1.0),
+            );
+        }
+
+        model
+    }
+
+    #[test]
+    fn water_vapour_amplifies_warm_oceans() {
+        let dry = warm_ocean(ClimateConfig::earth());
+        let humid =
+            warm_ocean(ClimateConfig::earth().with_water_vapour(WaterVapourFeedback::earth()));
+
+        assert_eq!(0.0, dry.vapour_forcing);
+        assert!(humid.vapour_forcing > 0.0);
+        assert!(humid.heat_trapping() < dry.heat_trapping());
+        assert!(humid.temperature[0] > dry.temperature[0]);
+    }
+
+    #[test]
+    fn water_vapour_forcing_is_limited() {
+        let feedback = WaterVapourFeedback::earth();
+
+        assert_eq!(
+            feedback.limit,
+            feedback.equilibrium_forcing(Temperature::in_c(200.0))
+        );
+        assert_eq!(
+            -feedback.limit,
+            feedback.equilibrium_forcing(Temperature::in_c(-200.0))
+        );
+        assert_eq!(0.0, feedback.equilibrium_forcing(feedback.reference));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_step_matches_serial() {
+        use crate::adjacency::{Spiral, Tiling};
+
+        let tiles = 256;
+        assert!(tiles >= ClimateModel::PARALLEL_TILES);
+
+        let adjacency = Spiral.adjacency(tiles);
+        let terrain = (0..tiles)
+            .map(|i| Terrain::new((i * 37 % 256) as u8, 0, 0))
+            .collect::<Vec<_>>();
+        let intensity = (0..tiles)
+            .map(|i| (i as f64 * 0.1).sin().max(0.0))
+            .collect::<Vec<_>>();
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        let mut serial = ClimateModel::new(ClimateConfig::earth(), tiles, Temperature::in_c(15.0));
+        let mut parallel = serial.clone();
+
+        for _ in 0..24 {
+            let dt = Duration::in_hr(1.0);
+            serial.step_with(&terrain, &adjacency, flux, &intensity, dt, false);
+            parallel.step(&terrain, &adjacency, flux, &intensity, dt);
+        }
+
+        assert_eq!(serial.temperature, parallel.temperature);
+    }
+
+    #[test]
+    fn analytic_initialization() {
+        use crate::adjacency::{Spiral, Tiling};
+
+        let n = 48;
+        let positions = Spiral.positions(n);
+        let terrain = vec![Terrain::new(200, 20, 0); n];
+        let mut model = ClimateModel::new(ClimateConfig::earth(), n, Temperature::in_c(15.0));
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        model.initialize_analytic(&terrain, &positions, Angle::in_deg(23.44), flux);
+
+        let equator = (0..n).min_by(|a, b| positions[*a].z.abs().total_cmp(&positions[*b].z.abs()));
+        let pole = (0..n).max_by(|a, b| positions[*a].z.abs().total_cmp(&positions[*b].z.abs()));
+        assert!(model.temperature[equator.unwrap()] > model.temperature[pole.unwrap()]);
+
+        let mean = model.temperature.iter().copied().sum::<Temperature>() / n as f64;
+        assert!(mean > Temperature::in_c(-10.0) && mean < Temperature::in_c(35.0));
+    }
+
+    #[test]
+    fn equilibrium_is_unchanged_by_stepping() {
+        let terrain = [Terrain::new(0, 50, 0), Terrain::new(200, 20, 0)];
+        let intensity = [1.0, 0.3];
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 2, Temperature::in_c(15.0));
+
+        assert!(model
+            .solve_equilibrium(&terrain, &pair(), flux, &intensity)
+            .is_some());
+        let solved = model.temperature.clone();
+        let dt = Duration::in_hr(1.0);
+
+        // radiation alone moves each tile by the heat its neighbour supplies over the step
+        let mut isolated = model.clone();
+        let alone = [AdjArray::default(), AdjArray::default()];
+        isolated.step(&terrain, &alone, flux, &intensity, dt);
+        model.step(&terrain, &pair(), flux, &intensity, dt);
+
+        // the transfer applied in turn undoes that shift to within O(dt²),
+        // the largest shift scaled by dt over the fastest tile's response time
+        let shift = (0..2)
+            .map(|i| (isolated.temperature[i] - solved[i]).value.abs())
+            .fold(0.0, f64::max);
+        let response = (0..2)
+            .map(|i| {
+                let scale = model.relative_area(i).recip();
+                let conductance =
+                    edge_conductance(&model.config, &terrain, &pair(), i, 1 - i) * scale;
+                let radiative = 4.0 * emission(&model.config, solved[i]) / solved[i].value;
+                terrain[i].heat_capacity().value / (conductance + radiative)
+            })
+            .fold(f64::INFINITY, f64::min);
+        let tolerance = shift * dt.value / response;
+
+        assert!(solved[0] > solved[1]);
+        for (solved, stepped) in solved.iter().zip(&model.temperature) {
+            let drift = (*solved - *stepped).value.abs();
+            assert!(drift < tolerance, "{} > {}", drift, tolerance);
+        }
+    }
+
+    #[test]
+    fn polar_caps_change_the_equilibrium() {
+        use crate::adjacency::{PolarCaps, Tiling};
+
+        const N: usize = 48;
+        let caps = PolarCaps::new(0.2);
+        let adjacency = caps.adjacency(N);
+        let terrain = vec![Terrain::new(0, 50, 0); N];
+        let intensity = caps
+            .positions(N)
+            .iter()
+            .map(|p| annual_mean_intensity(p.z.asin(), Angle::in_deg(23.4)))
+            .collect::<Vec<_>>();
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        let mut ground = vec![ClimateConfig::earth().ground_absorption; N];
+        ground[0] = RadiativeAbsorption::ICE;
+        ground[N - 1] = RadiativeAbsorption::ICE;
+
+        let solve = |area: Vec<f64>| {
+            let mut model = ClimateModel::new(ClimateConfig::earth(), N, Temperature::in_c(15.0))
+                .with_ground(ground.clone())
+                .with_area(area);
+            assert!(model
+                .solve_equilibrium(&terrain, &adjacency, flux, &intensity)
+                .is_some());
+            model
+        };
+        let mean = |model: &ClimateModel| {
+            let weighted = model.temperature.iter().zip(&model.area);
+            weighted.map(|(t, a)| t.value * a).sum::<f64>()
+        };
+
+        let equal = solve(vec![1.0 / N as f64; N]);
+        let capped = solve(caps.relative_areas(N));
+
+        // the heat reaching a cap across its rim is spread over more ice
+        assert!(capped.temperature[0] < equal.temperature[0]);
+        // and the icy caps cover more of the planet
+        assert!(
+            mean(&capped) + 1.0 < mean(&equal),
+            "{} {}",
+            mean(&capped),
+            mean(&equal)
+        );
+    }
+
+    #[test]
+    fn equilibrium_of_an_isolated_tile() {
+        let config = ClimateConfig {
+            clouds: CloudModel::Fixed(FractionalU8::default()),
+            ..ClimateConfig::earth()
+        };
+        let (mut model, terrain) = isolated_tile(config, Temperature::in_c(-50.0));
+        let flux = FluxDensity::in_w_per_m2(400.0);
+
+        model.solve_equilibrium(&[terrain], &[AdjArray::default()], flux, &[1.0]);
+
+        let absorbed =
+            w_per_m2(flux * terrain.absorption(config.ground_absorption, fixed_clouds(&config)));
+        let expected = equilibrium(&config, absorbed);
+        assert!((model.temperature[0] - expected).value.abs() < 1e-3);
+    }
+
+    #[test]
+    fn internal_heat_warms_the_night_side() {
+        let land = Terrain::new(0, 50, 0);
+        let adjacency = [AdjArray::default(), AdjArray::default()];
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 2, Temperature::in_c(-50.0))
+            .with_internal_heat(vec![
+                FluxDensity::in_w_per_m2(0.0),
+                FluxDensity::in_w_per_m2(2.0),
+            ])
+            .with_audit();
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        for _ in 0..24 {
+            model.step(
+                &[land, land],
+                &adjacency,
+                flux,
+                &[0.0, 0.0],
+                Duration::in_hr(1.0),
+            );
+        }
+
+        assert!(model.temperature[1] > model.temperature[0]);
+        assert!(model.audit.unwrap().internal > 0.0);
+        assert!(model.audit.unwrap().relative_imbalance().abs() < 1e-9);
+    }
+
+    /// The difference between the warmest and coolest temperature of a bare rock tile over a day
+    fn diurnal_range(config: ClimateConfig) -> Temperature {
+        let rock = Terrain::new(0, 50, 0);
+        let mut model = ClimateModel::new(config, 1, Temperature::in_k(210.0)).with_audit();
+        let flux = FluxDensity::in_w_per_m2(590.0);
+        let (mut min, mut max) = (Temperature::in_k(f64::INFINITY), Temperature::in_k(0.0));
+
+        for day in 0..30 {
+            for hour in 0..24 {
+                let intensity = (std::f64::consts::TAU * hour as f64 / 24.0).cos().max(0.0);
+                let dt = Duration::in_hr(1.0);
+                model.step(&[rock], &[AdjArray::default()], flux, &[intensity], dt);
+                assert!(model.audit.unwrap().relative_imbalance().abs() < 1e-9);
+
+                if day == 29 {
+                    min = min.min(model.temperature[0]);
+                    max = max.max(model.temperature[0]);
+                }
+            }
+        }
+
+        max - min
+    }
+
+    #[test]
+    fn subsurface_damps_the_diurnal_cycle() {
+        let bare = ClimateConfig {
+            subsurface: None,
+            ..ClimateConfig::mars()
+        };
+        let rock = bare.with_subsurface(SubsurfaceConfig::rock());
+
+        assert!(diurnal_range(rock) < diurnal_range(bare));
+    }
+
+    /// The warmest and coolest temperature of a Martian tile over the last of 30 sols
+    fn dusty_sols(config: ClimateConfig, flux: f64) -> (ClimateModel, Temperature, Temperature) {
+        let rock = Terrain::new(0, 50, 0);
+        let mut model = ClimateModel::new(config, 1, Temperature::in_k(210.0));
+        let flux = FluxDensity::in_w_per_m2(flux);
+        let (mut min, mut max) = (Temperature::in_k(f64::INFINITY), Temperature::in_k(0.0));
+
+        for sol in 0..30 {
+            for hour in 0..24 {
+                let intensity = (std::f64::consts::TAU * hour as f64 / 24.0).cos().max(0.0);
+                let dt = Duration::in_hr(1.0);
+                model.step(&[rock], &[AdjArray::default()], flux, &[intensity], dt);
+
+                if sol == 29 {
+                    min = min.min(model.temperature[0]);
+                    max = max.max(model.temperature[0]);
+                }
+            }
+        }
+
+        (model, min, max)
+    }
+
+    #[test]
+    fn dust_storms_narrow_the_diurnal_cycle() {
+        let clear = ClimateConfig {
+            dust: None,
+            ..ClimateConfig::mars()
+        };
+        let perihelion = 717.0;
+
+        let (dusty, dusty_min, dusty_max) = dusty_sols(ClimateConfig::mars(), perihelion);
+        let (_, clear_min, clear_max) = dusty_sols(clear, perihelion);
+
+        assert!(dusty.dust > 1.0, "{}", dusty.dust);
+        assert!(dusty_max < clear_max);
+        assert!(dusty_max - dusty_min < clear_max - clear_min);
+
+        let aphelion = 493.0;
+        let (calm, _, _) = dusty_sols(ClimateConfig::mars(), aphelion);
+        assert_eq!(DustConfig::mars().background, calm.dust);
+    }
+
+    #[test]
+    fn dust_settles_over_weeks() {
+        let mars = DustConfig::mars();
+        let mut model = ClimateModel::new(ClimateConfig::mars(), 1, Temperature::in_k(210.0));
+        model.raise_dust(3.0);
+        let flux = FluxDensity::in_w_per_m2(493.0);
+
+        for _ in 0..60 {
+            let dt = Duration::in_d(1.0);
+            model.step(
+                &[Terrain::new(0, 50, 0)],
+                &[AdjArray::default()],
+                flux,
+                &[0.5],
+                dt,
+            );
+        }
+
+        let excess = model.dust - mars.background;
+        assert!(excess > 0.0 && excess < 0.3, "{}", excess);
+    }
+
+    #[test]
+    fn energy_audit_balances() {
+        use crate::adjacency::{Spiral, Tiling};
+
+        // tiles with different neighbour counts and heat capacities
+        let n = 96;
+        let adjacency = Spiral.adjacency(n);
+        let terrain = (0..n)
+            .map(|i| Terrain::new((i * 53 % 256) as u8, 0, 0))
+            .collect::<Vec<_>>();
+        let intensity = (0..n)
+            .map(|i| (i as f64 * 0.3).cos().max(0.0))
+            .collect::<Vec<_>>();
+
+        let mut model =
+            ClimateModel::new(ClimateConfig::earth(), n, Temperature::in_c(15.0)).with_audit();
+        for (i, temp) in model.temperature.iter_mut().enumerate() {
+            *temp = Temperature::in_c(i as f64 % 30.0);
+        }
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        model.step(&terrain, &adjacency, flux, &intensity, Duration::in_hr(1.0));
+        let audit = model.audit.unwrap();
+
+        assert!(audit.absorbed > 0.0);
+        assert!(audit.emitted > 0.0);
+        assert!(audit.transported.abs() < 1e-6 * audit.absorbed);
+        assert!(audit.relative_imbalance().abs() < 1e-9);
+    }
+
+    #[test]
+    fn audit_is_disabled_by_default() {
+        let land = Terrain::new(0, 50, 0);
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 2, Temperature::in_c(15.0));
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        model.step(
+            &[land, land],
+            &pair(),
+            flux,
+            &[1.0, 1.0],
+            Duration::in_hr(1.0),
+        );
+
+        assert_eq!(None, model.audit);
+    }
+
+    #[test]
+    fn coastal_land_swings_more_than_the_sea() {
+        let coast = Terrain::new(128, 20, 0);
+        let mut model =
+            ClimateModel::new(ClimateConfig::earth(), 1, Temperature::in_c(15.0)).with_split();
+        let mut summary = ClimateSummary::new(1);
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        let (mut land_range, mut ocean_range) = (0.0, 0.0);
+
+        for day in 0..10 {
+            let (mut land, mut ocean) = (vec![], vec![]);
+            for hour in 0..24 {
+                let intensity = (std::f64::consts::TAU * hour as f64 / 24.0).cos().max(0.0);
+                let dt = Duration::in_hr(1.0);
+                model.step(&[coast], &[AdjArray::default()], flux, &[intensity], dt);
+
+                let split = model.split.as_ref().unwrap();
+                let capacity = coast.heat_capacity().value;
+                let ocean_fraction = coast.ocean.f64();
+                let mean = ((1.0 - ocean_fraction)
+                    * Terrain::LAND_HEAT_CAPACITY
+                    * split.land_temp[0].value
+                    + ocean_fraction * Terrain::OCEAN_HEAT_CAPACITY * split.ocean_temp[0].value)
+                    / capacity;
+                assert!((mean - model.temperature[0].value).abs() < 1e-9);
+
+                summary.record(&model.temperature, &[0.0], dt);
+                summary.record_split(&split.land_temp, &split.ocean_temp, dt);
+                land.push(split.land_temp[0].value);
+                ocean.push(split.ocean_temp[0].value);
+            }
+
+            if day == 9 {
+                let range = |t: &[f64]| {
+                    t.iter().cloned().fold(f64::MIN, f64::max)
+                        - t.iter().cloned().fold(f64::MAX, f64::min)
+                };
+                land_range = range(&land);
+                ocean_range = range(&ocean);
+            }
+        }
+
+        assert!(land_range > ocean_range, "{} {}", land_range, ocean_range);
+        assert!(summary.land_mean(0).is_some());
+        assert!(summary.ocean_mean(0).is_some());
+    }
+
+    #[test]
+    fn split_is_disabled_by_default() {
+        let summary = ClimateSummary::new(1);
+        let model = ClimateModel::new(ClimateConfig::earth(), 1, Temperature::in_c(15.0));
+
+        assert_eq!(None, model.split);
+        assert_eq!(None, summary.land_mean(0));
+        assert_eq!(None, summary.ocean_mean(0));
+    }
+
+    fn stepper() -> AdaptiveStep {
+        AdaptiveStep::new(
+            Duration::in_s(60.0),
+            Duration::in_d(1.0),
+            Temperature::in_k(0.5),
+        )
+    }
+
+    #[test]
+    fn adaptive_step_limits_change() {
+        let land = Terrain::new(0, 50, 0);
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 1, Temperature::in_c(15.0));
+        let mut stepper = stepper();
+        stepper.dt = stepper.max;
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+
+        let before = model.temperature[0];
+        let dt = model.step_adaptive(&mut stepper, &[land], &[AdjArray::default()], flux, &[1.0]);
+        let change = model.temperature[0] - before;
+
+        assert!(dt < stepper.max);
+        assert!(change > Temperature::in_k(0.0));
+        assert!(change <= stepper.threshold);
+    }
+
+    #[test]
+    fn adaptive_step_grows_while_settled() {
+        let land = Terrain::new(0, 50, 0);
+        let mut model = ClimateModel::new(insulated(), 1, Temperature::in_c(15.0));
+        let mut stepper = stepper();
+        let flux = FluxDensity::in_w_per_m2(0.0);
+
+        let mut elapsed = Duration::in_s(0.0);
+        for _ in 0..20 {
+            elapsed +=
+                model.step_adaptive(&mut stepper, &[land], &[AdjArray::default()], flux, &[0.0]);
+        }
+
+        assert_eq!(stepper.max, stepper.dt);
+        assert!(elapsed > Duration::in_d(1.0));
+    }
+
+    fn fixed_clouds(config: &ClimateConfig) -> FractionalU8 {
+        match config.clouds {
+            CloudModel::Fixed(cover) => cover,
+            _ => panic!("analytic tests need fixed cloud cover"),
+        }
+    }
+
+    #[test]
+    fn cloud_models() {
+        let warm = Temperature::in_c(25.0);
+        let cold = Temperature::in_c(-25.0);
+        let land = &Terrain::new(0, 50, 0);
+
+        let threshold = CloudModel::earth_threshold();
+        assert!(threshold.cover(land, cold, 0.5).u8() < threshold.cover(land, warm, 0.5).u8());
+
+        let humidity = CloudModel::earth_humidity();
+        assert!(humidity.cover(land, warm, 0.2).u8() < humidity.cover(land, warm, 0.8).u8());
+        assert_eq!(FractionalU8::new(255), humidity.cover(land, warm, 2.5));
+
+        let fixed = ClimateConfig::earth().clouds;
+        assert_eq!(fixed.cover(land, cold, 0.0), fixed.cover(land, warm, 1.0));
+    }
+
+    #[test]
+    fn evaporation_clouds() {
+        let clouds = CloudModel::earth_evaporation();
+        let earth = Terrain::new(179, 30, 0);
+        let ocean = Terrain::new(255, 0, 0);
+        let sea_ice = Terrain::new(255, 0, 255);
+        let cover =
+            |terrain: &Terrain, c: f64| clouds.cover(terrain, Temperature::in_c(c), 0.5).f64();
+
+        assert!((cover(&earth, 15.0) - 0.24).abs() < 0.01);
+        assert!(cover(&ocean, 15.0) > cover(&sea_ice, 15.0));
+        assert!(cover(&ocean, 30.0) > cover(&ocean, 15.0));
+        assert!(cover(&ocean, -30.0) < 0.5 * cover(&ocean, 15.0));
+    }
+
+    #[test]
+    fn annual_summary_of_warm_and_cold_tiles() {
+        let land = Terrain::new(0, 50, 0);
+        let adjacency = [AdjArray::default(), AdjArray::default()];
+        let mut model = ClimateModel::new(ClimateConfig::earth(), 2, Temperature::in_c(15.0));
+        model.temperature[1] = Temperature::in_c(-20.0);
+
+        let summary = model.annual_summary(
+            &[land, land],
+            &adjacency,
+            Duration::in_d(365.0),
+            Duration::in_hr(6.0),
+            |time, intensity| {
+                let day = (time / Duration::in_d(1.0)).fract() < 0.5;
+                intensity[0] = if day { 1.0 } else { 0.0 };
+                intensity[1] = 0.0;
+                FluxDensity::in_w_per_m2(1361.0)
+            },
+        );
+
+        assert!(summary.climate.min[0] < summary.climate.mean(0));
+        assert!(summary.climate.mean(0) < summary.climate.max[0]);
+        assert_eq!(365, summary.growing_season[0]);
+        assert_eq!(365, summary.frost_days[1]);
+        assert_eq!(0, summary.growing_season[1]);
+        assert_eq!(Duration::in_d(365.0), summary.climate.duration());
+    }
+
+    #[test]
+    fn climate_summary() {
+        let mut summary = ClimateSummary::new(1);
+        summary.record(&[Temperature::in_c(10.0)], &[1.0], Duration::in_d(1.0));
+        summary.record(&[Temperature::in_c(20.0)], &[2.0], Duration::in_d(3.0));
+
+        assert_eq!(Temperature::in_c(10.0), summary.min[0]);
+        assert_eq!(Temperature::in_c(20.0), summary.max[0]);
+        assert!((summary.mean(0) - Temperature::in_c(17.5)).value.abs() < 1e-9);
+        assert!((summary.annual_precipitation(0) - 3.0 * 365.25 / 4.0).abs() < 1.0);
+        assert_eq!(
+            vec![summary.annual_precipitation(0)],
+            summary.precipitation_map()
+        );
+    }
+
+    #[test]
+    fn climate_sensitivity_without_feedbacks() {
+        let terrain = [Terrain::new(0, 50, 0)];
+        let adjacency = [AdjArray::default()];
+        let flux = FluxDensity::in_w_per_m2(400.0);
+        let sensitivity = |ratio: f64| {
+            let config = ClimateConfig::earth();
+            climate_sensitivity(config, &terrain, &adjacency, flux, &[1.0], ratio)
+        };
+
+        let doubled = sensitivity(2.0);
+        assert!(doubled > Temperature::in_k(1.0), "{:?}", doubled);
+        assert!(doubled < Temperature::in_k(2.0), "{:?}", doubled);
+
+        assert!(sensitivity(1.0).value.abs() < 1e-3);
+        assert!(sensitivity(0.5) < Temperature::in_k(0.0));
+    }
+
+    #[test]
+    fn climate_along_path() {
+        let mut summary = ClimateSummary::new(3);
+        let temperature = [
+            Temperature::in_c(0.0),
+            Temperature::in_c(10.0),
+            Temperature::in_c(40.0),
+        ];
+        let flux = FluxDensity::in_w_per_m2(1000.0);
+        summary.record(&temperature, &[0.0; 3], Duration::in_d(1.0));
+        summary.record_insolation(flux, &[0.2, 0.4, 1.0], Duration::in_d(1.0));
+
+        let area = [1.0, 3.0, 1.0];
+        let path = summary.along_path(&[0, 1], &area).unwrap();
+
+        assert_eq!(Temperature::in_c(0.0), path.min);
+        assert_eq!(Temperature::in_c(10.0), path.max);
+        assert!((path.mean - Temperature::in_c(7.5)).value.abs() < 1e-9);
+        assert!((path.insolation.value - 350.0).abs() < 1e-9);
+
+        assert_eq!(None, summary.along_path(&[], &area));
+    }
+
+    /// Earth-like tiles lit from one side should reflect about 30% of the sunlight,
+    /// which catches drift in the cloud, ice, and absorption code
+    #[test]
+    fn earth_bond_albedo() {
+        use crate::adjacency::{Spiral, Tiling};
+
+        const N: usize = 96;
+        let terrain = vec![Terrain::new_fraction(0.7, 0.24, 0.03); N];
+        let adjacency = vec![AdjArray::default(); N];
+        let intensity = Spiral
+            .positions(N)
+            .iter()
+            .map(|p| p.x.max(0.0))
+            .collect::<Vec<_>>();
+
+        let mut model = ClimateModel::new(ClimateConfig::earth(), N, Temperature::in_c(15.0));
+        assert_eq!(None, model.bond_albedo());
+
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        model.step(&terrain, &adjacency, flux, &intensity, Duration::in_hr(1.0));
+
+        let albedo = model.bond_albedo().unwrap();
+        assert!((albedo - 0.30).abs() < 0.02, "{}", albedo);
+    }
+
+    /// A single tile with no neighbours, lit from directly overhead
+    fn isolated_tile(config: ClimateConfig, initial: Temperature) -> (ClimateModel, Terrain) {
+        let model = ClimateModel::new(config, 1, initial);
+        (model, Terrain::new(0, 0, 0))
+    }
+
+    fn w_per_m2(flux: FluxDensity) -> f64 {
+        flux / FluxDensity::in_w_per_m2(1.0)
+    }
+
+    /// Emission as a function of temperature for the given config
+    fn emission(config: &ClimateConfig, temp: Temperature) -> f64 {
+        w_per_m2(FluxDensity::blackbody(temp) * config.heat_trapping * config.emissivity)
+    }
+
+    fn equilibrium(config: &ClimateConfig, absorbed: f64) -> Temperature {
+        let (mut low, mut high) = (Temperature::in_k(1.0), Temperature::in_k(1000.0));
+        for _ in 0..100 {
+            let mid = (low + high) * 0.5;
+            if emission(config, mid) < absorbed {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    fn run(
+        model: &mut ClimateModel,
+        terrain: Terrain,
+        flux: FluxDensity,
+        duration: Duration,
+        dt: Duration,
+    ) {
+        let adjacency = [AdjArray::default()];
+        let steps = (duration / dt).round() as usize;
+        for _ in 0..steps {
+            model.step(&[terrain], &adjacency, flux, &[1.0], dt);
+        }
+    }
+
+    #[test]
+    fn constant_heating_without_emission_is_linear() {
+        let config = insulated();
+        let flux = FluxDensity::in_w_per_m2(100.0);
+        let duration = Duration::in_d(10.0);
+
+        for dt in [
+            Duration::in_s(60.0),
+            Duration::in_hr(1.0),
+            Duration::in_d(1.0),
+        ] {
+            let (mut model, terrain) = isolated_tile(config, Temperature::in_k(250.0));
+            run(&mut model, terrain, flux, duration, dt);
+
+            let absorbed = w_per_m2(flux)
+                * terrain
+                    .absorption(config.ground_absorption, fixed_clouds(&config))
+                    .0;
+            let expected =
+                absorbed * (duration / Duration::in_s(1.0)) / Terrain::LAND_HEAT_CAPACITY;
+            let actual = (model.temperature[0] - Temperature::in_k(250.0)).value;
+
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "{} != {}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn equilibrium_is_a_fixed_point() {
+        let config = ClimateConfig::earth();
+        let (_, terrain) = isolated_tile(config, Temperature::default());
+        let flux = FluxDensity::in_w_per_m2(400.0);
+        let absorbed = w_per_m2(flux)
+            * terrain
+                .absorption(config.ground_absorption, fixed_clouds(&config))
+                .0;
+        let t_eq = equilibrium(&config, absorbed);
+
+        let (mut model, _) = isolated_tile(config, t_eq);
+        run(
+            &mut model,
+            terrain,
+            flux,
+            Duration::in_d(30.0),
+            Duration::in_hr(1.0),
+        );
+
+        assert!((model.temperature[0] - t_eq).value.abs() < 1e-3);
+    }
+
+    /// Near equilibrium the emission is linear in temperature,
+    /// so a perturbation decays exponentially with time constant C / (dF/dT)
+    #[test]
+    fn exponential_approach_to_equilibrium() {
+        let config = ClimateConfig::earth();
+        let (_, terrain) = isolated_tile(config, Temperature::default());
+        let flux = FluxDensity::in_w_per_m2(400.0);
+        let absorbed = w_per_m2(flux)
+            * terrain
+                .absorption(config.ground_absorption, fixed_clouds(&config))
+                .0;
+        let t_eq = equilibrium(&config, absorbed);
+
+        let delta = Temperature::in_k(0.01);
+        let slope = (emission(&config, t_eq + delta) - emission(&config, t_eq - delta)) / 0.02;
+        let tau = Terrain::LAND_HEAT_CAPACITY / slope;
+
+        let perturbation = 2.0;
+        let duration = Duration::in_d(10.0);
+        let expected = perturbation * (-(duration / Duration::in_s(1.0)) / tau).exp();
+
+        for dt in [
+            Duration::in_s(60.0),
+            Duration::in_hr(1.0),
+            Duration::in_hr(3.0),
+        ] {
+            let (mut model, _) = isolated_tile(config, t_eq + Temperature::in_k(perturbation));
+            run(&mut model, terrain, flux, duration, dt);
+
+            let actual = (model.temperature[0] - t_eq).value;
+            assert!(
+                (actual - expected).abs() < 0.02,
+                "dt: {} s, {} != {}",
+                dt / Duration::in_s(1.0),
+                actual,
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::adjacency::AdjArray;
+use crate::climate::{ClimateModel, DustConfig};
+use crate::constants::SIGMA;
+use crate::solar_radiation::InfraredTransparency;
+use crate::terrain::Terrain;
+use physics_types::{Duration, FluxDensity, Temperature};
+
+/// A struct-of-arrays copy of a [`ClimateModel`] in raw SI units, for stepping many tiles quickly.
+///
+/// Units are converted once when copying in and out, so the step is plain `f64` arithmetic.
+/// Cloud cover, ground absorption, water vapour forcing, and internal heat are held at their values when copied,
+/// so call `refresh` after terrain or humidity changes. Dust is lifted and settles as in the model.
+/// Separate land and ocean temperatures and ocean currents are not supported.
+/// See `benches/climate_step.rs` for a comparison with `ClimateModel::step`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClimateArrays {
+    /// Sea-level temperature in K
+    pub temperature: Vec<f64>,
+    /// Natural log of the absorption of each tile, see `Terrain::absorption`
+    log_absorption: Vec<f64>,
+    /// Heat capacity in J/K/m²
+    heat_capacity: Vec<f64>,
+    /// Area of each tile relative to the mean tile, see `ClimateModel::with_area`
+    relative_area: Vec<f64>,
+    /// Ocean fraction of each tile
+    ocean: Vec<f64>,
+    /// Heat rising from the interior in W/m²
+    internal_heat: Vec<f64>,
+    /// Temperature of the layer beneath each tile in K, empty unless `ClimateConfig::subsurface` is set
+    pub subsurface: Vec<f64>,
+    /// Heat capacity in J/K/m² and conductance in W/m²/K of the layer beneath each tile
+    layers: Vec<(f64, f64)>,
+    /// Fraction of blackbody emission escaping to space before any dust, see `ClimateModel::heat_trapping`
+    trapping: InfraredTransparency,
+    emissivity: f64,
+    /// Visible optical depth of dust in the air, see `ClimateConfig::dust`
+    pub dust: f64,
+    dust_config: Option<DustConfig>,
+    atmospheric_conductance: f64,
+    ocean_conductance: f64,
+    transfer: Vec<f64>,
+}
+
+impl ClimateArrays {
+    pub fn new(model: &ClimateModel, terrain: &[Terrain]) -> Self {
+        let mut arrays = Self::default();
+        arrays.refresh(model, terrain);
+        arrays
+    }
+
+    /// Copies the temperatures and recalculates the fixed inputs from the model
+    ///
+    /// # Panics
+    ///
+    /// If the model has split land and ocean temperatures or ocean currents
+    pub fn refresh(&mut self, model: &ClimateModel, terrain: &[Terrain]) {
+        let config = &model.config;
+        assert!(
+            model.split.is_none() && model.currents.is_none(),
+            "split temperatures and ocean currents are not supported"
+        );
+
+        self.temperature.clear();
+        self.temperature
+            .extend(model.temperature.iter().map(|t| t.value));
+
+        self.log_absorption.clear();
+        self.log_absorption.extend(
+            model
+                .temperature
+                .iter()
+                .zip(terrain)
+                .zip(&model.ground)
+                .zip(&model.humidity)
+                .map(|(((temp, terrain), ground), humidity)| {
+                    let clouds = config.clouds.cover(terrain, *temp, *humidity);
+                    terrain.absorption(*ground, clouds).0.ln()
+                }),
+        );
+
+        self.heat_capacity.clear();
+        self.heat_capacity
+            .extend(terrain.iter().map(|t| t.heat_capacity().value));
+
+        let tiles = model.area.len() as f64;
+        self.relative_area.clear();
+        self.relative_area
+            .extend(model.area.iter().map(|area| area * tiles));
+
+        self.ocean.clear();
+        self.ocean.extend(terrain.iter().map(|t| t.ocean.f64()));
+
+        self.internal_heat.clear();
+        self.internal_heat
+            .extend(model.internal_heat.iter().map(|f| f.value));
+
+        self.subsurface.clear();
+        self.layers.clear();
+        if let Some(subsurface) = config.subsurface {
+            self.subsurface
+                .extend(model.subsurface.iter().map(|t| t.value));
+            self.layers.extend(terrain.iter().map(|t| {
+                let layer = subsurface.layer(t);
+                (layer.heat_capacity, layer.conductance)
+            }));
+        }
+
+        self.trapping = model.vapour_trapping();
+        self.emissivity = config.emissivity;
+        self.dust = model.dust;
+        self.dust_config = config.dust;
+        self.atmospheric_conductance = config.atmospheric_conductance;
+        self.ocean_conductance = config.ocean_conductance;
+    }
+
+    /// Copies the temperatures back into the model
+    pub fn write_to(&self, model: &mut ClimateModel) {
+        for (temp, value) in model.temperature.iter_mut().zip(&self.temperature) {
+            *temp = Temperature::in_k(*value);
+        }
+        for (temp, value) in model.subsurface.iter_mut().zip(&self.subsurface) {
+            *temp = Temperature::in_k(*value);
+        }
+        model.dust = self.dust;
+    }
+
+    /// Advances the temperature of each tile, equivalent to `ClimateModel::step`
+    pub fn step(
+        &mut self,
+        adjacency: &[AdjArray],
+        flux: FluxDensity,
+        intensity: &[f64],
+        dt: Duration,
+    ) {
+        let (trapping, transmission) = match self.dust_config {
+            Some(config) => {
+                self.dust = config.update(self.dust, flux, dt);
+                let trapping = config.heat_trapping(self.dust, self.trapping);
+                (trapping, config.transmission(self.dust))
+            }
+            None => (self.trapping, 1.0),
+        };
+
+        let flux = flux.value * transmission;
+        let seconds = dt / Duration::in_s(1.0);
+        let emission = SIGMA * trapping.0 * self.emissivity;
+
+        let iter = self
+            .temperature
+            .iter_mut()
+            .zip(&self.log_absorption)
+            .zip(&self.heat_capacity)
+            .zip(&self.internal_heat)
+            .zip(intensity);
+
+        for ((((temp, log_absorption), heat_capacity), internal), intensity) in iter {
+            // oblique light passes through more atmosphere and is reflected more
+            let absorbed = if *intensity > 0.0 {
+                flux * intensity * (log_absorption * intensity.powf(-0.678)).exp()
+            } else {
+                0.0
+            };
+
+            let t2 = *temp * *temp;
+            let emitted = emission * t2 * t2;
+
+            *temp += (absorbed - emitted) * seconds / heat_capacity;
+            *temp += internal * seconds / heat_capacity;
+        }
+
+        self.exchange_subsurface(seconds);
+        self.transfer_heat(adjacency, seconds);
+    }
+
+    /// Relaxes the surface and the layer beneath toward their shared temperature,
+    /// as in `ClimateModel::step`
+    fn exchange_subsurface(&mut self, seconds: f64) {
+        let iter = self
+            .temperature
+            .iter_mut()
+            .zip(&mut self.subsurface)
+            .zip(&self.heat_capacity)
+            .zip(&self.layers);
+
+        for (((surface, subsurface), surface_capacity), (layer_capacity, conductance)) in iter {
+            let total = surface_capacity + layer_capacity;
+            let shared = (*surface * surface_capacity + *subsurface * layer_capacity) / total;
+            let rate = conductance * seconds * total / (surface_capacity * layer_capacity);
+            let relaxation = 1.0 - (-rate).exp();
+
+            *surface += (shared - *surface) * relaxation;
+            *subsurface += (shared - *subsurface) * relaxation;
+        }
+    }
+
+    fn transfer_heat(&mut self, adjacency: &[AdjArray], seconds: f64) {
+        let temperature = &self.temperature;
+        let ocean = &self.ocean;
+
+        self.transfer.clear();
+        self.transfer.resize(temperature.len(), 0.0);
+
+        // one flux per edge, as in `ClimateModel::step`
+        for (i, neighbours) in adjacency.iter().enumerate() {
+            for n in neighbours.iter().filter(|n| *n > i) {
+                let shared = 0.5 * (ocean[i] + ocean[n]);
+                let edges = 0.5 * (adjacency[i].len() + adjacency[n].len()) as f64;
+                let conductance =
+                    (self.atmospheric_conductance + self.ocean_conductance * shared) / edges;
+
+                let energy = conductance * (temperature[n] - temperature[i]) * seconds;
+                self.transfer[i] += energy / (self.heat_capacity[i] * self.relative_area[i]);
+                self.transfer[n] -= energy / (self.heat_capacity[n] * self.relative_area[n]);
+            }
+        }
+
+        for (temp, transfer) in self.temperature.iter_mut().zip(&self.transfer) {
+            *temp += transfer;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::climate::{ClimateConfig, SubsurfaceConfig};
+
+    /// Steps both for a day on a strip of tiles from dry land to open ocean,
+    /// returning the largest difference in temperature
+    fn compare(config: ClimateConfig) -> (ClimateModel, ClimateArrays, f64) {
+        let n = 24;
+        let mut adjacency = Adjacency::default();
+        adjacency.register(n);
+        let adjacency = adjacency.get(n);
+
+        let terrain = (0..n)
+            .map(|i| {
+                let ocean = i as f64 / n as f64;
+                Terrain::new_fraction(ocean, 0.3 * (1.0 - ocean), 0.0)
+            })
+            .collect::<Vec<_>>();
+        let intensity = (0..n)
+            .map(|i| (i as f64 / n as f64 * 6.0).sin().max(0.0))
+            .collect::<Vec<_>>();
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        let dt = Duration::in_hr(1.0);
+
+        let mut model = ClimateModel::new(config, n, Temperature::in_c(15.0));
+        let mut arrays = ClimateArrays::new(&model, &terrain);
+
+        for _ in 0..24 {
+            model.step(&terrain, adjacency, flux, &intensity, dt);
+            arrays.step(adjacency, flux, &intensity, dt);
+        }
+
+        let difference = model
+            .temperature
+            .iter()
+            .zip(&arrays.temperature)
+            .map(|(expected, actual)| (expected.value - actual).abs())
+            .fold(0.0, f64::max);
+
+        (model, arrays, difference)
+    }
+
+    #[test]
+    fn matches_climate_model() {
+        let (model, arrays, difference) = compare(ClimateConfig::earth());
+        assert!(difference < 1e-4, "{}", difference);
+
+        let mut copy = model.clone();
+        arrays.write_to(&mut copy);
+        assert_eq!(model.temperature.len(), copy.temperature.len());
+    }
+
+    #[test]
+    fn matches_climate_model_with_subsurface() {
+        let config = ClimateConfig::earth().with_subsurface(SubsurfaceConfig::regolith());
+        let (model, arrays, difference) = compare(config);
+        assert!(difference < 1e-4, "{}", difference);
+
+        let mut copy = ClimateModel::new(config, model.subsurface.len(), Temperature::in_c(15.0));
+        arrays.write_to(&mut copy);
+        for (expected, actual) in model.subsurface.iter().zip(&copy.subsurface) {
+            assert!((expected.value - actual.value).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn matches_climate_model_on_mars() {
+        let (model, arrays, difference) = compare(ClimateConfig::mars());
+        assert!(difference < 1e-4, "{}", difference);
+
+        assert!(model.dust > DustConfig::mars().background);
+        assert!((model.dust - arrays.dust).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_temperatures_are_not_supported() {
+        let model = ClimateModel::new(ClimateConfig::earth(), 1, Temperature::in_c(15.0));
+        ClimateArrays::new(&model.with_split(), &[Terrain::new(128, 0, 0)]);
+    }
+}
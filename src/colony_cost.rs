@@ -1,7 +1,14 @@
-use physics_types::{Pressure, Temperature};
+use crate::atmosphere::Atmosphere;
+use crate::climate::ClimateSummary;
+use crate::magnetosphere::Magnetosphere;
+use crate::planet::Planet;
+use crate::radiation::DoseRate;
+use crate::terrain::Terrain;
+use physics_types::{Acceleration, FluxDensity, Pressure, Temperature};
 use std::ops::Range;
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColonyCost(f64);
 
 impl ColonyCost {
@@ -13,6 +20,67 @@ impl ColonyCost {
         Self(min)
     }
 
+    /// Includes the cost of surface gravity and ionizing radiation along with the other conditions
+    pub fn new_with_body(
+        temp: Range<Temperature>,
+        pressure: Pressure,
+        shielding: Shielding,
+        gravity: Acceleration,
+        dose: DoseRate,
+    ) -> Self {
+        Self::new(temp, pressure, shielding)
+            .with_gravity(gravity)
+            .with_radiation(dose)
+    }
+
+    pub fn with_gravity(self, gravity: Acceleration) -> Self {
+        Self(self.0.max(Self::gravity_min(gravity)))
+    }
+
+    /// Radiation dose at the surface, after shielding from the atmosphere and magnetic field
+    pub fn with_radiation(self, dose: DoseRate) -> Self {
+        Self(self.0.max(Self::radiation_min(dose)))
+    }
+
+    /// Scales the cost by the difficulty of building on the tile's surface
+    pub fn with_terrain(self, terrain: &Terrain) -> Self {
+        Self(self.0 * terrain.colony_modifier())
+    }
+
+    /// The cost of a tile using the temperature range recorded in `climate`
+    /// and the surface pressure and shielding of the atmosphere
+    pub fn from_tile(
+        tile: usize,
+        atmosphere: &Atmosphere,
+        climate: &ClimateSummary,
+        magnetic_field: bool,
+    ) -> Self {
+        let temp = climate.min[tile]..climate.max[tile];
+        let shielding = Shielding::new(magnetic_field, atmosphere.pressure);
+        Self::new(temp, atmosphere.pressure, shielding)
+    }
+
+    /// The cost of a tile as in `from_tile`, with the shielding and surface dose
+    /// given by the planet's magnetosphere and the column of air above it
+    pub fn from_magnetosphere(
+        tile: usize,
+        atmosphere: &Atmosphere,
+        climate: &ClimateSummary,
+        magnetosphere: &Magnetosphere,
+        surface_gravity: Acceleration,
+    ) -> Self {
+        let temp = climate.min[tile]..climate.max[tile];
+        let pressure = atmosphere.pressure;
+        let shielding = magnetosphere.shielding(pressure);
+        let dose = magnetosphere.surface_dose(pressure, surface_gravity);
+        Self::new(temp, pressure, shielding).with_radiation(dose)
+    }
+
+    /// Whole-number tier for coarse comparisons, starting at 1 for ideal conditions
+    pub fn tier(self) -> u8 {
+        self.0.ceil().clamp(1.0, u8::MAX as f64) as u8
+    }
+
     fn pressure_min(pressure: Pressure) -> f64 {
         let atm = pressure / Pressure::in_atm(1.0);
 
@@ -23,6 +91,25 @@ impl ColonyCost {
         }
     }
 
+    fn gravity_min(gravity: Acceleration) -> f64 {
+        let g = gravity / Acceleration::in_m_per_s2(9.80665);
+
+        if g < 0.8 {
+            (0.8 - g) * 2.5 + 1.0
+        } else if g > 1.2 {
+            (g - 1.2) * 5.0 + 1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Doses below the 20 mSv/yr occupational limit carry no extra cost
+    fn radiation_min(dose: DoseRate) -> f64 {
+        const LIMIT: DoseRate = DoseRate::in_msv_per_yr(20.0);
+        let excess = (dose.sv_per_yr() - LIMIT.sv_per_yr()).max(0.0);
+        excess * 10.0 + 1.0
+    }
+
     fn temperature_min(temperature: Range<Temperature>) -> f64 {
         const LOWER_BOUND: Temperature = Temperature::in_c(5.0);
         const UPPER_BOUND: Temperature = Temperature::in_c(30.0);
@@ -40,7 +127,127 @@ impl ColonyCost {
     }
 }
 
+/// The cost of every tile of every planet, as given by `ColonyCost::from_tile`
+/// with each planet's recorded climate, atmosphere, and magnetic field
+pub fn evaluate_system(planets: &[Planet]) -> Vec<Vec<ColonyCost>> {
+    planets
+        .iter()
+        .map(|planet| {
+            let state = &planet.state;
+            let magnetic_field = planet.descriptor.magnetic_field;
+            (0..state.summary.min.len())
+                .map(|tile| {
+                    ColonyCost::from_tile(tile, &state.atmosphere, &state.summary, magnetic_field)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// How well a tile could grow crops, from 0 for ice, rock, and open water
+/// to 1 for well-watered temperate plains with plenty of sunlight
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FoodPotential(f64);
+
+impl FoodPotential {
+    /// The product of how suitable each condition is, so that any one can rule out farming
+    ///
+    /// # Arguments
+    ///
+    /// * `temp`: the range of temperatures over the year
+    /// * `mean`: the mean temperature over the year
+    /// * `precipitation`: annual precipitation in kg/m²/yr
+    /// * `insolation`: mean sunlight at the top of the atmosphere
+    pub fn new(
+        temp: Range<Temperature>,
+        mean: Temperature,
+        precipitation: f64,
+        insolation: FluxDensity,
+        terrain: &Terrain,
+    ) -> Self {
+        Self(
+            Self::temperature_factor(temp, mean)
+                * Self::precipitation_factor(precipitation)
+                * Self::insolation_factor(insolation)
+                * Self::terrain_factor(terrain),
+        )
+    }
+
+    /// The potential of a tile using the climate recorded in `climate`
+    pub fn from_tile(tile: usize, terrain: &Terrain, climate: &ClimateSummary) -> Self {
+        Self::new(
+            climate.min[tile]..climate.max[tile],
+            climate.mean(tile),
+            climate.annual_precipitation(tile),
+            climate.mean_insolation(tile),
+            terrain,
+        )
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// Best for means of 10 to 25 °C, and reduced by hard winter frosts
+    fn temperature_factor(temperature: Range<Temperature>, mean: Temperature) -> f64 {
+        let celsius = (mean - Temperature::in_c(0.0)).value;
+        let growing = if celsius < 10.0 {
+            celsius / 10.0
+        } else if celsius > 25.0 {
+            (35.0 - celsius) / 10.0
+        } else {
+            1.0
+        };
+
+        let frost = ((Temperature::in_c(0.0) - temperature.start).value / 20.0).clamp(0.0, 1.0);
+        growing.clamp(0.0, 1.0) * (1.0 - 0.5 * frost)
+    }
+
+    /// Too dry for crops below 200 kg/m²/yr without irrigation, with waterlogging past 2500 kg/m²/yr
+    fn precipitation_factor(precipitation: f64) -> f64 {
+        if precipitation > 2500.0 {
+            (1.0 - (precipitation - 2500.0) / 5000.0).max(0.5)
+        } else {
+            ((precipitation - 200.0) / 400.0).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Plants are limited by light below about Earth's global mean insolation
+    fn insolation_factor(insolation: FluxDensity) -> f64 {
+        (insolation.value / 300.0).clamp(0.0, 1.0)
+    }
+
+    /// Only plains free of ice can be farmed, see `Terrain::glacier`
+    fn terrain_factor(terrain: &Terrain) -> f64 {
+        // glaciers cover the mountains before the plains
+        let covered = terrain
+            .land_ice()
+            .u8()
+            .saturating_sub(terrain.mountains.u8());
+        let farmland = terrain.plains.u8().saturating_sub(covered);
+        farmland as f64 / u8::MAX as f64
+    }
+}
+
+/// The food potential of every tile of every planet, as given by `FoodPotential::from_tile`
+pub fn evaluate_food(planets: &[Planet]) -> Vec<Vec<FoodPotential>> {
+    planets
+        .iter()
+        .map(|planet| {
+            let state = &planet.state;
+            state
+                .terrain
+                .iter()
+                .enumerate()
+                .map(|(tile, terrain)| FoodPotential::from_tile(tile, terrain, &state.summary))
+                .collect()
+        })
+        .collect()
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shielding {
     Shielded,
     Partial,
@@ -48,6 +255,20 @@ pub enum Shielding {
 }
 
 impl Shielding {
+    /// Shielding from a magnetic field and the column of air above the surface.
+    /// A thick enough atmosphere blocks radiation on its own.
+    pub fn new(magnetic_field: bool, pressure: Pressure) -> Self {
+        let atm = pressure / Pressure::in_atm(1.0);
+
+        match (magnetic_field, atm) {
+            (_, atm) if atm >= 10.0 => Shielding::Shielded,
+            (true, atm) if atm >= 0.5 => Shielding::Shielded,
+            (true, _) => Shielding::Partial,
+            (false, atm) if atm >= 0.5 => Shielding::Partial,
+            _ => Shielding::Unshielded,
+        }
+    }
+
     pub fn min_cost(self) -> f64 {
         match self {
             Shielding::Shielded => 1.0,
@@ -71,6 +292,58 @@ mod test {
         assert!(ideal < ColonyCost(1.05))
     }
 
+    #[test]
+    fn evaluate_system_matches_each_tile() {
+        use crate::climate::ClimateConfig;
+        use crate::planet::{Orbit, PlanetDescriptor};
+        use physics_types::{Duration, FluxDensity, Length};
+        use std::sync::Arc;
+
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(149.6e9),
+            eccentricity: 0.0,
+            period: Duration::in_yr(1.0),
+        };
+        let planet = |climate: ClimateConfig, atmosphere: Atmosphere, magnetic_field: bool| {
+            let descriptor = PlanetDescriptor::new(Length::in_m(6e6), orbit, 16, climate)
+                .with_magnetic_field(magnetic_field);
+            let state = descriptor.generate(atmosphere, Temperature::in_c(0.0), 1);
+            let mut planet = Planet::new(Arc::new(descriptor), state);
+
+            let intensity = (0..16).map(|i| i as f64 / 16.0).collect::<Vec<_>>();
+            for _ in 0..10 {
+                let flux = FluxDensity::in_w_per_m2(1361.0);
+                planet.step(flux, &intensity, Duration::in_hr(1.0));
+            }
+            planet
+        };
+
+        let planets = [
+            planet(ClimateConfig::earth(), Atmosphere::earth(), true),
+            planet(ClimateConfig::mars(), Atmosphere::mars(), false),
+        ];
+        let costs = evaluate_system(&planets);
+
+        assert_eq!(2, costs.len());
+        for (planet, costs) in planets.iter().zip(&costs) {
+            let state = &planet.state;
+            let magnetic_field = planet.descriptor.magnetic_field;
+
+            for (tile, cost) in costs.iter().enumerate() {
+                let expected =
+                    ColonyCost::from_tile(tile, &state.atmosphere, &state.summary, magnetic_field);
+                assert_eq!(expected, *cost);
+            }
+        }
+    }
+
+    #[test]
+    fn tier() {
+        assert_eq!(1, ColonyCost(1.0).tier());
+        assert_eq!(2, ColonyCost(1.5).tier());
+        assert_eq!(u8::MAX, ColonyCost(1e6).tier());
+    }
+
     #[test]
     fn pressure_min() {
         let vac = ColonyCost::pressure_min(Pressure::zero());
@@ -99,6 +372,130 @@ mod test {
         assert!(frozen > cold);
     }
 
+    #[test]
+    fn shielding_new() {
+        use Shielding::*;
+        assert_eq!(Shielded, Shielding::new(true, Pressure::in_atm(1.0)));
+        assert_eq!(Shielded, Shielding::new(false, Pressure::in_atm(92.0)));
+        assert_eq!(Partial, Shielding::new(false, Pressure::in_atm(1.0)));
+        assert_eq!(Unshielded, Shielding::new(false, Pressure::in_atm(0.006)));
+    }
+
+    #[test]
+    fn from_tile() {
+        use physics_types::Duration;
+
+        let mut climate = ClimateSummary::new(2);
+        let temperature = [Temperature::in_c(15.0), Temperature::in_c(-60.0)];
+        climate.record(&temperature, &[0.0, 0.0], Duration::in_d(1.0));
+
+        let earth = Atmosphere::earth();
+        let temperate = ColonyCost::from_tile(0, &earth, &climate, true);
+        let frozen = ColonyCost::from_tile(1, &earth, &climate, true);
+        let exposed = ColonyCost::from_tile(0, &Atmosphere::mars(), &climate, false);
+
+        assert_eq!(1, temperate.tier());
+        assert!(frozen > temperate);
+        assert!(exposed > temperate);
+    }
+
+    #[test]
+    fn gravity_min() {
+        let g = |g: f64| ColonyCost::gravity_min(Acceleration::in_m_per_s2(9.80665 * g));
+
+        assert_eq!(1.0, g(1.0));
+        assert!(g(0.38) > g(1.0));
+        assert!(g(0.16) > g(0.38));
+        assert!(g(2.5) > g(1.0));
+    }
+
+    #[test]
+    fn radiation_min() {
+        let earth = ColonyCost::radiation_min(DoseRate::in_msv_per_yr(2.4));
+        let mars = ColonyCost::radiation_min(DoseRate::in_msv_per_yr(230.0));
+
+        assert_eq!(1.0, earth);
+        assert!(mars > earth);
+    }
+
+    #[test]
+    fn new_with_body() {
+        let temp = Temperature::in_c(15.0)..Temperature::in_c(20.0);
+        let earth = ColonyCost::new_with_body(
+            temp.clone(),
+            Pressure::in_atm(1.0),
+            Shielding::Shielded,
+            Acceleration::in_m_per_s2(9.8),
+            DoseRate::in_msv_per_yr(2.4),
+        );
+        let heavy = earth.with_gravity(Acceleration::in_m_per_s2(30.0));
+
+        assert_eq!(1, earth.tier());
+        assert!(heavy > earth);
+    }
+
+    #[test]
+    fn with_terrain() {
+        let cost = ColonyCost(1.5);
+        let plains = cost.with_terrain(&Terrain::new(0, 0, 0));
+        let ocean = cost.with_terrain(&Terrain::new(247, 0, 0));
+
+        assert_eq!(cost, plains);
+        assert!(ocean > plains);
+    }
+
+    #[test]
+    fn food_potential() {
+        let food = |min: f64, mean: f64, precipitation: f64, terrain: Terrain| {
+            let temp = Temperature::in_c(min)..Temperature::in_c(mean + 10.0);
+            let insolation = FluxDensity::in_w_per_m2(340.0);
+            FoodPotential::new(
+                temp,
+                Temperature::in_c(mean),
+                precipitation,
+                insolation,
+                &terrain,
+            )
+        };
+        let plains = Terrain::new(0, 0, 0);
+
+        let farmland = food(5.0, 15.0, 1000.0, plains);
+        assert_eq!(1.0, farmland.value());
+
+        assert!(food(5.0, 15.0, 100.0, plains) < farmland);
+        assert!(food(-30.0, 5.0, 1000.0, plains) < farmland);
+        assert!(food(5.0, 15.0, 1000.0, Terrain::new(0, 200, 0)) < farmland);
+        assert_eq!(
+            0.0,
+            food(5.0, 15.0, 1000.0, Terrain::new(255, 0, 0)).value()
+        );
+        assert_eq!(
+            0.0,
+            food(5.0, 15.0, 1000.0, Terrain::new(0, 0, 255)).value()
+        );
+    }
+
+    #[test]
+    fn food_from_tile() {
+        use physics_types::Duration;
+
+        let mut climate = ClimateSummary::new(2);
+        let temperature = [Temperature::in_c(15.0), Temperature::in_c(-60.0)];
+        climate.record(&temperature, &[5.0, 5.0], Duration::in_d(1.0));
+        climate.record_insolation(
+            FluxDensity::in_w_per_m2(1361.0),
+            &[0.5, 0.5],
+            Duration::in_d(1.0),
+        );
+
+        let plains = Terrain::new(0, 0, 0);
+        let temperate = FoodPotential::from_tile(0, &plains, &climate);
+        let frozen = FoodPotential::from_tile(1, &plains, &climate);
+
+        assert!(temperate.value() > 0.5);
+        assert_eq!(0.0, frozen.value());
+    }
+
     #[test]
     fn shielding_min() {
         use Shielding::*;
@@ -0,0 +1,26 @@
+//! Physical constants and the Earth and sun as reference bodies, in SI units.
+//! Distances in astronomical units use `physics_types::AU`.
+
+/// Stefan–Boltzmann constant in W/m²/K⁴
+pub const SIGMA: f64 = 5.670374419e-8;
+
+/// Newtonian constant of gravitation in m³/kg/s²
+pub const G: f64 = 6.674e-11;
+
+/// Molar gas constant in J/mol/K
+pub const GAS_CONSTANT: f64 = 8.314;
+
+/// Mass of the Earth in kg
+pub const EARTH_MASS: f64 = 5.972e24;
+
+/// Mean radius of the Earth in m
+pub const EARTH_RADIUS: f64 = 6371e3;
+
+/// Mass of the sun in kg
+pub const SOLAR_MASS: f64 = 1.989e30;
+
+/// Luminosity of the sun in W
+pub const SOLAR_LUMINOSITY: f64 = 3.828e26;
+
+/// Radiative forcing from an e-fold increase in CO2 in W/m²
+pub const CO2_FORCING: f64 = 5.35;
@@ -0,0 +1,233 @@
+use crate::adjacency::units::Position3;
+use crate::adjacency::AdjArray;
+use crate::terrain::Terrain;
+use crate::weather::{tangent_direction, Weather};
+use physics_types::{Duration, Temperature};
+
+/// Horizontal velocity of the surface ocean in m/s
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Current {
+    pub east: f64,
+    pub north: f64,
+}
+
+impl Current {
+    pub fn speed(self) -> f64 {
+        self.east.hypot(self.north)
+    }
+}
+
+/// Surface currents of idealized wind-driven gyres.
+/// The prevailing winds push the ocean east or west, and where a continent blocks the way
+/// the current turns along the coast: poleward on western boundaries, carrying warm water
+/// like the Gulf Stream, and equatorward on eastern boundaries.
+///
+/// Recompute when coastlines change, e.g., with `SeaLevel`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OceanCurrents {
+    /// Zero on dry tiles
+    pub current: Vec<Current>,
+    /// Current speed toward each neighbour in m/s, in the order of the tile's `AdjArray`
+    flow: Vec<[f64; AdjArray::CAPACITY]>,
+}
+
+impl OceanCurrents {
+    /// Peak speed of the open-ocean currents in m/s
+    pub const SPEED: f64 = 0.2;
+
+    /// Western boundary currents are narrowed and sped up by the planet's rotation
+    pub const WESTERN_INTENSIFICATION: f64 = 3.0;
+
+    /// Heat carried downstream per kelvin of difference in W/m²/K, for a current at `SPEED` between open-ocean tiles
+    pub const CONDUCTANCE: f64 = 20.0;
+
+    /// Tiles with less ocean than this block the current
+    const COAST: u8 = u8::MAX / 2;
+
+    /// The currents around tiles centred on `positions`, see `Adjacency::positions`
+    pub fn new(
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        positions: &[Position3],
+        rotation_period: Duration,
+    ) -> Self {
+        let nodes = adjacency.len();
+        let cells = Weather::cells(rotation_period);
+
+        let current = (0..nodes)
+            .map(|i| {
+                if terrain[i].ocean.u8() == 0 {
+                    return Current::default();
+                }
+
+                let latitude = positions[i].z.clamp(-1.0, 1.0).asin();
+                let wind = Weather::prevailing(latitude, cells);
+                let east = Self::SPEED * wind.east / Weather::SPEED;
+
+                // the neighbour lying furthest along the current
+                let ahead = adjacency[i].iter().max_by(|a, b| {
+                    let a = tangent_direction(positions[i], positions[*a]).0 * east;
+                    let b = tangent_direction(positions[i], positions[*b]).0 * east;
+                    a.total_cmp(&b)
+                });
+
+                match ahead {
+                    Some(n) if terrain[n].ocean.u8() < Self::COAST => {
+                        let poleward = latitude.signum() * east.abs();
+                        if east < 0.0 {
+                            Current {
+                                east: 0.0,
+                                north: Self::WESTERN_INTENSIFICATION * poleward,
+                            }
+                        } else {
+                            Current {
+                                east: 0.0,
+                                north: -poleward,
+                            }
+                        }
+                    }
+                    _ => Current { east, north: 0.0 },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let flow = positions
+            .iter()
+            .zip(adjacency)
+            .zip(&current)
+            .map(|((p, adj), current)| {
+                let mut flow = [0.0; AdjArray::CAPACITY];
+                for (flow, n) in flow.iter_mut().zip(adj.iter()) {
+                    if terrain[n].ocean.u8() > 0 {
+                        let (east, north) = tangent_direction(*p, positions[n]);
+                        *flow = (current.east * east + current.north * north).max(0.0);
+                    }
+                }
+                flow
+            })
+            .collect();
+
+        Self { current, flow }
+    }
+
+    /// Carries heat downstream, warming each tile toward the water flowing into it.
+    /// Heat leaving one tile arrives at the next, so the total is conserved for tiles of equal area.
+    pub fn advect_heat(
+        &self,
+        temperature: &mut [Temperature],
+        buffer: &mut Vec<f64>,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        dt: Duration,
+    ) {
+        let seconds = dt / Duration::in_s(1.0);
+
+        // J/m² gained by each tile
+        buffer.clear();
+        buffer.resize(temperature.len(), 0.0);
+
+        for (i, (flow, adj)) in self.flow.iter().zip(adjacency).enumerate() {
+            for (flow, n) in flow.iter().zip(adj.iter()) {
+                if *flow <= 0.0 {
+                    continue;
+                }
+
+                let ocean = terrain[i].ocean.f64().min(terrain[n].ocean.f64());
+                let conductance = Self::CONDUCTANCE * ocean * flow / Self::SPEED;
+
+                // limited so that a long step can't overshoot
+                let capacity = terrain[i]
+                    .heat_capacity()
+                    .value
+                    .min(terrain[n].heat_capacity().value);
+                let exchange = (conductance * seconds).min(0.25 * capacity);
+
+                let energy = exchange * (temperature[i] - temperature[n]).value;
+                buffer[i] -= energy;
+                buffer[n] += energy;
+            }
+        }
+
+        for ((temp, energy), terrain) in temperature.iter_mut().zip(buffer.iter()).zip(terrain) {
+            *temp += Temperature::in_k(energy / terrain.heat_capacity().value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use std::iter::FromIterator;
+
+    #[test]
+    fn western_boundary_currents_run_poleward() {
+        const N: usize = 256;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        let adjacency = adj.get(N);
+        let positions = adj.positions(N);
+
+        // a continent spanning a quarter of the longitudes
+        let terrain = positions
+            .iter()
+            .map(|p| {
+                let longitude = p.y.atan2(p.x).to_degrees();
+                let ocean = if (0.0..90.0).contains(&longitude) {
+                    0
+                } else {
+                    255
+                };
+                Terrain::new(ocean, 0, 0)
+            })
+            .collect::<Vec<_>>();
+
+        let currents = OceanCurrents::new(&terrain, adjacency, positions, Duration::in_d(1.0));
+        let cells = Weather::cells(Duration::in_d(1.0));
+
+        let mut boundary = 0;
+        for (i, current) in currents.current.iter().enumerate() {
+            let latitude = positions[i].z.asin();
+            let blocked = current.east == 0.0 && current.north != 0.0;
+            if terrain[i].ocean.u8() == 0 {
+                assert_eq!(Current::default(), *current);
+            } else if blocked && Weather::prevailing(latitude, cells).east < 0.0 {
+                assert_eq!(latitude.signum(), current.north.signum());
+                boundary += 1;
+            }
+        }
+
+        assert!(boundary > 0);
+    }
+
+    #[test]
+    fn heat_flows_downstream() {
+        let terrain = [Terrain::new(255, 0, 0); 2];
+        let adjacency = [AdjArray::from_iter(vec![1]), AdjArray::from_iter(vec![0])];
+        let mut downstream = [0.0; AdjArray::CAPACITY];
+        downstream[0] = OceanCurrents::SPEED;
+        let currents = OceanCurrents {
+            current: vec![Current::default(); 2],
+            flow: vec![downstream, [0.0; AdjArray::CAPACITY]],
+        };
+
+        let mut temperature = [Temperature::in_c(25.0), Temperature::in_c(5.0)];
+        let before = temperature[0].value + temperature[1].value;
+        let mut buffer = vec![];
+        currents.advect_heat(
+            &mut temperature,
+            &mut buffer,
+            &terrain,
+            &adjacency,
+            Duration::in_d(1.0),
+        );
+
+        assert!(temperature[0] < Temperature::in_c(25.0));
+        assert!(temperature[1] > Temperature::in_c(5.0));
+        assert!(temperature[1] < temperature[0]);
+        let after = temperature[0].value + temperature[1].value;
+        assert!((before - after).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::constants::G;
+use crate::moon::Primary;
+use crate::planet::Orbit;
+use physics_types::{Duration, Length, Mass};
+use std::f64::consts::PI;
+
+/// The mass and size of a body, enough to find the tides it raises and feels
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Body {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+}
+
+impl Body {
+    /// Mean density in kg/m³
+    pub fn density(&self) -> f64 {
+        self.mass.value / (4.0 / 3.0 * PI * self.radius.value.powi(3))
+    }
+}
+
+impl From<Primary> for Body {
+    fn from(primary: Primary) -> Self {
+        Self {
+            mass: primary.mass,
+            radius: primary.radius,
+        }
+    }
+}
+
+/// The distance from the primary inside which a rigid secondary would be pulled apart by tides.
+/// Fluid bodies like rubble piles break up further out, at about twice this distance.
+pub fn roche_limit(primary: Body, secondary: Body) -> Length {
+    secondary.radius * (2.0 * primary.mass.value / secondary.mass.value).cbrt()
+}
+
+/// The distance from an orbiting body within which its own gravity holds onto satellites,
+/// taken at the closest approach of an eccentric orbit.
+/// Satellites are only stable within about half of this distance.
+///
+/// # Arguments
+///
+/// * `orbit`: the orbit of the body with `mass` around `central_mass`
+pub fn hill_radius(orbit: &Orbit, mass: Mass, central_mass: Mass) -> Length {
+    let mass_ratio = mass.value / (3.0 * central_mass.value);
+    orbit.semi_major_axis * (1.0 - orbit.eccentricity) * mass_ratio.cbrt()
+}
+
+/// The time for tides raised by the primary to slow a body's rotation until it is tidally locked,
+/// treating the body as a uniform sphere
+///
+/// # Arguments
+///
+/// * `initial_rotation`: the body's rotation period before it was slowed
+/// * `tidal_response`: the ratio of the body's tidal Love number to its tidal quality factor, k₂/Q,
+///   about 0.001 for rocky moons
+pub fn tidal_lock_timescale(
+    body: Body,
+    semi_major_axis: Length,
+    primary_mass: Mass,
+    initial_rotation: Duration,
+    tidal_response: f64,
+) -> Duration {
+    let spin = 2.0 * PI / initial_rotation.value;
+    let moment_of_inertia = 0.4 * body.mass.value * body.radius.value.powi(2);
+
+    let torque = 3.0 * G * primary_mass.value.powi(2) * tidal_response * body.radius.value.powi(5);
+    Duration::in_s(spin * semi_major_axis.value.powi(6) * moment_of_inertia / torque)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::{EARTH_MASS, EARTH_RADIUS, SOLAR_MASS};
+
+    fn earth() -> Body {
+        Body {
+            mass: Mass::in_kg(EARTH_MASS),
+            radius: Length::in_m(EARTH_RADIUS),
+        }
+    }
+
+    fn moon() -> Body {
+        Body {
+            mass: Mass::in_kg(7.342e22),
+            radius: Length::in_m(1737e3),
+        }
+    }
+
+    #[test]
+    fn earth_moon_limits() {
+        let roche = roche_limit(earth(), moon());
+        assert!(roche > Length::in_m(9.0e6) && roche < Length::in_m(10.0e6));
+
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(149.6e9),
+            eccentricity: 0.0167,
+            period: Duration::in_yr(1.0),
+        };
+        let hill = hill_radius(&orbit, earth().mass, Mass::in_kg(SOLAR_MASS));
+        assert!(hill > Length::in_m(1.4e9) && hill < Length::in_m(1.6e9));
+    }
+
+    #[test]
+    fn close_moons_lock_quickly() {
+        let lock = |a: f64| {
+            tidal_lock_timescale(
+                moon(),
+                Length::in_m(a),
+                earth().mass,
+                Duration::in_hr(12.0),
+                1e-3,
+            )
+        };
+
+        let today = lock(384_400e3);
+        assert!(today > Duration::in_yr(1e6) && today < Duration::in_yr(1e8));
+        assert!(lock(100_000e3) < today);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::adjacency::AdjArray;
+use crate::climate::ClimateSummary;
+use crate::constants::GAS_CONSTANT;
+use crate::terrain::{downhill, Terrain};
+use crate::weather::Weather;
+use physics_types::{Acceleration, Area, Duration, FluxDensity, Power, Pressure, Temperature};
+
+/// Molar mass of dry air in kg/mol, assumed for any atmosphere
+const AIR_MOLAR_MASS: f64 = 0.02897;
+
+/// Fraction of precipitation on land that runs off into rivers rather than evaporating, about Earth's
+pub const RUNOFF: f64 = 0.35;
+
+/// Fraction of the falling water's energy that a hydroelectric turbine recovers
+pub const TURBINE_EFFICIENCY: f64 = 0.9;
+
+/// Density of the air at the surface in kg/m³, about 1.2 for Earth
+pub fn air_density(pressure: Pressure, temperature: Temperature) -> f64 {
+    pressure.value * AIR_MOLAR_MASS / (GAS_CONSTANT * temperature.value)
+}
+
+/// Kinetic energy carried by the prevailing wind through each m² facing it, ½ρv³.
+/// Turbines can extract at most 16/27 of this, by the Betz limit.
+///
+/// # Arguments
+///
+/// * `temperatures`: the surface temperature of each tile, which sets the density of the air
+pub fn wind_power(
+    weather: &Weather,
+    pressure: Pressure,
+    temperatures: &[Temperature],
+) -> Vec<FluxDensity> {
+    weather
+        .wind
+        .iter()
+        .zip(temperatures)
+        .map(|(wind, temperature)| {
+            let density = air_density(pressure, *temperature);
+            FluxDensity::in_w_per_m2(0.5 * density * wind.speed().powi(3))
+        })
+        .collect()
+}
+
+/// Power that dams could draw from the rivers of each tile,
+/// from the runoff gathered upstream falling to the next tile downhill, or to the sea.
+/// Zero on tiles without rivers, see `Terrain::river`.
+///
+/// # Arguments
+///
+/// * `climate`: the recorded climate, whose precipitation feeds the rivers
+/// * `tile_area`: the area of each tile, see `adjacency::get_tile_area`
+pub fn hydro_power(
+    terrain: &[Terrain],
+    adjacency: &[AdjArray],
+    climate: &ClimateSummary,
+    tile_area: Area,
+    surface_gravity: Acceleration,
+) -> Vec<Power> {
+    let seconds = Duration::in_yr(1.0) / Duration::in_s(1.0);
+
+    let mut order = (0..terrain.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| std::cmp::Reverse(terrain[*i].elevation));
+
+    // kg/s flowing out of each tile, gathered from the highest tiles down
+    let mut discharge = vec![0.0; terrain.len()];
+    let mut power = vec![Power::in_w(0.0); terrain.len()];
+
+    for i in order {
+        let tile = &terrain[i];
+        if tile.ocean.u8() == u8::MAX {
+            continue;
+        }
+
+        let land = 1.0 - tile.ocean.f64();
+        discharge[i] += RUNOFF * land * climate.annual_precipitation(i) * tile_area.value / seconds;
+
+        if let Some(n) = downhill(terrain, adjacency, i) {
+            let drop = tile.elevation.m() - terrain[n].elevation.m().max(0.0);
+            if tile.has_river() && drop > 0.0 {
+                let watts = discharge[i] * surface_gravity.value * drop * TURBINE_EFFICIENCY;
+                power[i] = Power::in_w(watts);
+            }
+            discharge[n] += discharge[i];
+        }
+    }
+
+    power
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::constants::EARTH_RADIUS;
+    use crate::terrain::Elevation;
+    use crate::tile_gen::{erode, TileGenScratch};
+    use physics_types::Length;
+    use std::iter::FromIterator;
+
+    #[test]
+    fn wind_in_thin_air() {
+        let earth = Temperature::in_c(15.0);
+        let density = air_density(Pressure::in_atm(1.0), earth);
+        assert!((density - 1.225).abs() < 0.01, "{}", density);
+
+        let mut adj = Adjacency::default();
+        adj.register(48);
+        let adjacency = adj.get(48);
+        let positions = adj.positions(48);
+        let weather = Weather::new(
+            adjacency,
+            positions,
+            Length::in_m(EARTH_RADIUS),
+            Duration::in_d(1.0),
+        );
+        let temperatures = [earth; 48];
+
+        let thick = wind_power(&weather, Pressure::in_atm(1.0), &temperatures);
+        let thin = wind_power(&weather, Pressure::in_atm(0.01), &temperatures);
+
+        let strongest = thick.iter().map(|p| p.value).fold(0.0, f64::max);
+        assert!(strongest > 100.0 && strongest < 500.0, "{}", strongest);
+        for (thick, thin) in thick.iter().zip(&thin) {
+            assert!((thin.value - 0.01 * thick.value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rivers_grow_downstream() {
+        let adjacency = [
+            AdjArray::from_iter(vec![1]),
+            AdjArray::from_iter(vec![0, 2]),
+            AdjArray::from_iter(vec![1, 3]),
+            AdjArray::from_iter(vec![2]),
+        ];
+        let mut terrain = [
+            Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(900.0)),
+            Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(600.0)),
+            Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(300.0)),
+            Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-1000.0)),
+        ];
+        erode(&mut terrain, &adjacency, 0, &mut TileGenScratch::default());
+
+        let mut climate = ClimateSummary::new(4);
+        let temperature = [Temperature::in_c(15.0); 4];
+        climate.record(&temperature, &[1000.0; 4], Duration::in_yr(1.0));
+
+        let area = Area::in_m2(1e10);
+        let gravity = Acceleration::in_m_per_s2(9.81);
+        let power = hydro_power(&terrain, &adjacency, &climate, area, gravity);
+
+        assert_eq!(Power::in_w(0.0), power[0]);
+        assert!(power[1] > Power::in_w(0.0));
+        assert!(power[2] > power[1]);
+        assert_eq!(Power::in_w(0.0), power[3]);
+    }
+}
@@ -0,0 +1,328 @@
+use crate::adjacency::units::Position3;
+use crate::terrain::Terrain;
+use physics_types::Temperature;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::io::{self, Write};
+
+const OCEAN: [f64; 3] = [28.0, 68.0, 140.0];
+const PLAINS: [f64; 3] = [96.0, 140.0, 64.0];
+const MOUNTAINS: [f64; 3] = [130.0, 110.0, 90.0];
+const GLACIER: [f64; 3] = [240.0, 240.0, 245.0];
+
+/// A blend of the colours of each surface type by its share of the tile, with glacier on top
+pub fn terrain_color(terrain: &Terrain) -> [u8; 3] {
+    let ocean = terrain.ocean.f64();
+    let plains = terrain.plains.f64();
+    let mountains = terrain.mountains.f64();
+    let glacier = terrain.glacier.f64();
+
+    let mut color = [0; 3];
+    for (c, channel) in color.iter_mut().enumerate() {
+        let ground = ocean * OCEAN[c] + plains * PLAINS[c] + mountains * MOUNTAINS[c];
+        let blend = ground + glacier * (GLACIER[c] - ground);
+        *channel = blend.round().clamp(0.0, 255.0) as u8;
+    }
+    color
+}
+
+/// Writes the tiles as a Wavefront OBJ mesh on a unit sphere, with vertex colours from `terrain_color`.
+/// Vertex colours follow the `v x y z r g b` extension read by Blender and MeshLab.
+///
+/// Each tile is a fan of triangles around its centre. Rim vertices are raised by the mean
+/// elevation of the tiles meeting there, so neighbouring tiles join without gaps.
+/// Oceans are drawn at sea level.
+///
+/// # Arguments
+///
+/// * `positions`: the centre of each tile, see `Tiling::positions`
+/// * `cells`: the polygon of each tile, see `Tiling::cells`
+/// * `relief`: radial displacement per metre of elevation, e.g., 1e-5 for an exaggerated view
+pub fn write_obj<W: Write>(
+    writer: &mut W,
+    positions: &[Position3],
+    cells: &[Vec<Position3>],
+    terrain: &[Terrain],
+    relief: f64,
+) -> io::Result<()> {
+    assert_eq!(positions.len(), cells.len());
+    assert_eq!(positions.len(), terrain.len());
+
+    let height = |tile: &Terrain| 1.0 + relief * tile.elevation.m().max(0.0);
+    let dot = |a: Position3, b: Position3| a.x * b.x + a.y * b.y + a.z * b.z;
+
+    writeln!(writer, "# {} tiles", positions.len())?;
+
+    let mut faces = vec![];
+    let mut count = 0usize;
+
+    for ((p, cell), tile) in positions.iter().zip(cells).zip(terrain) {
+        let color = terrain_color(tile);
+
+        write_vertex(writer, *p, height(tile), color)?;
+        count += 1;
+        let centre = count;
+
+        for v in cell {
+            // every tile with its centre nearest to the vertex meets there
+            let nearest = positions.iter().map(|q| dot(*v, *q)).fold(-1.0, f64::max);
+            let (sum, n) = positions
+                .iter()
+                .zip(terrain)
+                .filter(|(q, _)| nearest - dot(*v, **q) < 1e-6)
+                .fold((0.0, 0), |(sum, n), (_, t)| (sum + height(t), n + 1));
+
+            write_vertex(writer, *v, sum / n as f64, color)?;
+            count += 1;
+        }
+
+        for k in 0..cell.len() {
+            let next = (k + 1) % cell.len();
+            faces.push([centre, centre + 1 + k, centre + 1 + next]);
+        }
+    }
+
+    for [a, b, c] in faces {
+        writeln!(writer, "f {} {} {}", a, b, c)?;
+    }
+
+    Ok(())
+}
+
+fn write_vertex<W: Write>(
+    writer: &mut W,
+    v: Position3,
+    scale: f64,
+    color: [u8; 3],
+) -> io::Result<()> {
+    let [r, g, b] = color.map(|c| c as f64 / 255.0);
+    writeln!(
+        writer,
+        "v {:.6} {:.6} {:.6} {:.4} {:.4} {:.4}",
+        v.x * scale,
+        v.y * scale,
+        v.z * scale,
+        r,
+        g,
+        b
+    )
+}
+
+/// The quantity coloured on a map
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MapLayer {
+    Terrain,
+    Temperature,
+}
+
+/// Colours from cold to hot, with white at freezing, as (°C, colour)
+const TEMPERATURE_RAMP: [(f64, [f64; 3]); 3] = [
+    (-40.0, [40.0, 60.0, 200.0]),
+    (0.0, [255.0, 255.0, 255.0]),
+    (40.0, [200.0, 30.0, 30.0]),
+];
+
+pub fn temperature_color(temperature: Temperature) -> [u8; 3] {
+    let celsius = (temperature - Temperature::in_c(0.0)).value;
+    let t = celsius.clamp(TEMPERATURE_RAMP[0].0, TEMPERATURE_RAMP[2].0);
+    let (a, b) = if t < TEMPERATURE_RAMP[1].0 {
+        (TEMPERATURE_RAMP[0], TEMPERATURE_RAMP[1])
+    } else {
+        (TEMPERATURE_RAMP[1], TEMPERATURE_RAMP[2])
+    };
+    let f = (t - a.0) / (b.0 - a.0);
+
+    let mut color = [0; 3];
+    for (c, channel) in color.iter_mut().enumerate() {
+        *channel = (a.1[c] + f * (b.1[c] - a.1[c])).round() as u8;
+    }
+    color
+}
+
+/// Colours from dry to wet, as (annual precipitation in kg/m², colour)
+const PRECIPITATION_RAMP: [(f64, [f64; 3]); 3] = [
+    (0.0, [230.0, 210.0, 160.0]),
+    (1000.0, [80.0, 170.0, 80.0]),
+    (3000.0, [20.0, 60.0, 160.0]),
+];
+
+/// Colour for a tile's annual precipitation in kg/m², see `ClimateSummary::precipitation_map`
+pub fn precipitation_color(precipitation: f64) -> [u8; 3] {
+    let ramp = PRECIPITATION_RAMP;
+    let p = precipitation.clamp(ramp[0].0, ramp[2].0);
+    let (a, b) = if p < ramp[1].0 {
+        (ramp[0], ramp[1])
+    } else {
+        (ramp[1], ramp[2])
+    };
+    let f = (p - a.0) / (b.0 - a.0);
+
+    let mut color = [0; 3];
+    for (c, channel) in color.iter_mut().enumerate() {
+        *channel = (a.1[c] + f * (b.1[c] - a.1[c])).round() as u8;
+    }
+    color
+}
+
+/// An RGB image stored row by row from the top left
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Raster {
+    pub fn get(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Writes the image as a binary PPM, which most image viewers can open
+    pub fn write_ppm<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for pixel in &self.pixels {
+            writer.write_all(pixel)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders a map with longitude across and latitude down, colouring each pixel by its nearest tile.
+/// The planet's axis is the z axis of `positions`, and longitude zero is the x axis.
+///
+/// # Arguments
+///
+/// * `positions`: the centre of each tile, see `Tiling::positions`
+/// * `temperature`: the temperature of each tile, only read for `MapLayer::Temperature`
+pub fn render_equirectangular(
+    width: usize,
+    height: usize,
+    positions: &[Position3],
+    terrain: &[Terrain],
+    temperature: &[Temperature],
+    layer: MapLayer,
+) -> Raster {
+    assert_eq!(positions.len(), terrain.len());
+    if layer == MapLayer::Temperature {
+        assert_eq!(positions.len(), temperature.len());
+    }
+
+    let colors = match layer {
+        MapLayer::Terrain => terrain.iter().map(terrain_color).collect::<Vec<_>>(),
+        MapLayer::Temperature => temperature.iter().map(|t| temperature_color(*t)).collect(),
+    };
+
+    render_colors(width, height, positions, &colors)
+}
+
+/// Renders a map like `render_equirectangular` with a colour given for each tile,
+/// e.g., from `precipitation_color`
+pub fn render_colors(
+    width: usize,
+    height: usize,
+    positions: &[Position3],
+    colors: &[[u8; 3]],
+) -> Raster {
+    assert_eq!(positions.len(), colors.len());
+
+    let mut pixels = Vec::with_capacity(width * height);
+
+    for y in 0..height {
+        let latitude = FRAC_PI_2 - PI * (y as f64 + 0.5) / height as f64;
+        let (sin_lat, cos_lat) = latitude.sin_cos();
+
+        for x in 0..width {
+            let longitude = 2.0 * PI * (x as f64 + 0.5) / width as f64 - PI;
+            let (sin_lon, cos_lon) = longitude.sin_cos();
+            let (px, py, pz) = (cos_lat * cos_lon, cos_lat * sin_lon, sin_lat);
+
+            let tile = positions
+                .iter()
+                .map(|q| q.x * px + q.y * py + q.z * pz)
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+                .expect("no tiles to render");
+
+            pixels.push(colors[tile]);
+        }
+    }
+
+    Raster {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::{Spiral, Tiling};
+
+    #[test]
+    fn terrain_colors() {
+        let ocean = Terrain::new(255, 0, 0);
+        let ice = Terrain::new(0, 0, 255);
+
+        assert_eq!([28, 68, 140], terrain_color(&ocean));
+        assert_eq!([240, 240, 245], terrain_color(&ice));
+    }
+
+    #[test]
+    fn temperature_colors() {
+        assert_eq!([255, 255, 255], temperature_color(Temperature::in_c(0.0)));
+        assert_eq!([40, 60, 200], temperature_color(Temperature::in_c(-80.0)));
+        assert_eq!([200, 30, 30], temperature_color(Temperature::in_c(40.0)));
+    }
+
+    #[test]
+    fn precipitation_colors() {
+        assert_eq!([230, 210, 160], precipitation_color(0.0));
+        assert_eq!([80, 170, 80], precipitation_color(1000.0));
+        assert_eq!([20, 60, 160], precipitation_color(5000.0));
+    }
+
+    #[test]
+    fn equirectangular_poles() {
+        let nodes = 24;
+        let positions = Spiral.positions(nodes);
+        let mut terrain = vec![Terrain::new(255, 0, 0); nodes];
+        terrain[0] = Terrain::new(0, 0, 255);
+        terrain[nodes - 1] = Terrain::new(0, 0, 255);
+
+        let map = render_equirectangular(64, 32, &positions, &terrain, &[], MapLayer::Terrain);
+        let ice = terrain_color(&terrain[0]);
+        let ocean = terrain_color(&terrain[1]);
+
+        assert_eq!(64 * 32, map.pixels.len());
+        assert_eq!(ice, map.get(0, 0));
+        assert_eq!(ice, map.get(63, 31));
+        assert_eq!(ocean, map.get(32, 16));
+
+        let mut ppm = vec![];
+        map.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n64 32\n255\n"));
+        assert_eq!(64 * 32 * 3 + 13, ppm.len());
+    }
+
+    #[test]
+    fn obj_has_a_fan_per_tile() {
+        let nodes = 24;
+        let positions = Spiral.positions(nodes);
+        let cells = Spiral.cells(nodes);
+        let terrain = vec![Terrain::new(128, 50, 0); nodes];
+
+        let mut obj = vec![];
+        write_obj(&mut obj, &positions, &cells, &terrain, 1e-5).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+
+        let rim = cells.iter().map(Vec::len).sum::<usize>();
+        let vertices = obj.lines().filter(|l| l.starts_with("v ")).count();
+        let faces = obj.lines().filter(|l| l.starts_with("f ")).count();
+
+        assert_eq!(nodes + rim, vertices);
+        assert_eq!(rim, faces);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::planet::Orbit;
+use physics_types::{Angle, Duration, FluxDensity, Length, Power, TimeFloat};
+use std::f64::consts::{PI, TAU};
+
+/// Stellar flux reaching a planet on an elliptical orbit, and where it is in its seasons.
+///
+/// Eccentric orbits bring stronger sunlight near perihelion, which strengthens the seasons of
+/// whichever hemisphere has summer then, see `perihelion_longitude`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Forcing {
+    /// Flux density at a distance of one semi-major axis
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub flux: FluxDensity,
+    pub orbit: Orbit,
+    /// A time at which the planet passes perihelion
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub perihelion: TimeFloat,
+    /// Angle travelled from the northern spring equinox to perihelion,
+    /// e.g., about 283° for Earth, whose perihelion falls in the southern summer
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub perihelion_longitude: Angle,
+}
+
+impl Forcing {
+    pub fn new(luminosity: Power, orbit: Orbit, perihelion: TimeFloat) -> Self {
+        assert!((0.0..1.0).contains(&orbit.eccentricity));
+
+        let a = orbit.semi_major_axis.value;
+        Self {
+            flux: FluxDensity::in_w_per_m2(luminosity.value / (4.0 * PI * a * a)),
+            orbit,
+            perihelion,
+            perihelion_longitude: Angle::default(),
+        }
+    }
+
+    pub fn with_perihelion_longitude(mut self, longitude: Angle) -> Self {
+        self.perihelion_longitude = longitude;
+        self
+    }
+
+    /// Angle travelled since perihelion in radians, from 0 to 2π
+    pub fn true_anomaly(&self, time: TimeFloat) -> f64 {
+        let e = self.orbit.eccentricity;
+        let elapsed: Duration = time - self.perihelion;
+        let mean = (TAU * (elapsed / self.orbit.period)).rem_euclid(TAU);
+
+        // Kepler's equation by Newton's method
+        let mut eccentric = if e > 0.8 { PI } else { mean };
+        for _ in 0..32 {
+            let delta = (eccentric - e * eccentric.sin() - mean) / (1.0 - e * eccentric.cos());
+            eccentric -= delta;
+            if delta.abs() < 1e-14 {
+                break;
+            }
+        }
+
+        let (sin, cos) = (0.5 * eccentric).sin_cos();
+        let true_anomaly = 2.0 * ((1.0 + e).sqrt() * sin).atan2((1.0 - e).sqrt() * cos);
+        true_anomaly.rem_euclid(TAU)
+    }
+
+    pub fn distance(&self, time: TimeFloat) -> Length {
+        let e = self.orbit.eccentricity;
+        let a = self.orbit.semi_major_axis;
+        a * ((1.0 - e * e) / (1.0 + e * self.true_anomaly(time).cos()))
+    }
+
+    /// Stellar flux density at the planet's distance
+    pub fn flux(&self, time: TimeFloat) -> FluxDensity {
+        let ratio = self.orbit.semi_major_axis / self.distance(time);
+        self.flux * (ratio * ratio)
+    }
+
+    /// Fraction of the year since the northern spring equinox, see `insolation::intensity`
+    pub fn orbital_phase(&self, time: TimeFloat) -> f64 {
+        let longitude = self.true_anomaly(time) + self.perihelion_longitude.value;
+        (longitude / TAU).rem_euclid(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::SOLAR_LUMINOSITY;
+    use physics_types::AU;
+
+    fn forcing(eccentricity: f64) -> Forcing {
+        let orbit = Orbit {
+            semi_major_axis: AU,
+            eccentricity,
+            period: Duration::in_yr(1.0),
+        };
+        Forcing::new(Power::in_w(SOLAR_LUMINOSITY), orbit, TimeFloat::default())
+    }
+
+    #[test]
+    fn circular_orbit_has_constant_flux() {
+        let forcing = forcing(0.0);
+        let start = TimeFloat::default();
+
+        for month in 0..12 {
+            let time = start + Duration::in_yr(month as f64 / 12.0);
+            let flux = forcing.flux(time).value;
+            assert!((flux - 1361.0).abs() < 1.0, "{}", flux);
+        }
+    }
+
+    #[test]
+    fn eccentric_seasons() {
+        let e = 0.0167;
+        let forcing = forcing(e);
+        let perihelion = TimeFloat::default();
+        let aphelion = perihelion + Duration::in_yr(0.5);
+
+        let ratio = forcing.flux(perihelion) / forcing.flux(aphelion);
+        let expected = ((1.0 + e) / (1.0 - e)).powi(2);
+        assert!((ratio - expected).abs() < 1e-9);
+        assert!((forcing.true_anomaly(aphelion) - PI).abs() < 1e-9);
+    }
+
+    #[test]
+    fn orbital_phase_from_perihelion() {
+        let forcing = forcing(0.0167).with_perihelion_longitude(Angle::in_deg(283.0));
+        let phase = forcing.orbital_phase(TimeFloat::default());
+
+        assert!((phase - 283.0 / 360.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::atmosphere::Atmosphere;
+use crate::climate::ClimateModel;
+use crate::solar_radiation::Gas;
+use crate::terrain::Terrain;
+use fractional_int::FractionalU8;
+use physics_types::{Acceleration, Pressure, Temperature};
+
+/// A gas freezing out of the atmosphere onto tiles colder than its frost point,
+/// such as the seasonal CO2 caps of Mars.
+///
+/// Freezing releases latent heat that holds a tile near the frost point,
+/// and sublimation takes it back when the tile warms. Frost brightens the ground beneath it,
+/// see `GroundCover`, and the gas it holds is removed from the atmosphere's pressure.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrostModel {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::gas"))]
+    pub gas: Gas,
+    /// Frozen gas on each tile in kg/m²
+    pub frost: Vec<f64>,
+}
+
+impl FrostModel {
+    /// Frost in kg/m² that covers the whole tile
+    pub const COVER: f64 = 50.0;
+
+    /// No frost on the tiles of the climate model, whose frost cover is then managed by `step`
+    pub fn new(gas: Gas, climate: &ClimateModel) -> Self {
+        assert!(gas.latent_heat().is_some(), "{:?} doesn't freeze", gas);
+
+        Self {
+            gas,
+            frost: vec![0.0; climate.temperature.len()],
+        }
+    }
+
+    /// Fraction of the tile covered by frost
+    pub fn cover(&self, tile: usize) -> FractionalU8 {
+        FractionalU8::new_f64((self.frost[tile] / Self::COVER).min(1.0))
+    }
+
+    /// Freezes gas onto tiles below its frost point, and sublimates frost from tiles above it
+    ///
+    /// # Arguments
+    ///
+    /// * `surface_gravity`: relates the atmosphere's mass to its pressure
+    pub fn step(
+        &mut self,
+        climate: &mut ClimateModel,
+        terrain: &[Terrain],
+        atmosphere: &mut Atmosphere,
+        surface_gravity: Acceleration,
+    ) {
+        let gas = self.gas;
+        let latent = gas.latent_heat().unwrap();
+        let partial = atmosphere.partial_pressure(gas);
+        let frost_point = gas.frost_point(partial).unwrap();
+
+        // mass of each tile that would freeze (positive) or sublimate (negative) to reach the frost point
+        let mut change = climate
+            .temperature
+            .iter()
+            .zip(terrain)
+            .zip(&self.frost)
+            .map(|((temp, terrain), frost)| {
+                let surface = terrain.surface_temperature(*temp);
+                let deficit = (frost_point - surface).value;
+                let mass = deficit * terrain.heat_capacity().value / latent;
+                mass.max(-frost)
+            })
+            .collect::<Vec<_>>();
+
+        // kg/m² of the planet's surface, weighting each tile by its area
+        let area = &climate.area;
+        let total = |change: &[f64], keep: fn(f64) -> bool| -> f64 {
+            change
+                .iter()
+                .zip(area)
+                .filter(|(m, _)| keep(**m))
+                .map(|(m, a)| m * a)
+                .sum()
+        };
+
+        // can't freeze more gas than the atmosphere holds
+        let freezing = total(&change, |m| m > 0.0);
+        let sublimating = -total(&change, |m| m < 0.0);
+        let available = partial.value / surface_gravity.value + sublimating;
+        if freezing > available {
+            let scale = available / freezing;
+            change
+                .iter_mut()
+                .filter(|m| **m > 0.0)
+                .for_each(|m| *m *= scale);
+        }
+
+        let frozen = total(&change, |_| true);
+
+        let iter = climate
+            .temperature
+            .iter_mut()
+            .zip(climate.cover.iter_mut())
+            .zip(self.frost.iter_mut())
+            .zip(terrain)
+            .zip(&change);
+
+        for ((((temp, cover), frost), terrain), mass) in iter {
+            *frost += mass;
+            *temp += Temperature::in_k(mass * latent / terrain.heat_capacity().value);
+
+            cover.frost = FractionalU8::new_f64((*frost / Self::COVER).min(1.0));
+        }
+
+        climate.update_ground();
+
+        let pressure = Pressure::in_pa(-frozen * surface_gravity.value);
+        atmosphere.add_partial_pressure(gas, pressure);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::climate::ClimateConfig;
+
+    fn mars_gravity() -> Acceleration {
+        Acceleration::in_m_per_s2(3.72)
+    }
+
+    #[test]
+    fn co2_frost_point_on_mars() {
+        let partial = Atmosphere::mars().partial_pressure(Gas::CarbonDioxide);
+        let frost_point = Gas::CarbonDioxide.frost_point(partial).unwrap();
+
+        assert!((frost_point.value - 148.0).abs() < 3.0, "{:?}", frost_point);
+        assert!(Gas::Nitrogen.frost_point(partial).unwrap() < Temperature::in_k(55.0));
+    }
+
+    #[test]
+    fn polar_cap_freezes_and_sublimates() {
+        let land = Terrain::new(0, 50, 0);
+        let terrain = [land, land];
+        let mut atmosphere = Atmosphere::mars();
+        let mut climate = ClimateModel::new(ClimateConfig::mars(), 2, Temperature::in_k(210.0));
+        let mut frost = FrostModel::new(Gas::CarbonDioxide, &climate);
+        let before = atmosphere.pressure;
+
+        climate.temperature[0] = Temperature::in_k(140.0);
+        frost.step(&mut climate, &terrain, &mut atmosphere, mars_gravity());
+
+        assert!(frost.frost[0] > 0.0);
+        assert_eq!(0.0, frost.frost[1]);
+        assert!(atmosphere.pressure < before);
+        assert!(climate.ground[0] < climate.ground[1]);
+        assert!(climate.temperature[0] > Temperature::in_k(140.0));
+
+        climate.temperature[0] = Temperature::in_k(210.0);
+        frost.step(&mut climate, &terrain, &mut atmosphere, mars_gravity());
+
+        assert_eq!(0.0, frost.frost[0]);
+        assert!((atmosphere.pressure / before - 1.0).abs() < 1e-9);
+        assert_eq!(climate.ground[1], climate.ground[0]);
+    }
+
+    #[test]
+    fn frost_covers_plants_without_replacing_them() {
+        let land = Terrain::new(0, 50, 0);
+        let terrain = [land, land];
+        let mut atmosphere = Atmosphere::mars();
+        let mut climate = ClimateModel::new(ClimateConfig::mars(), 2, Temperature::in_k(210.0));
+        let mut frost = FrostModel::new(Gas::CarbonDioxide, &climate);
+
+        climate.cover[0].vegetation = FractionalU8::new_f64(1.0);
+        climate.update_ground();
+        let forest = climate.ground[0];
+
+        climate.temperature[0] = Temperature::in_k(140.0);
+        frost.step(&mut climate, &terrain, &mut atmosphere, mars_gravity());
+        assert!(climate.ground[0] < forest);
+
+        climate.temperature[0] = Temperature::in_k(210.0);
+        frost.step(&mut climate, &terrain, &mut atmosphere, mars_gravity());
+        assert_eq!(forest, climate.ground[0]);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::adjacency::AdjArray;
+use crate::climate::ClimateSummary;
+use crate::constants::EARTH_MASS;
+use crate::tectonics::Tectonics;
+use crate::terrain::{Elevation, Terrain};
+use crate::tile_gen::{Progress, TileGenError};
+use crate::watershed::drainage_sinks;
+use fractional_int::FractionalU8;
+use physics_types::{Duration, FluxDensity, Mass};
+
+/// Fraction of a tile's mountains worn away per million years, per metre of annual precipitation
+pub const RAIN_EROSION: f64 = 0.05;
+/// Fraction of a tile's mountains worn away per million years under complete glacier cover
+pub const GLACIAL_EROSION: f64 = 0.1;
+
+/// Heat flux through Earth's surface from its interior in W/m²
+pub const EARTH_HEAT_FLUX: f64 = 0.087;
+/// Time for a rocky planet's internal heat to fall by a factor of e, from radioactive decay and cooling
+pub const HEAT_DECAY_TIME: f64 = 3.0e9;
+
+/// Mean heat flux through the surface of a rocky planet from radioactive decay and primordial heat,
+/// scaled from Earth's flux at 4.5 billion years, see `ClimateModel::internal_heat`.
+///
+/// Heat production grows with mass, and rocky radii grow as mass to the 0.27, so larger planets
+/// have a higher flux through each square metre. Younger planets are hotter.
+pub fn internal_heat_flux(mass: Mass, age: Duration) -> FluxDensity {
+    const EARTH_AGE: f64 = 4.5e9;
+
+    let relative_mass = mass.value / EARTH_MASS;
+    let years = age / Duration::in_yr(1.0);
+    let decay = ((EARTH_AGE - years) / HEAT_DECAY_TIME).exp();
+
+    FluxDensity::in_w_per_m2(EARTH_HEAT_FLUX * relative_mass.powf(1.0 - 2.0 * 0.27) * decay)
+}
+
+/// Fast-forwards the terrain through geologic time, building mountains along plate boundaries
+/// while the climate wears them down and fills basins with the sediment.
+///
+/// # Arguments
+///
+/// * `climate`: the recorded climate, assumed to hold for the whole duration
+/// * `dt`: the geologic time step, typically 10⁵ to 10⁶ years
+pub fn evolve(
+    terrain: &mut [Terrain],
+    adjacency: &[AdjArray],
+    tectonics: &mut Tectonics,
+    climate: &ClimateSummary,
+    duration: Duration,
+    dt: Duration,
+) {
+    let progress = &mut |_: f64| true;
+    let result = evolve_with_progress(
+        terrain, adjacency, tectonics, climate, duration, dt, progress,
+    );
+    debug_assert!(result.is_ok());
+}
+
+/// Fast-forwards the terrain as in `evolve`, reporting the fraction of `duration` covered after each step.
+/// Returns `TileGenError::Cancelled` if the progress report cancels it, leaving the terrain part way.
+pub fn evolve_with_progress<P: Progress>(
+    terrain: &mut [Terrain],
+    adjacency: &[AdjArray],
+    tectonics: &mut Tectonics,
+    climate: &ClimateSummary,
+    duration: Duration,
+    dt: Duration,
+    progress: &mut P,
+) -> Result<(), TileGenError> {
+    let steps = (duration / dt).ceil() as usize;
+
+    for step in 0..steps {
+        tectonics.step(terrain, adjacency, dt);
+        erode_by_climate(terrain, adjacency, climate, dt);
+
+        if !progress.report((step + 1) as f64 / steps as f64) {
+            return Err(TileGenError::Cancelled);
+        }
+    }
+
+    Ok(())
+}
+
+/// Erodes mountains at rates set by precipitation and glaciation.
+/// The sediment is carried downhill and deposited in the sink of each drainage basin,
+/// filling it no higher than its lowest neighbour.
+pub fn erode_by_climate(
+    terrain: &mut [Terrain],
+    adjacency: &[AdjArray],
+    climate: &ClimateSummary,
+    dt: Duration,
+) {
+    let myr = dt / Duration::in_yr(1e6);
+    let sinks = drainage_sinks(terrain, adjacency);
+    let mut sediment = vec![0.0; terrain.len()];
+
+    for (i, tile) in terrain.iter_mut().enumerate() {
+        let rain = climate.annual_precipitation(i) / 1000.0;
+        let rate = RAIN_EROSION * rain + GLACIAL_EROSION * tile.glacier.f64();
+        let fraction = 1.0 - (-rate * myr).exp();
+        let eroded = (tile.mountains.u8() as f64 * fraction).round() as u8;
+
+        tile.mountains = FractionalU8::new(tile.mountains.u8() - eroded);
+        tile.plains = FractionalU8::new(tile.plains.u8() + eroded);
+
+        // mountains account for up to 4000 m of a tile's elevation
+        let elevation = tile.elevation.m();
+        if elevation > 0.0 {
+            let lowered = (4000.0 * eroded as f64 / 255.0).min(elevation);
+            tile.elevation = Elevation::in_m(elevation - lowered);
+            sediment[sinks[i]] += lowered;
+        }
+    }
+
+    for (sink, sediment) in sediment.into_iter().enumerate() {
+        if sediment == 0.0 {
+            continue;
+        }
+
+        let elevation = terrain[sink].elevation.m();
+        let spill = adjacency[sink]
+            .iter()
+            .map(|n| terrain[n].elevation.m())
+            .fold(f64::INFINITY, f64::min);
+
+        let raised = sediment.min((spill - elevation).max(0.0));
+        terrain[sink].elevation = Elevation::in_m(elevation + raised);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::line;
+    use crate::adjacency::units::Position3;
+    use crate::constants::EARTH_RADIUS;
+    use crate::tectonics::Plate;
+    use physics_types::{Length, Temperature};
+
+    const N: usize = 5;
+
+    /// A mountain range draining into a hollow at tile 0
+    fn range() -> (Vec<Terrain>, Vec<AdjArray>) {
+        let terrain = (0..N)
+            .map(|i| {
+                let mountains = if i == 0 { 0 } else { 200 };
+                let elevation = Elevation::in_m(1000.0 * i as f64);
+                Terrain::new(0, mountains, 0).with_elevation(elevation)
+            })
+            .collect();
+
+        (terrain, line(N))
+    }
+
+    fn climate(precipitation: f64) -> ClimateSummary {
+        let mut climate = ClimateSummary::new(N);
+        let temperature = vec![Temperature::in_c(15.0); N];
+        climate.record(&temperature, &[precipitation; N], Duration::in_yr(1.0));
+        climate
+    }
+
+    /// A single plate that doesn't drift, under tiles spaced along the equator
+    fn still_plate() -> Tectonics {
+        let plate = Plate {
+            pole: [0.0, 0.0, 1.0],
+            rate: 0.0,
+        };
+        let positions = (0..N)
+            .map(|i| {
+                let longitude = 0.1 * i as f64;
+                Position3 {
+                    x: longitude.cos(),
+                    y: longitude.sin(),
+                    z: 0.0,
+                }
+            })
+            .collect();
+
+        Tectonics::new(
+            vec![plate],
+            vec![0; N],
+            positions,
+            Length::in_m(EARTH_RADIUS),
+        )
+    }
+
+    fn mountains(terrain: &[Terrain]) -> u32 {
+        terrain.iter().map(|t| t.mountains.u8() as u32).sum()
+    }
+
+    #[test]
+    fn internal_heat_by_mass_and_age() {
+        let earth = Mass::in_kg(EARTH_MASS);
+        let today = Duration::in_yr(4.5e9);
+        let flux = |mass: Mass, age: Duration| internal_heat_flux(mass, age).value;
+
+        assert!((flux(earth, today) - EARTH_HEAT_FLUX).abs() < 1e-12);
+        assert!(flux(earth, Duration::in_yr(1e9)) > flux(earth, today));
+        assert!(flux(earth * 5.0, today) > flux(earth, today));
+        assert!(flux(earth * 0.1, today) < flux(earth, today));
+    }
+
+    #[test]
+    fn wet_worlds_erode_faster() {
+        let (mut wet, adjacency) = range();
+        let (mut dry, _) = range();
+
+        for _ in 0..10 {
+            erode_by_climate(&mut wet, &adjacency, &climate(2000.0), Duration::in_yr(1e6));
+            erode_by_climate(&mut dry, &adjacency, &climate(50.0), Duration::in_yr(1e6));
+        }
+
+        assert!(mountains(&wet) < mountains(&dry));
+        assert!(wet[4].elevation < dry[4].elevation);
+    }
+
+    #[test]
+    fn sediment_fills_basins() {
+        let (mut terrain, adjacency) = range();
+        erode_by_climate(
+            &mut terrain,
+            &adjacency,
+            &climate(2000.0),
+            Duration::in_yr(1e6),
+        );
+
+        assert!(terrain[0].elevation.m() > 0.0);
+        assert!(terrain[0].elevation <= terrain[1].elevation);
+        for tile in &terrain {
+            assert_eq!(
+                255,
+                tile.ocean.u8() + tile.plains.u8() + tile.mountains.u8()
+            );
+        }
+    }
+
+    #[test]
+    fn evolve_without_drift_only_erodes() {
+        let (mut terrain, adjacency) = range();
+        let mut tectonics = still_plate();
+        let before = mountains(&terrain);
+
+        evolve(
+            &mut terrain,
+            &adjacency,
+            &mut tectonics,
+            &climate(1000.0),
+            Duration::in_yr(1e7),
+            Duration::in_yr(1e6),
+        );
+
+        assert!(mountains(&terrain) < before);
+    }
+
+    #[test]
+    fn evolve_can_be_cancelled() {
+        let (mut terrain, adjacency) = range();
+        let mut tectonics = still_plate();
+        let mut reports = vec![];
+
+        let result = evolve_with_progress(
+            &mut terrain,
+            &adjacency,
+            &mut tectonics,
+            &climate(1000.0),
+            Duration::in_yr(1e7),
+            Duration::in_yr(1e6),
+            &mut |fraction: f64| {
+                reports.push(fraction);
+                fraction < 0.25
+            },
+        );
+
+        assert_eq!(Err(TileGenError::Cancelled), result);
+        assert_eq!(3, reports.len());
+    }
+}
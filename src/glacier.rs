@@ -0,0 +1,146 @@
+use crate::hydrology::Hydrology;
+use crate::terrain::Terrain;
+use fractional_int::FractionalU8;
+use physics_types::{Duration, Temperature};
+
+/// Grows and melts glaciers from each tile's temperature history using degree-days.
+/// Changes to `Terrain::glacier` feed back into `Terrain::absorption`.
+///
+/// Ice grows over mountains, then plains, then the ocean as sea ice, and melts in the reverse order.
+/// Melting sea ice returns to the ocean in place, while melting land ice runs off as `meltwater`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlacierModel {
+    /// Ice deposited per day below freezing in kg/m².
+    /// Set to zero when snowfall is supplied by [`Hydrology`] to avoid counting it twice.
+    pub accumulation: f64,
+    /// Ice melted per degree-day above freezing in kg/m²
+    pub melt_factor: f64,
+    /// Runoff from land ice melted during the last step in kg/m², which drains to the ocean
+    pub meltwater: Vec<f64>,
+    /// Mass balance of each tile that hasn't yet amounted to a whole increment of cover, in kg/m²
+    balance: Vec<f64>,
+}
+
+impl GlacierModel {
+    pub fn new(tiles: usize) -> Self {
+        Self {
+            accumulation: 0.3,
+            melt_factor: 7.0,
+            meltwater: vec![0.0; tiles],
+            balance: vec![0.0; tiles],
+        }
+    }
+
+    pub fn step(&mut self, terrain: &mut [Terrain], temperature: &[Temperature], dt: Duration) {
+        let days = dt / Duration::in_d(1.0);
+        let freezing = Temperature::in_c(0.0);
+        let increment = Hydrology::GLACIER_COVER / u8::MAX as f64;
+
+        self.balance.resize(terrain.len(), 0.0);
+        self.meltwater.resize(terrain.len(), 0.0);
+
+        let iter = self
+            .balance
+            .iter_mut()
+            .zip(self.meltwater.iter_mut())
+            .zip(terrain.iter_mut())
+            .zip(temperature);
+
+        for (((balance, meltwater), terrain), temp) in iter {
+            let land_ice = terrain.land_ice().u8();
+
+            let degrees = (*temp - freezing).value;
+
+            if degrees < 0.0 {
+                *balance += self.accumulation * days;
+            } else {
+                *balance -= self.melt_factor * degrees * days;
+            }
+
+            let glacier = terrain.glacier.u8() as f64;
+            let change = (*balance / increment)
+                .trunc()
+                .clamp(-glacier, u8::MAX as f64 - glacier);
+
+            terrain.glacier = FractionalU8::new((glacier + change) as u8);
+            *balance -= change * increment;
+
+            let melted = land_ice - terrain.land_ice().u8().min(land_ice);
+            *meltwater = melted as f64 * increment;
+
+            // bare tiles can't bank melt and fully covered tiles can't bank growth
+            *balance = balance.clamp(-increment, increment);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solar_radiation::RadiativeAbsorption;
+
+    fn run(terrain: &mut Terrain, temperature: Temperature, days: usize) {
+        let mut model = GlacierModel::new(1);
+        for _ in 0..days {
+            model.step(
+                std::slice::from_mut(terrain),
+                &[temperature],
+                Duration::in_d(1.0),
+            );
+        }
+    }
+
+    #[test]
+    fn accumulates_below_freezing() {
+        let mut terrain = Terrain::new(0, 100, 0);
+        run(&mut terrain, Temperature::in_c(-10.0), 365);
+        assert!(terrain.glacier.u8() > 0);
+    }
+
+    #[test]
+    fn melts_above_freezing() {
+        let mut terrain = Terrain::new(0, 100, 255);
+        run(&mut terrain, Temperature::in_c(5.0), 30);
+        assert!(terrain.glacier.u8() < 255);
+    }
+
+    #[test]
+    fn bare_ground_does_not_bank_melt() {
+        let mut terrain = Terrain::new(0, 100, 0);
+        let mut model = GlacierModel::new(1);
+        let tiles = std::slice::from_mut(&mut terrain);
+
+        model.step(tiles, &[Temperature::in_c(30.0)], Duration::in_d(365.0));
+        model.step(tiles, &[Temperature::in_c(-10.0)], Duration::in_d(365.0));
+
+        assert!(terrain.glacier.u8() > 0);
+    }
+
+    #[test]
+    fn only_land_ice_runs_off() {
+        let mut model = GlacierModel::new(2);
+        let mut terrain = [Terrain::new(255, 0, 255), Terrain::new(0, 100, 255)];
+        let warm = [Temperature::in_c(5.0); 2];
+
+        model.step(&mut terrain, &warm, Duration::in_d(30.0));
+
+        assert!(terrain[0].glacier.u8() < 255);
+        assert_eq!(0.0, model.meltwater[0]);
+        assert!(terrain[1].glacier.u8() < 255);
+        assert!(model.meltwater[1] > 0.0);
+    }
+
+    #[test]
+    fn ice_albedo_feedback() {
+        let ground = RadiativeAbsorption::new(0.8);
+        let clouds = FractionalU8::default();
+
+        let mut terrain = Terrain::new(0, 100, 0);
+        let before = terrain.absorption(ground, clouds);
+        run(&mut terrain, Temperature::in_c(-10.0), 3650);
+        let after = terrain.absorption(ground, clouds);
+
+        assert!(after < before);
+    }
+}
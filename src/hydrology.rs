@@ -0,0 +1,333 @@
+use crate::adjacency::AdjArray;
+use crate::solar_radiation::Gas;
+use crate::terrain::Terrain;
+use crate::weather::Weather;
+use fractional_int::FractionalU8;
+use physics_types::{Duration, Pressure, Temperature};
+
+/// Per-tile water cycle, with quantities in kg/m² (equivalent to mm of water)
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hydrology {
+    /// Precipitable water in the air above each tile
+    pub humidity: Vec<f64>,
+    /// Precipitation during the last step
+    pub precipitation: Vec<f64>,
+    /// Snow that hasn't yet built up to a whole increment of glacier cover
+    pub snowpack: Vec<f64>,
+    /// Carries water vapour downwind if set, see `with_wind`
+    pub wind: Option<Weather>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    transport: Vec<f64>,
+}
+
+impl Hydrology {
+    /// Evaporation from open ocean at 15 °C in kg/m²/day
+    pub const EVAPORATION: f64 = 3.5;
+    /// Precipitable water that saturates the air at 15 °C in kg/m²
+    pub const CAPACITY: f64 = 50.0;
+    /// Fractional increase in saturation vapour pressure per kelvin (Clausius–Clapeyron)
+    pub const CLAUSIUS_CLAPEYRON: f64 = 0.07;
+    /// Mean time spent by water vapour in the air before precipitating, in days
+    pub const RESIDENCE_TIME: f64 = 9.0;
+    /// Fraction of the difference from the neighbour average exchanged per day
+    pub const TRANSPORT: f64 = 0.3;
+    /// Snowfall needed to completely cover a tile with glacier in kg/m²
+    pub const GLACIER_COVER: f64 = 5000.0;
+    /// Climb in m that shortens the residence time of water vapour by half,
+    /// as air rising over mountains cools and rains out on the windward slopes
+    pub const OROGRAPHIC_HEIGHT: f64 = 500.0;
+
+    pub fn new(tiles: usize) -> Self {
+        Self {
+            humidity: vec![0.0; tiles],
+            precipitation: vec![0.0; tiles],
+            snowpack: vec![0.0; tiles],
+            wind: None,
+            transport: vec![0.0; tiles],
+        }
+    }
+
+    /// Blows water vapour downwind and rains it out where the wind climbs mountains,
+    /// leaving rain shadows on their lee sides
+    pub fn with_wind(mut self, weather: Weather) -> Self {
+        self.wind = Some(weather);
+        self
+    }
+
+    /// The temperature at which water boils under the given surface pressure
+    pub fn boiling_point(pressure: Pressure) -> Temperature {
+        Gas::Water.boiling_point(pressure)
+    }
+
+    /// Relative to the value at 15 °C
+    fn saturation(temperature: Temperature) -> f64 {
+        let delta = temperature - Temperature::in_c(15.0);
+        (Self::CLAUSIUS_CLAPEYRON * delta.value).exp()
+    }
+
+    /// The ratio of each tile's water vapour to what would saturate the air at the given temperature
+    pub fn relative_humidity<'a>(
+        &'a self,
+        temperature: &'a [Temperature],
+    ) -> impl Iterator<Item = f64> + 'a {
+        self.humidity
+            .iter()
+            .zip(temperature)
+            .map(|(humidity, temp)| humidity / (Self::CAPACITY * Self::saturation(*temp)))
+    }
+
+    /// Advances the water cycle given the surface temperature of each tile.
+    /// Precipitation on tiles below freezing accumulates as glacier.
+    /// Vapour drifts toward drier neighbours, and downwind if `wind` is set.
+    pub fn step(
+        &mut self,
+        terrain: &mut [Terrain],
+        temperature: &[Temperature],
+        adjacency: &[AdjArray],
+        dt: Duration,
+    ) {
+        let days = dt / Duration::in_d(1.0);
+
+        for ((humidity, terrain), temp) in self
+            .humidity
+            .iter_mut()
+            .zip(terrain.iter())
+            .zip(temperature)
+        {
+            let open_ocean = (!terrain.glacier).min(terrain.ocean).f64();
+            *humidity += Self::EVAPORATION * open_ocean * Self::saturation(*temp) * days;
+        }
+
+        if let Some(weather) = &self.wind {
+            weather.advect(&mut self.humidity, &mut self.transport, adjacency, dt);
+        }
+
+        self.transport(adjacency, days);
+
+        // reuse the transport buffer for the height the wind lifts the air over each tile
+        for (i, lift) in self.transport.iter_mut().enumerate() {
+            *lift = match &self.wind {
+                Some(weather) => weather.windward_rise(terrain, adjacency, i),
+                None => 0.0,
+            };
+        }
+
+        let freezing = Temperature::in_c(0.0);
+
+        let iter = self
+            .humidity
+            .iter_mut()
+            .zip(self.precipitation.iter_mut())
+            .zip(self.snowpack.iter_mut())
+            .zip(terrain.iter_mut())
+            .zip(temperature)
+            .zip(self.transport.iter());
+
+        for (((((humidity, precipitation), snowpack), terrain), temp), lift) in iter {
+            let uplift = 1.0 + lift / Self::OROGRAPHIC_HEIGHT;
+            let rain_out = 1.0 - (-days * uplift / Self::RESIDENCE_TIME).exp();
+            let capacity = Self::CAPACITY * Self::saturation(*temp);
+            let excess = (*humidity * (1.0 - rain_out) - capacity).max(0.0);
+
+            *precipitation = *humidity * rain_out + excess;
+            *humidity -= *precipitation;
+
+            if *temp < freezing {
+                *snowpack += *precipitation;
+                Self::accumulate(snowpack, terrain);
+            }
+        }
+    }
+
+    fn transport(&mut self, adjacency: &[AdjArray], days: f64) {
+        let rate = 1.0 - (1.0 - Self::TRANSPORT).powf(days);
+        let humidity = &self.humidity;
+
+        self.transport.resize(humidity.len(), 0.0);
+
+        for (i, transport) in self.transport.iter_mut().enumerate() {
+            let (sum, count) = adjacency[i].iter().fold((0.0, 0usize), |(sum, count), n| {
+                (sum + humidity[n], count + 1)
+            });
+
+            *transport = if count == 0 {
+                0.0
+            } else {
+                (sum / count as f64 - humidity[i]) * rate
+            };
+        }
+
+        for (humidity, transport) in self.humidity.iter_mut().zip(self.transport.iter()) {
+            *humidity += *transport;
+        }
+    }
+
+    /// Converts whole increments of snowpack into glacier cover
+    fn accumulate(snowpack: &mut f64, terrain: &mut Terrain) {
+        let increment = Self::GLACIER_COVER / u8::MAX as f64;
+        let glacier = terrain.glacier.u8();
+
+        if glacier == u8::MAX {
+            *snowpack = snowpack.min(increment);
+            return;
+        }
+
+        let growth = (*snowpack / increment)
+            .floor()
+            .min((u8::MAX - glacier) as f64) as u8;
+        terrain.glacier = FractionalU8::new(glacier + growth);
+        *snowpack -= growth as f64 * increment;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::constants::EARTH_RADIUS;
+    use crate::terrain::Elevation;
+    use crate::weather::tangent_direction;
+    use physics_types::Length;
+
+    const N: usize = 16;
+
+    fn adjacency() -> Vec<AdjArray> {
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        adj.get(N).to_vec()
+    }
+
+    fn run(terrain: &mut [Terrain], temperature: Temperature, days: usize) -> Hydrology {
+        let adjacency = adjacency();
+        let temperature = vec![temperature; N];
+        let mut hydrology = Hydrology::new(N);
+
+        for _ in 0..days {
+            hydrology.step(terrain, &temperature, &adjacency, Duration::in_d(1.0));
+        }
+
+        hydrology
+    }
+
+    #[test]
+    fn dry_planet_stays_dry() {
+        let mut terrain = vec![Terrain::new(0, 100, 0); N];
+        let hydrology = run(&mut terrain, Temperature::in_c(15.0), 30);
+
+        assert!(hydrology.humidity.iter().all(|h| *h == 0.0));
+    }
+
+    #[test]
+    fn moisture_reaches_land() {
+        let mut terrain = vec![Terrain::new(0, 100, 0); N];
+        terrain[0] = Terrain::new(255, 0, 0);
+        let hydrology = run(&mut terrain, Temperature::in_c(15.0), 30);
+
+        assert!(hydrology.precipitation[N - 1] > 0.0);
+    }
+
+    #[test]
+    fn rain_shadow() {
+        const N: usize = 64;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        let adjacency = adj.get(N);
+        let positions = adj.positions(N);
+        let weather = Weather::new(
+            adjacency,
+            positions,
+            Length::in_m(EARTH_RADIUS),
+            Duration::in_d(1.0),
+        );
+
+        let position = |i: usize| positions[i];
+        let windiest = (0..N)
+            .max_by(|a, b| {
+                weather.wind[*a]
+                    .speed()
+                    .total_cmp(&weather.wind[*b].speed())
+            })
+            .unwrap();
+        let wind = weather.wind[windiest];
+        let lee = adjacency[windiest]
+            .iter()
+            .max_by(|a, b| {
+                let along = |n: usize| {
+                    let (east, north) = tangent_direction(position(windiest), position(n));
+                    wind.east * east + wind.north * north
+                };
+                along(*a).total_cmp(&along(*b))
+            })
+            .unwrap();
+
+        let run = |height: f64| {
+            let mut terrain = vec![Terrain::new(255, 0, 0); N];
+            terrain[windiest] = Terrain::new(0, 200, 0).with_elevation(Elevation::in_m(height));
+            terrain[lee] = Terrain::new(0, 0, 0);
+            let temperature = vec![Temperature::in_c(15.0); N];
+            let mut hydrology = Hydrology::new(N).with_wind(weather.clone());
+
+            let mut total = vec![0.0; N];
+            for _ in 0..60 {
+                hydrology.step(&mut terrain, &temperature, adjacency, Duration::in_d(1.0));
+                for (total, precipitation) in total.iter_mut().zip(&hydrology.precipitation) {
+                    *total += precipitation;
+                }
+            }
+            total
+        };
+
+        let flat = run(0.0);
+        let mountain = run(3000.0);
+
+        assert!(mountain[windiest] > flat[windiest]);
+        assert!(mountain[lee] < flat[lee]);
+    }
+
+    #[test]
+    fn snow_builds_glaciers_on_cold_tiles() {
+        let mut terrain = vec![Terrain::new(128, 50, 0); N];
+        run(&mut terrain, Temperature::in_c(-5.0), 365);
+
+        assert!(terrain.iter().all(|t| t.glacier.u8() > 0));
+    }
+
+    #[test]
+    fn no_glaciers_on_warm_tiles() {
+        let mut terrain = vec![Terrain::new(128, 50, 0); N];
+        run(&mut terrain, Temperature::in_c(20.0), 365);
+
+        assert!(terrain.iter().all(|t| t.glacier.u8() == 0));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn wind_survives_serde() {
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        let adjacency = adj.get(N);
+        let weather = Weather::new(
+            adjacency,
+            adj.positions(N),
+            Length::in_m(EARTH_RADIUS),
+            Duration::in_d(1.0),
+        );
+        let hydrology = Hydrology::new(N).with_wind(weather);
+
+        let json = serde_json::to_string(&hydrology).unwrap();
+        let mut actual = serde_json::from_str::<Hydrology>(&json).unwrap();
+        assert_eq!(hydrology.wind, actual.wind);
+
+        let mut expected = hydrology;
+        let mut terrain = vec![Terrain::new(255, 0, 0); N];
+        terrain[0] = Terrain::new(0, 200, 0).with_elevation(Elevation::in_m(3000.0));
+        let temperature = vec![Temperature::in_c(15.0); N];
+        for hydrology in [&mut expected, &mut actual] {
+            for _ in 0..10 {
+                hydrology.step(&mut terrain, &temperature, adjacency, Duration::in_d(1.0));
+            }
+        }
+        assert_eq!(expected.precipitation, actual.precipitation);
+    }
+}
@@ -0,0 +1,351 @@
+use crate::adjacency::units::Position3;
+use crate::planet::Orbit;
+use crate::spin::Spin;
+use physics_types::{Angle, Duration};
+use std::f64::consts::{PI, TAU};
+
+/// The cosine of the sun's zenith angle at a point on the surface, zero at night.
+///
+/// # Arguments
+///
+/// * `position`: unit vector in the planet's frame, with the rotation axis along z
+/// * `orbital_phase`: fraction of the year since the northern spring equinox
+/// * `rotation_phase`: fraction of the solar day since noon at the x axis
+pub fn intensity(
+    position: Position3,
+    axial_tilt: Angle,
+    orbital_phase: f64,
+    rotation_phase: f64,
+) -> f64 {
+    let sun = sun_direction(axial_tilt, orbital_phase, rotation_phase);
+    (position.x * sun[0] + position.y * sun[1] + position.z * sun[2]).max(0.0)
+}
+
+/// Unit vector toward the sun in the planet's frame, with the rotation axis along z,
+/// see `intensity` for the arguments
+pub fn sun_direction(axial_tilt: Angle, orbital_phase: f64, rotation_phase: f64) -> [f64; 3] {
+    let declination = declination(axial_tilt, orbital_phase);
+    let hour_angle = TAU * rotation_phase;
+
+    let (sin_dec, cos_dec) = declination.sin_cos();
+    let (sin_hour, cos_hour) = hour_angle.sin_cos();
+
+    [cos_dec * cos_hour, -cos_dec * sin_hour, sin_dec]
+}
+
+/// The sun's latitude, which moves between the tropics over the year
+pub fn declination(axial_tilt: Angle, orbital_phase: f64) -> f64 {
+    (axial_tilt.value.sin() * (TAU * orbital_phase).sin()).asin()
+}
+
+/// The cosine of the sun's zenith angle averaged over a day, from the latitude and declination in radians
+pub fn daily_mean_intensity(latitude: f64, declination: f64) -> f64 {
+    let (sin_lat, cos_lat) = latitude.sin_cos();
+    let (sin_dec, cos_dec) = declination.sin_cos();
+    let sunset = sunset_hour_angle(latitude, declination);
+
+    (sunset * sin_lat * sin_dec + cos_lat * cos_dec * sunset.sin()) / PI
+}
+
+/// The fraction of the day that the sun is above the horizon, from the latitude and declination in radians,
+/// which is zero during polar night and one during polar day
+pub fn daylight_fraction(latitude: f64, declination: f64) -> f64 {
+    sunset_hour_angle(latitude, declination) / PI
+}
+
+/// Hour angle of sunset, zero during polar night and π during polar day
+fn sunset_hour_angle(latitude: f64, declination: f64) -> f64 {
+    (-latitude.tan() * declination.tan())
+        .clamp(-1.0, 1.0)
+        .acos()
+}
+
+/// The cosine of the sun's zenith angle averaged over a year of a circular orbit
+pub fn annual_mean_intensity(latitude: f64, axial_tilt: Angle) -> f64 {
+    const SEASONS: usize = 48;
+
+    (0..SEASONS)
+        .map(|i| {
+            let declination = declination(axial_tilt, (i as f64 + 0.5) / SEASONS as f64);
+            daily_mean_intensity(latitude, declination)
+        })
+        .sum::<f64>()
+        / SEASONS as f64
+}
+
+/// How sunlight is spread over time for the climate model
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClimateMode {
+    /// Sunlight follows the planet's rotation, which needs steps much shorter than a day
+    Diurnal,
+    /// Sunlight is averaged over each day, allowing steps of a day or longer
+    DailyMean,
+    /// The sun stays overhead at the x axis of a tidally locked planet,
+    /// leaving a hot substellar point and a permanently dark night side
+    Locked,
+}
+
+impl ClimateMode {
+    /// The mode suited to a planet's rotation and the climate time step,
+    /// averaging over days when a step covers more than an eighth of a day
+    pub fn for_spin(spin: &Spin, orbit: &Orbit, dt: Duration) -> Self {
+        match spin.solar_day(orbit) {
+            None => ClimateMode::Locked,
+            Some(day) if dt * 8.0 > day => ClimateMode::DailyMean,
+            Some(_) => ClimateMode::Diurnal,
+        }
+    }
+
+    /// The intensity of sunlight on each tile, see `ClimateModel::step`.
+    /// The rotation phase is ignored for `ClimateMode::DailyMean` and `ClimateMode::Locked`.
+    pub fn intensity(
+        self,
+        positions: &[Position3],
+        axial_tilt: Angle,
+        orbital_phase: f64,
+        rotation_phase: f64,
+        intensity: &mut [f64],
+    ) {
+        let iter = intensity.iter_mut().zip(positions);
+
+        match self {
+            ClimateMode::Diurnal => {
+                for (intensity, p) in iter {
+                    *intensity = self::intensity(*p, axial_tilt, orbital_phase, rotation_phase);
+                }
+            }
+            ClimateMode::DailyMean => {
+                let declination = declination(axial_tilt, orbital_phase);
+                for (intensity, p) in iter {
+                    let latitude = p.z.clamp(-1.0, 1.0).asin();
+                    *intensity = daily_mean_intensity(latitude, declination);
+                }
+            }
+            ClimateMode::Locked => {
+                for (intensity, p) in iter {
+                    *intensity = self::intensity(*p, axial_tilt, orbital_phase, 0.0);
+                }
+            }
+        }
+    }
+}
+
+/// Intensity of sunlight on each tile over a grid of orbital and rotation phases,
+/// so long runs can look up insolation instead of recomputing the geometry each step
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InsolationTable {
+    tiles: usize,
+    orbital_steps: usize,
+    rotation_steps: usize,
+    /// Indexed by orbital step, then rotation step, then tile
+    values: Vec<f64>,
+}
+
+impl InsolationTable {
+    pub fn new(
+        positions: &[Position3],
+        axial_tilt: Angle,
+        orbital_steps: usize,
+        rotation_steps: usize,
+    ) -> Self {
+        assert!(orbital_steps > 0 && rotation_steps > 0);
+
+        let mut values = Vec::with_capacity(orbital_steps * rotation_steps * positions.len());
+
+        for o in 0..orbital_steps {
+            let orbital_phase = o as f64 / orbital_steps as f64;
+            for r in 0..rotation_steps {
+                let rotation_phase = r as f64 / rotation_steps as f64;
+                values.extend(
+                    positions
+                        .iter()
+                        .map(|p| intensity(*p, axial_tilt, orbital_phase, rotation_phase)),
+                );
+            }
+        }
+
+        Self {
+            tiles: positions.len(),
+            orbital_steps,
+            rotation_steps,
+            values,
+        }
+    }
+
+    fn row(&self, orbital_step: usize, rotation_step: usize) -> &[f64] {
+        let start = (orbital_step * self.rotation_steps + rotation_step) * self.tiles;
+        &self.values[start..start + self.tiles]
+    }
+
+    /// Interpolates the intensity of each tile between the surrounding grid points.
+    /// Phases wrap, so any real value is accepted.
+    pub fn sample(&self, orbital_phase: f64, rotation_phase: f64, intensity: &mut [f64]) {
+        let grid = |phase: f64, steps: usize| {
+            let position = phase.rem_euclid(1.0) * steps as f64;
+            let lower = (position.floor() as usize).min(steps - 1);
+            (lower, (lower + 1) % steps, position - lower as f64)
+        };
+
+        let (o0, o1, fo) = grid(orbital_phase, self.orbital_steps);
+        let (r0, r1, fr) = grid(rotation_phase, self.rotation_steps);
+
+        let rows = [
+            self.row(o0, r0),
+            self.row(o0, r1),
+            self.row(o1, r0),
+            self.row(o1, r1),
+        ];
+        let weights = [
+            (1.0 - fo) * (1.0 - fr),
+            (1.0 - fo) * fr,
+            fo * (1.0 - fr),
+            fo * fr,
+        ];
+
+        for (i, intensity) in intensity.iter_mut().enumerate().take(self.tiles) {
+            *intensity = rows
+                .iter()
+                .zip(weights)
+                .map(|(row, weight)| row[i] * weight)
+                .sum();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::{Spiral, Tiling};
+
+    const TILES: usize = 48;
+
+    fn table() -> (Vec<Position3>, InsolationTable) {
+        let positions = Spiral.positions(TILES);
+        let table = InsolationTable::new(&positions, Angle::in_deg(23.44), 32, 64);
+        (positions, table)
+    }
+
+    #[test]
+    fn noon_at_the_equinox() {
+        let equator = Position3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let tilt = Angle::in_deg(23.44);
+
+        assert!((intensity(equator, tilt, 0.0, 0.0) - 1.0).abs() < 1e-12);
+        assert_eq!(0.0, intensity(equator, tilt, 0.0, 0.5));
+    }
+
+    #[test]
+    fn polar_day_at_the_solstice() {
+        let north_pole = Position3 {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        let tilt = Angle::in_deg(23.44);
+
+        for hour in 0..24 {
+            let phase = hour as f64 / 24.0;
+            assert!(intensity(north_pole, tilt, 0.25, phase) > 0.0);
+            assert_eq!(0.0, intensity(north_pole, tilt, 0.75, phase));
+        }
+    }
+
+    #[test]
+    fn daily_mean_matches_diurnal_average() {
+        let (positions, _) = table();
+        let tilt = Angle::in_deg(23.44);
+        let hours = 1440;
+
+        let mut daily = vec![0.0; TILES];
+        ClimateMode::DailyMean.intensity(&positions, tilt, 0.2, 0.0, &mut daily);
+
+        let mut total = vec![0.0; TILES];
+        let mut diurnal = vec![0.0; TILES];
+        for hour in 0..hours {
+            let phase = hour as f64 / hours as f64;
+            ClimateMode::Diurnal.intensity(&positions, tilt, 0.2, phase, &mut diurnal);
+            for (total, diurnal) in total.iter_mut().zip(&diurnal) {
+                *total += diurnal / hours as f64;
+            }
+        }
+
+        for (daily, total) in daily.iter().zip(&total) {
+            assert!((daily - total).abs() < 1e-3, "{} != {}", daily, total);
+        }
+    }
+
+    #[test]
+    fn daily_mean_extremes() {
+        let solstice = Angle::in_deg(23.44).value;
+
+        assert!((daily_mean_intensity(0.0, 0.0) - PI.recip()).abs() < 1e-12);
+        assert_eq!(0.0, daily_mean_intensity(1.5, -solstice));
+        assert!((daily_mean_intensity(PI / 2.0, solstice) - solstice.sin()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn polar_day_and_night() {
+        let solstice = Angle::in_deg(23.44).value;
+
+        assert_eq!(1.0, daylight_fraction(1.4, solstice));
+        assert_eq!(0.0, daylight_fraction(-1.4, solstice));
+        assert!((daylight_fraction(0.0, solstice) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn annual_mean_by_latitude() {
+        let tilt = Angle::in_deg(23.44);
+
+        assert!(annual_mean_intensity(0.0, tilt) > annual_mean_intensity(1.0, tilt));
+        assert!(annual_mean_intensity(PI / 2.0, tilt) > 0.0);
+        assert!((annual_mean_intensity(0.0, Angle::default()) - PI.recip()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn locked_planets_have_a_night_side() {
+        let (positions, _) = table();
+        let orbit = Orbit {
+            semi_major_axis: physics_types::Length::in_m(7.5e9),
+            eccentricity: 0.0,
+            period: Duration::in_d(6.1),
+        };
+        let spin = Spin::tidally_locked(&orbit);
+        let mode = ClimateMode::for_spin(&spin, &orbit, Duration::in_hr(1.0));
+        assert_eq!(ClimateMode::Locked, mode);
+
+        let mut first = vec![0.0; TILES];
+        let mut later = vec![0.0; TILES];
+        mode.intensity(&positions, spin.axial_tilt, 0.0, 0.0, &mut first);
+        mode.intensity(&positions, spin.axial_tilt, 0.4, 0.7, &mut later);
+
+        assert_eq!(first, later);
+        for (p, intensity) in positions.iter().zip(&first) {
+            assert_eq!(p.x <= 0.0, *intensity == 0.0);
+        }
+    }
+
+    #[test]
+    fn table_matches_exact_values() {
+        let (positions, table) = table();
+        let tilt = Angle::in_deg(23.44);
+        let mut sampled = vec![0.0; TILES];
+
+        table.sample(3.0 / 32.0, 10.0 / 64.0, &mut sampled);
+        for (p, sampled) in positions.iter().zip(&sampled) {
+            let exact = intensity(*p, tilt, 3.0 / 32.0, 10.0 / 64.0);
+            assert!((exact - sampled).abs() < 1e-12);
+        }
+
+        table.sample(0.41, 0.77, &mut sampled);
+        for (p, sampled) in positions.iter().zip(&sampled) {
+            let exact = intensity(*p, tilt, 0.41, 0.77);
+            assert!((exact - sampled).abs() < 0.05);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::constants::SIGMA;
+use crate::dynamics;
+use crate::planet::Orbit;
+use physics_types::{Area, Duration, FluxDensity, Length, Mass, Power, Temperature};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LagrangePoint {
+    L1,
+    L2,
+    L3,
+    L4,
+    L5,
+}
+
+/// A planet on a circular orbit around its star
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LagrangeSystem {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub luminosity: Power,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub star_mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub star_radius: Length,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub planet_mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub planet_radius: Length,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub semi_major_axis: Length,
+}
+
+impl LagrangeSystem {
+    /// Approximate distance from the planet to L1 and L2, see `dynamics::hill_radius`
+    pub fn hill_radius(&self) -> Length {
+        // the period of the circular orbit doesn't affect its Hill radius
+        let orbit = Orbit {
+            semi_major_axis: self.semi_major_axis,
+            eccentricity: 0.0,
+            period: Duration::default(),
+        };
+        dynamics::hill_radius(&orbit, self.planet_mass, self.star_mass)
+    }
+
+    pub fn distance_from_star(&self, point: LagrangePoint) -> Length {
+        let a = self.semi_major_axis;
+        match point {
+            LagrangePoint::L1 => a - self.hill_radius(),
+            LagrangePoint::L2 => a + self.hill_radius(),
+            LagrangePoint::L3 => {
+                let mass_ratio = self.planet_mass.value / self.star_mass.value;
+                a * (1.0 + 5.0 / 12.0 * mass_ratio)
+            }
+            LagrangePoint::L4 | LagrangePoint::L5 => a,
+        }
+    }
+
+    /// The fraction of the star's disc hidden behind the planet, which is only non-zero at L2
+    pub fn shadow_fraction(&self, point: LagrangePoint) -> f64 {
+        if point != LagrangePoint::L2 {
+            return 0.0;
+        }
+
+        let planet = self.planet_radius / self.hill_radius();
+        let star = self.star_radius / self.distance_from_star(point);
+
+        (planet / star).min(1.0).powi(2)
+    }
+
+    pub fn environment(&self, point: LagrangePoint) -> LagrangeEnvironment {
+        let distance = self.distance_from_star(point);
+        LagrangeEnvironment {
+            distance,
+            flux: self.luminosity / Area::of_sphere(distance),
+            shadow_fraction: self.shadow_fraction(point),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LagrangeEnvironment {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub distance: Length,
+    /// Unobstructed stellar flux
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub flux: FluxDensity,
+    pub shadow_fraction: f64,
+}
+
+impl LagrangeEnvironment {
+    pub fn effective_flux(&self) -> FluxDensity {
+        self.flux * (1.0 - self.shadow_fraction)
+    }
+
+    /// Equilibrium temperature of a rapidly rotating black sphere, such as a small station
+    pub fn equilibrium_temperature(&self) -> Temperature {
+        let flux = self.effective_flux().value;
+        Temperature::in_k((flux / (4.0 * SIGMA)).powf(0.25))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::{EARTH_MASS, EARTH_RADIUS, SOLAR_MASS};
+    use physics_types::AU;
+
+    fn sun_earth() -> LagrangeSystem {
+        LagrangeSystem {
+            luminosity: Power::blackbody(Temperature::in_k(5772.0), Length::in_m(695_700e3)),
+            star_mass: Mass::in_kg(SOLAR_MASS),
+            star_radius: Length::in_m(695_700e3),
+            planet_mass: Mass::in_kg(EARTH_MASS),
+            planet_radius: Length::in_m(EARTH_RADIUS),
+            semi_major_axis: AU,
+        }
+    }
+
+    #[test]
+    fn earth_hill_radius() {
+        let km = sun_earth().hill_radius() / Length::in_m(1e3);
+        assert!(km > 1.4e6 && km < 1.6e6, "{}", km);
+    }
+
+    #[test]
+    fn l2_is_partially_shadowed() {
+        let shadow = sun_earth().shadow_fraction(LagrangePoint::L2);
+        assert!(shadow > 0.8 && shadow < 0.9, "{}", shadow);
+        assert_eq!(0.0, sun_earth().shadow_fraction(LagrangePoint::L1));
+    }
+
+    #[test]
+    fn flux_decreases_with_distance() {
+        let system = sun_earth();
+        let l1 = system.environment(LagrangePoint::L1).flux;
+        let l4 = system.environment(LagrangePoint::L4).flux;
+        let l2 = system.environment(LagrangePoint::L2).flux;
+
+        assert!(l1 > l4);
+        assert!(l4 > l2);
+    }
+
+    #[test]
+    fn l4_equilibrium_temperature() {
+        let t = sun_earth()
+            .environment(LagrangePoint::L4)
+            .equilibrium_temperature();
+        assert!((t.value - 278.0).abs() < 2.0, "{}", t.value);
+    }
+}
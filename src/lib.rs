@@ -1,11 +1,89 @@
 #![feature(const_trait_impl, const_fn_floating_point_arithmetic)]
 
-// TODO incorporate orbital_mechanics
-// TODO add orbital parameters related to rotation speed (period) and axial tilt (line)
 // TODO simulate temperature
 
 pub mod adjacency;
+pub mod atmosphere;
+pub mod biome;
+pub mod biosphere;
+pub mod climate;
+pub mod climate_arrays;
 pub mod colony_cost;
+pub mod constants;
+pub mod currents;
+pub mod dynamics;
+pub mod energy;
+pub mod export;
+pub mod forcing;
+pub mod frost;
+pub mod geology;
+pub mod glacier;
+pub mod hydrology;
+pub mod insolation;
+pub mod lagrange;
+pub mod magnetosphere;
+pub mod moon;
+pub mod noise;
+pub mod photochemistry;
+pub mod planet;
+pub mod planet_class;
+pub mod planet_facts;
+pub mod radiation;
+pub mod regime;
+pub mod resources;
+pub mod rings;
+pub mod satellites;
+pub mod sea_level;
+#[cfg(feature = "serde")]
+mod serde_impl;
 pub mod solar_radiation;
+pub mod spin;
+pub mod star;
+pub mod star_field;
+pub mod statistics;
+pub mod storms;
+pub mod sync;
+pub mod system_gen;
+pub mod system_report;
+pub mod tectonics;
 pub mod terrain;
 pub mod tile_gen;
+pub mod volcanism;
+pub mod watershed;
+pub mod weather;
+
+/// The random number generator used for reproducible generation
+pub type SeededRng = rand_chacha::ChaCha8Rng;
+
+/// Creates the crate's deterministic random number generator.
+///
+/// Generation from a given seed and tile count is stable across patch releases,
+/// and only changes with a minor or major version.
+pub fn rng(seed: u64) -> SeededRng {
+    use rand::SeedableRng;
+    SeededRng::seed_from_u64(seed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{Rng, RngCore};
+
+    /// Changing these values breaks reproducibility for every user of the crate
+    #[test]
+    fn rng_stream_is_stable() {
+        let mut rng = rng(0);
+        assert_eq!(13080132717333068652, rng.next_u64());
+        assert_eq!(8594738769458413623, rng.next_u64());
+        assert_eq!(12896916468484187878, rng.next_u64());
+    }
+
+    #[test]
+    fn rng_ranges_are_stable() {
+        let mut rng = rng(42);
+        let values = (0..5)
+            .map(|_| rng.gen_range(0..100))
+            .collect::<Vec<usize>>();
+        assert_eq!(vec![68, 95, 42, 62, 80], values);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::colony_cost::Shielding;
+use crate::constants::EARTH_MASS;
+use crate::radiation::DoseRate;
+use physics_types::{Acceleration, Duration, Mass, Pressure};
+
+/// The state of a planet's iron core, which only drives a dynamo while it convects
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Core {
+    /// A liquid outer core losing heat fast enough to churn, like Earth's
+    Convecting,
+    /// A liquid core under a lid that holds its heat in, as is thought of Venus
+    Stagnant,
+    /// A core that has frozen solid, as in small, old planets like Mars
+    Solid,
+}
+
+/// The magnetic field of a planet, which turns aside charged particles before they reach its atmosphere
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Magnetosphere {
+    /// Dipole moment relative to Earth's
+    pub moment: f64,
+}
+
+impl Magnetosphere {
+    /// Cosmic-ray dose in interplanetary space, with nothing in the way
+    pub const FREE_SPACE: DoseRate = DoseRate::in_msv_per_yr(660.0);
+
+    /// Column of air in kg/m² that cuts the dose from cosmic rays and their showers by 1/e
+    pub const ATTENUATION: f64 = 1500.0;
+
+    /// Dipole moment relative to Earth's needed to hold off the solar wind
+    pub const PROTECTIVE: f64 = 0.1;
+
+    /// A dynamo scaled from Earth's, growing with the size of the planet and slowing with its rotation.
+    /// Rotating faster than Earth doesn't strengthen the field much, so the rotation is capped at Earth's.
+    ///
+    /// # Arguments
+    ///
+    /// * `rotation_period`: sidereal rotation period, see `Spin::period`
+    pub fn new(mass: Mass, rotation_period: Duration, core: Core) -> Self {
+        const SIDEREAL_DAY: f64 = 86164.0;
+
+        let moment = match core {
+            Core::Convecting => {
+                let mass = mass.value / EARTH_MASS;
+                let rotation = SIDEREAL_DAY / rotation_period.value.abs();
+                mass.powf(4.0 / 3.0) * rotation.min(1.0).sqrt()
+            }
+            Core::Stagnant | Core::Solid => 0.0,
+        };
+
+        Self { moment }
+    }
+
+    pub fn earth() -> Self {
+        Self { moment: 1.0 }
+    }
+
+    /// Whether the field is strong enough to keep the solar wind from reaching the atmosphere
+    pub fn is_protective(&self) -> bool {
+        self.moment >= Self::PROTECTIVE
+    }
+
+    /// Shielding from the field and the column of air above the surface, see `Shielding::new`
+    pub fn shielding(&self, pressure: Pressure) -> Shielding {
+        Shielding::new(self.is_protective(), pressure)
+    }
+
+    /// Cosmic-ray dose at the surface, about 0.2 mSv/yr for Earth and 300 mSv/yr for Mars
+    ///
+    /// # Arguments
+    ///
+    /// * `pressure`: surface pressure, which with `surface_gravity` gives the column of air overhead
+    pub fn surface_dose(&self, pressure: Pressure, surface_gravity: Acceleration) -> DoseRate {
+        // the planet blocks half of the sky, and the field turns aside particles outside the poles
+        let field = 1.0 / (1.0 + self.moment);
+        let column = pressure.value / surface_gravity.value;
+        let atmosphere = (-column / Self::ATTENUATION).exp();
+
+        Self::FREE_SPACE * (0.5 * field * atmosphere)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn earth_dynamo() {
+        let earth = Magnetosphere::new(
+            Mass::in_kg(EARTH_MASS),
+            Duration::in_s(86164.0),
+            Core::Convecting,
+        );
+        assert!((earth.moment - 1.0).abs() < 1e-12);
+        assert!(earth.is_protective());
+
+        let locked = Magnetosphere::new(
+            Mass::in_kg(EARTH_MASS),
+            Duration::in_d(365.0),
+            Core::Convecting,
+        );
+        assert!(locked.moment < earth.moment);
+
+        let venus = Magnetosphere::new(Mass::in_kg(4.87e24), Duration::in_d(243.0), Core::Stagnant);
+        assert!(!venus.is_protective());
+    }
+
+    #[test]
+    fn surface_dose() {
+        let earth = Magnetosphere::earth();
+        let mars = Magnetosphere::new(Mass::in_kg(6.42e23), Duration::in_hr(24.6), Core::Solid);
+
+        let earth_dose = earth.surface_dose(Pressure::in_atm(1.0), Acceleration::in_m_per_s2(9.81));
+        let mars_dose = mars.surface_dose(Pressure::in_pa(600.0), Acceleration::in_m_per_s2(3.72));
+
+        assert!(
+            earth_dose < DoseRate::in_msv_per_yr(1.0),
+            "{:?}",
+            earth_dose
+        );
+        assert!(
+            mars_dose > DoseRate::in_msv_per_yr(200.0),
+            "{:?}",
+            mars_dose
+        );
+        assert_eq!(Shielding::Shielded, earth.shielding(Pressure::in_atm(1.0)));
+        assert_eq!(
+            Shielding::Unshielded,
+            mars.shielding(Pressure::in_pa(600.0))
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::adjacency::units::Position3;
+use crate::constants::G;
+use crate::radiation::DoseRate;
+use crate::star_field::overlap;
+use physics_types::{Duration, FluxDensity, Length, Mass};
+use std::f64::consts::PI;
+
+/// The planet that a moon orbits
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Primary {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+    /// Dose rate from trapped particle belts at one primary radius, falls off with distance cubed
+    pub belt_dose: DoseRate,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Moon {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub semi_major_axis: Length,
+    pub eccentricity: f64,
+    /// The ratio of the tidal Love number to the tidal quality factor, k₂/Q
+    pub tidal_response: f64,
+}
+
+impl Moon {
+    pub fn orbital_period(&self, primary: &Primary) -> Duration {
+        let a = self.semi_major_axis.value;
+        Duration::in_s(2.0 * PI * (a.powi(3) / (G * primary.mass.value)).sqrt())
+    }
+}
+
+/// Moon properties that follow from its orbit around the primary
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoonParameters {
+    /// Moons are assumed to be tidally locked to their primary
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub rotation_period: Duration,
+    /// The fraction of each orbit spent in the primary's shadow
+    pub eclipse_fraction: f64,
+    pub radiation_dose: DoseRate,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub tidal_heating: FluxDensity,
+}
+
+/// Caller-supplied values that replace the derived defaults
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoonOverrides {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_impl::optional_quantity")
+    )]
+    pub rotation_period: Option<Duration>,
+    pub eclipse_fraction: Option<f64>,
+    pub radiation_dose: Option<DoseRate>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_impl::optional_quantity")
+    )]
+    pub tidal_heating: Option<FluxDensity>,
+}
+
+impl MoonParameters {
+    pub fn new(primary: &Primary, moon: &Moon) -> Self {
+        let distance = primary.radius / moon.semi_major_axis;
+
+        Self {
+            rotation_period: moon.orbital_period(primary),
+            eclipse_fraction: distance.min(1.0).asin() / PI,
+            radiation_dose: primary.belt_dose * distance.powi(3),
+            tidal_heating: Self::tidal_heating(primary, moon),
+        }
+    }
+
+    fn tidal_heating(primary: &Primary, moon: &Moon) -> FluxDensity {
+        let power = tidal_power(
+            moon.tidal_response,
+            primary.mass,
+            moon.radius,
+            moon.semi_major_axis,
+            moon.eccentricity,
+        );
+        let r = moon.radius.value;
+
+        FluxDensity::in_w_per_m2(power / (4.0 * PI * r * r))
+    }
+
+    /// The fraction of starlight reaching the moon, which falls to zero while the primary covers the star
+    ///
+    /// # Arguments
+    ///
+    /// * `angle`: the moon's angle around the primary in radians, zero at the point opposite the star
+    /// * `star_angular_radius`: the star's apparent radius in radians, which softens the edges of the eclipse
+    pub fn starlight(primary: &Primary, moon: &Moon, angle: f64, star_angular_radius: f64) -> f64 {
+        let primary_radius = (primary.radius / moon.semi_major_axis).min(1.0).asin();
+        let separation = angle.rem_euclid(2.0 * PI);
+        let separation = separation.min(2.0 * PI - separation);
+
+        let star = PI * star_angular_radius * star_angular_radius;
+        let hidden = overlap(star_angular_radius, primary_radius, separation);
+
+        (1.0 - hidden / star).max(0.0)
+    }
+
+    pub fn with_overrides(self, overrides: MoonOverrides) -> Self {
+        Self {
+            rotation_period: overrides.rotation_period.unwrap_or(self.rotation_period),
+            eclipse_fraction: overrides.eclipse_fraction.unwrap_or(self.eclipse_fraction),
+            radiation_dose: overrides.radiation_dose.unwrap_or(self.radiation_dose),
+            tidal_heating: overrides.tidal_heating.unwrap_or(self.tidal_heating),
+        }
+    }
+}
+
+/// Heat dissipated in a synchronously rotating body by an eccentric orbit, in W
+///
+/// https://en.wikipedia.org/wiki/Tidal_heating
+fn tidal_power(
+    tidal_response: f64,
+    primary_mass: Mass,
+    radius: Length,
+    semi_major_axis: Length,
+    eccentricity: f64,
+) -> f64 {
+    let m = primary_mass.value;
+    let r = radius.value;
+    let a = semi_major_axis.value;
+    let e = eccentricity;
+    let n = (G * m / a.powi(3)).sqrt();
+
+    10.5 * tidal_response * G * m * m * r.powi(5) * n * e * e / a.powi(6)
+}
+
+/// Tidal heating of a body from its interior's rigidity, for the climate's internal heat.
+/// Io-like moons get more heat from tides than from their star.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TidalHeating {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub primary_mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub semi_major_axis: Length,
+    pub eccentricity: f64,
+    /// Shear modulus of the interior in Pa, around 6.5×10¹⁰ for cold rock and far less for partly molten rock
+    pub rigidity: f64,
+    /// Tidal quality factor, the inverse of the fraction of the tidal energy dissipated each cycle
+    pub quality: f64,
+}
+
+impl TidalHeating {
+    /// The tidal Love number k₂ of a uniform elastic sphere
+    pub fn love_number(&self) -> f64 {
+        let r = self.radius.value;
+        let gravity = G * self.mass.value / (r * r);
+        let density = self.mass.value / (4.0 / 3.0 * PI * r.powi(3));
+        1.5 / (1.0 + 19.0 * self.rigidity / (2.0 * density * gravity * r))
+    }
+
+    /// The mean heat flux through the surface
+    pub fn flux(&self) -> FluxDensity {
+        let power = tidal_power(
+            self.love_number() / self.quality,
+            self.primary_mass,
+            self.radius,
+            self.semi_major_axis,
+            self.eccentricity,
+        );
+        let r = self.radius.value;
+
+        FluxDensity::in_w_per_m2(power / (4.0 * PI * r * r))
+    }
+
+    /// The heat flux through each tile, see `ClimateModel::internal_heat`.
+    /// A `hotspot` from 0 to 1 concentrates the heat toward the points facing toward and away from the primary,
+    /// which lie on the x axis of a tidally locked body, while keeping the same mean.
+    pub fn distribution(&self, positions: &[Position3], hotspot: f64) -> Vec<FluxDensity> {
+        let weights = positions
+            .iter()
+            .map(|p| 1.0 + hotspot * 0.5 * (3.0 * p.x * p.x - 1.0))
+            .collect::<Vec<_>>();
+        let mean = weights.iter().sum::<f64>() / weights.len().max(1) as f64;
+        let flux = self.flux();
+
+        weights.into_iter().map(|w| flux * (w / mean)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn jupiter() -> Primary {
+        Primary {
+            mass: Mass::in_kg(1.898e27),
+            radius: Length::in_m(69_911e3),
+            belt_dose: DoseRate::in_sv_per_yr(2.7e6),
+        }
+    }
+
+    fn io() -> Moon {
+        Moon {
+            radius: Length::in_m(1821.6e3),
+            semi_major_axis: Length::in_m(421_700e3),
+            eccentricity: 0.0041,
+            tidal_response: 0.015,
+        }
+    }
+
+    #[test]
+    fn io_is_tidally_locked() {
+        let params = MoonParameters::new(&jupiter(), &io());
+        let days = params.rotation_period / Duration::in_d(1.0);
+        assert!((days - 1.769).abs() < 0.01, "{}", days);
+    }
+
+    #[test]
+    fn io_tidal_heating() {
+        let params = MoonParameters::new(&jupiter(), &io());
+        let flux = params.tidal_heating / FluxDensity::in_w_per_m2(1.0);
+        assert!(flux > 1.5 && flux < 3.0, "{}", flux);
+    }
+
+    #[test]
+    fn io_eclipse_fraction() {
+        let params = MoonParameters::new(&jupiter(), &io());
+        assert!(params.eclipse_fraction > 0.05 && params.eclipse_fraction < 0.06);
+    }
+
+    fn io_tides(rigidity: f64) -> TidalHeating {
+        TidalHeating {
+            primary_mass: jupiter().mass,
+            mass: Mass::in_kg(8.93e22),
+            radius: io().radius,
+            semi_major_axis: io().semi_major_axis,
+            eccentricity: io().eccentricity,
+            rigidity,
+            quality: 1.0,
+        }
+    }
+
+    #[test]
+    fn tidal_heating_from_rigidity() {
+        let stiff = io_tides(6.5e10);
+        let molten = io_tides(6.5e8);
+        assert!(stiff.love_number() < molten.love_number());
+        assert!(stiff.flux() < molten.flux());
+
+        let moon = Moon {
+            tidal_response: molten.love_number() / molten.quality,
+            ..io()
+        };
+        let params = MoonParameters::new(&jupiter(), &moon);
+        assert!((params.tidal_heating / molten.flux() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tidal_hotspots_keep_the_mean() {
+        use crate::adjacency::{Spiral, Tiling};
+
+        let tides = io_tides(6.5e8);
+        let positions = Spiral.positions(100);
+        let flux = tides.distribution(&positions, 1.0);
+
+        let mean = flux.iter().map(|f| f.value).sum::<f64>() / 100.0;
+        assert!((mean - tides.flux().value).abs() < 1e-9 * mean);
+
+        let (hot, cold) = (0..100).fold((0, 0), |(hot, cold), i| {
+            let x = positions[i].x.abs();
+            (
+                if x > positions[hot].x.abs() { i } else { hot },
+                if x < positions[cold].x.abs() { i } else { cold },
+            )
+        });
+        assert!(flux[hot] > flux[cold]);
+    }
+
+    #[test]
+    fn io_eclipse_cooling() {
+        let sun = 696e6 / 778.5e9;
+        let starlight = |angle: f64| MoonParameters::starlight(&jupiter(), &io(), angle, sun);
+
+        assert_eq!(0.0, starlight(0.0));
+        assert_eq!(1.0, starlight(PI));
+
+        let steps = 10_000;
+        let mean = (0..steps)
+            .map(|i| starlight(2.0 * PI * i as f64 / steps as f64))
+            .sum::<f64>()
+            / steps as f64;
+        let eclipse = MoonParameters::new(&jupiter(), &io()).eclipse_fraction;
+        assert!((1.0 - mean - eclipse).abs() < 0.005);
+    }
+
+    #[test]
+    fn overrides() {
+        let overrides = MoonOverrides {
+            eclipse_fraction: Some(0.0),
+            ..Default::default()
+        };
+        let derived = MoonParameters::new(&jupiter(), &io());
+        let params = derived.with_overrides(overrides);
+
+        assert_eq!(0.0, params.eclipse_fraction);
+        assert_eq!(derived.rotation_period, params.rotation_period);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::SeededRng;
+use physics_types::{Duration, Temperature};
+use rand::Rng;
+use std::f64::consts::TAU;
+
+/// Weather variability for displayed temperatures, as an Ornstein–Uhlenbeck process on each tile.
+///
+/// Anomalies wander around zero with a standard deviation of `amplitude`,
+/// forgetting their past over the `correlation` time. They are added to the model's output
+/// and don't feed back into the energy balance.
+#[derive(Debug, Clone)]
+pub struct WeatherNoise {
+    /// Standard deviation of the anomalies
+    pub amplitude: Temperature,
+    /// Time over which an anomaly decays by a factor of e, a few days for weather systems
+    pub correlation: Duration,
+    /// Temperature anomaly of each tile in K
+    anomaly: Vec<f64>,
+    rng: SeededRng,
+}
+
+impl WeatherNoise {
+    /// Starts each tile from a random anomaly, so the noise is stationary from the first step
+    pub fn new(tiles: usize, amplitude: Temperature, correlation: Duration, seed: u64) -> Self {
+        let mut rng = crate::rng(seed);
+        let anomaly = (0..tiles)
+            .map(|_| amplitude.value * standard_normal(&mut rng))
+            .collect();
+
+        Self {
+            amplitude,
+            correlation,
+            anomaly,
+            rng,
+        }
+    }
+
+    pub fn step(&mut self, dt: Duration) {
+        let decay = (-(dt / self.correlation)).exp();
+        let spread = self.amplitude.value * (1.0 - decay * decay).sqrt();
+
+        for anomaly in &mut self.anomaly {
+            *anomaly = *anomaly * decay + spread * standard_normal(&mut self.rng);
+        }
+    }
+
+    pub fn anomaly(&self, tile: usize) -> Temperature {
+        Temperature::in_k(self.anomaly[tile])
+    }
+
+    /// Adds the anomalies to the model's temperatures
+    pub fn apply<'a>(
+        &'a self,
+        temperature: impl IntoIterator<Item = Temperature> + 'a,
+    ) -> impl Iterator<Item = Temperature> + 'a {
+        temperature
+            .into_iter()
+            .zip(&self.anomaly)
+            .map(|(temp, anomaly)| temp + Temperature::in_k(*anomaly))
+    }
+}
+
+/// Box–Muller transform of two uniform samples
+fn standard_normal(rng: &mut SeededRng) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn noise(seed: u64) -> WeatherNoise {
+        WeatherNoise::new(1000, Temperature::in_k(3.0), Duration::in_d(3.0), seed)
+    }
+
+    #[test]
+    fn reproducible_from_seed() {
+        let mut a = noise(7);
+        let mut b = noise(7);
+
+        for _ in 0..10 {
+            a.step(Duration::in_hr(6.0));
+            b.step(Duration::in_hr(6.0));
+        }
+
+        assert_eq!(a.anomaly, b.anomaly);
+        assert_ne!(a.anomaly, noise(8).anomaly);
+    }
+
+    #[test]
+    fn amplitude_is_stationary() {
+        let mut noise = noise(0);
+        for _ in 0..100 {
+            noise.step(Duration::in_hr(6.0));
+        }
+
+        let variance = noise.anomaly.iter().map(|a| a * a).sum::<f64>() / 1000.0;
+        assert!((variance.sqrt() - 3.0).abs() < 0.3, "{}", variance.sqrt());
+    }
+
+    #[test]
+    fn short_steps_are_correlated() {
+        let mut noise = noise(0);
+        let before = noise.anomaly.clone();
+        noise.step(Duration::in_s(600.0));
+
+        let change = noise
+            .anomaly
+            .iter()
+            .zip(&before)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        assert!(change < 3.0);
+
+        let temperature = vec![Temperature::in_c(15.0); 1000];
+        let applied = noise.apply(temperature).collect::<Vec<_>>();
+        assert_eq!(Temperature::in_c(15.0) + noise.anomaly(5), applied[5]);
+    }
+}
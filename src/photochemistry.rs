@@ -0,0 +1,145 @@
+use crate::atmosphere::Atmosphere;
+use crate::solar_radiation::Gas;
+use crate::star::Star;
+use physics_types::{Duration, Length, Pressure, AU};
+use std::f64::consts::PI;
+
+/// Gases broken apart and formed by a star's ultraviolet light, for runs over geological time.
+///
+/// Water, methane, and ammonia are destroyed in proportion to the UV flux.
+/// Hydrogen freed from water escapes to space, leaving a small yield of oxygen,
+/// and ozone forms from whatever oxygen is present.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Photochemistry {
+    /// Ultraviolet flux at the top of the atmosphere relative to the modern sun's at 1 AU
+    pub uv: f64,
+}
+
+impl Photochemistry {
+    /// Wavelengths that break apart water, methane, and ammonia
+    const SHORTEST: f64 = 100e-9;
+    const LONGEST: f64 = 320e-9;
+
+    /// Fraction of the oxygen freed from water that stays in the air instead of oxidizing the crust
+    pub const OXYGEN_YIELD: f64 = 0.01;
+
+    /// Ratio of ozone to oxygen under Earth's UV
+    pub const OZONE_RATIO: f64 = 2.0e-6;
+
+    pub fn new(star: &Star, distance: Length) -> Self {
+        let uv_flux = |star: &Star, distance: f64| {
+            let uv =
+                star.spectral_fraction(Length::in_m(Self::SHORTEST), Length::in_m(Self::LONGEST));
+            star.luminosity().value * uv / (4.0 * PI * distance * distance)
+        };
+
+        let sun = uv_flux(&Star::sun(), AU.value);
+        Self {
+            uv: uv_flux(star, distance.value) / sun,
+        }
+    }
+
+    /// Time for the UV of the modern sun at 1 AU to destroy all but 1/e of a gas,
+    /// or `None` for gases that aren't destroyed
+    pub fn lifetime(gas: Gas) -> Option<Duration> {
+        match gas {
+            Gas::Methane => Some(Duration::in_yr(12.0)),
+            Gas::Ammonia => Some(Duration::in_yr(10.0)),
+            // most water is held below the cold trap, out of reach of the UV
+            Gas::Water => Some(Duration::in_yr(1.0e8)),
+            _ => None,
+        }
+    }
+
+    pub fn step(&self, atmosphere: &mut Atmosphere, dt: Duration) {
+        let destroyed = |gas: Gas| match Self::lifetime(gas) {
+            Some(lifetime) if self.uv > 0.0 => {
+                let fraction = 1.0 - (-self.uv * (dt / lifetime)).exp();
+                atmosphere.partial_pressure(gas) * fraction
+            }
+            _ => Pressure::zero(),
+        };
+
+        let water = destroyed(Gas::Water);
+        let methane = destroyed(Gas::Methane);
+        let ammonia = destroyed(Gas::Ammonia);
+
+        atmosphere.add_partial_pressure(Gas::Water, -water);
+        atmosphere.add_partial_pressure(Gas::Methane, -methane);
+        atmosphere.add_partial_pressure(Gas::Ammonia, -ammonia);
+
+        // 2 H2O -> 2 H2 + O2, with the hydrogen escaping
+        atmosphere.add_partial_pressure(Gas::Oxygen, water * (0.5 * Self::OXYGEN_YIELD));
+        // 2 NH3 -> N2 + 3 H2
+        atmosphere.add_partial_pressure(Gas::Nitrogen, ammonia * 0.5);
+
+        // ozone forms and is destroyed within weeks, so it's always in balance with the oxygen
+        let ozone = atmosphere.partial_pressure(Gas::Oxygen) * (Self::OZONE_RATIO * self.uv.sqrt());
+        let change = ozone - atmosphere.partial_pressure(Gas::Ozone);
+        atmosphere.add_partial_pressure(Gas::Ozone, change);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::SOLAR_MASS;
+    use physics_types::{Mass, Temperature};
+
+    fn early_earth() -> Atmosphere {
+        let mut atmosphere = Atmosphere::earth();
+        atmosphere.add_partial_pressure(Gas::Oxygen, -atmosphere.partial_pressure(Gas::Oxygen));
+        atmosphere.add_partial_pressure(Gas::Ozone, -atmosphere.partial_pressure(Gas::Ozone));
+        atmosphere.add_partial_pressure(Gas::Methane, Pressure::in_atm(0.001));
+        atmosphere.add_partial_pressure(Gas::Ammonia, Pressure::in_atm(0.0001));
+        atmosphere
+    }
+
+    #[test]
+    fn sun_at_one_au() {
+        let sun = Photochemistry::new(&Star::sun(), AU);
+        assert!((sun.uv - 1.0).abs() < 1e-12);
+
+        let red_dwarf = Star::new(
+            Temperature::in_k(3100.0),
+            Length::in_m(0.15 * 695.7e6),
+            Mass::in_kg(0.12 * SOLAR_MASS),
+            Duration::in_yr(1.0e9),
+        );
+        let habitable = Photochemistry::new(&red_dwarf, AU * 0.04);
+        assert!(habitable.uv < 0.1, "{}", habitable.uv);
+    }
+
+    #[test]
+    fn uv_destroys_methane_and_ammonia() {
+        let mut atmosphere = early_earth();
+        let methane = atmosphere.partial_pressure(Gas::Methane);
+        let nitrogen = atmosphere.partial_pressure(Gas::Nitrogen);
+
+        Photochemistry { uv: 1.0 }.step(&mut atmosphere, Duration::in_yr(12.0));
+
+        let remaining = atmosphere.partial_pressure(Gas::Methane) / methane;
+        assert!((remaining - (-1f64).exp()).abs() < 1e-9);
+        assert!(atmosphere.partial_pressure(Gas::Nitrogen) > nitrogen);
+    }
+
+    #[test]
+    fn oxygen_and_ozone_from_water() {
+        let mut atmosphere = early_earth();
+        assert_eq!(0.0, atmosphere.composition[Gas::Oxygen]);
+
+        Photochemistry { uv: 1.0 }.step(&mut atmosphere, Duration::in_yr(1.0e6));
+
+        assert!(atmosphere.composition[Gas::Oxygen] > 0.0);
+        assert!(atmosphere.composition[Gas::Ozone] > 0.0);
+        assert!(atmosphere.composition[Gas::Ozone] < atmosphere.composition[Gas::Oxygen]);
+
+        let mut dark = early_earth();
+        Photochemistry { uv: 0.0 }.step(&mut dark, Duration::in_yr(1.0e6));
+        assert_eq!(0.0, dark.composition[Gas::Oxygen]);
+        let methane =
+            dark.partial_pressure(Gas::Methane) / early_earth().partial_pressure(Gas::Methane);
+        assert!((methane - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,904 @@
+use crate::adjacency::units::Position3;
+use crate::adjacency::{AdjArray, Adjacency, TileDensity};
+use crate::atmosphere::Atmosphere;
+use crate::biosphere::Biosphere;
+use crate::climate::{ClimateConfig, ClimateModel, ClimateSummary};
+use crate::constants::{EARTH_MASS, EARTH_RADIUS, G};
+use crate::glacier::GlacierModel;
+use crate::hydrology::Hydrology;
+use crate::insolation::{daylight_fraction, declination, intensity};
+use crate::satellites::Satellite;
+use crate::sea_level::SeaLevel;
+use crate::solar_radiation::Gas;
+use crate::spin::Spin;
+use crate::terrain::Terrain;
+use crate::tile_gen::{generate_terrain, TileGenError};
+use crate::weather::Weather;
+use fractional_int::FractionalU8;
+use physics_types::{
+    Acceleration, Angle, Density, Duration, FluxDensity, Length, Mass, Power, Pressure,
+    Temperature, Velocity, AU,
+};
+use std::f64::consts::{PI, TAU};
+use std::sync::Arc;
+
+/// The size, shape, and period of an orbit around the star
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orbit {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub semi_major_axis: Length,
+    pub eccentricity: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub period: Duration,
+}
+
+/// The parts of a planet fixed when it is generated.
+/// Share between threads with the `Arc` in [`Planet`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanetDescriptor {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    pub orbit: Orbit,
+    pub spin: Spin,
+    /// Fraction of the surface generated as ocean
+    pub water_fraction: f64,
+    pub climate: ClimateConfig,
+    pub magnetic_field: bool,
+    tiles: usize,
+    /// Registered once for `tiles`, and reused when generating terrain
+    adjacency: Adjacency,
+}
+
+impl PlanetDescriptor {
+    /// Mean density of the Earth in kg/m³, used for the mass of new planets
+    pub const EARTH_DENSITY: f64 = 5514.0;
+
+    /// A planet as dense as Earth, without axial tilt or ocean, that rotates once per orbit,
+    /// see `with_mass` and `with_spin`
+    pub fn new(radius: Length, orbit: Orbit, tiles: usize, climate: ClimateConfig) -> Self {
+        let mut adjacency = Adjacency::default();
+        adjacency.register(tiles);
+
+        let volume = 4.0 / 3.0 * PI * radius.value.powi(3);
+
+        Self {
+            radius,
+            mass: Mass::in_kg(volume * Self::EARTH_DENSITY),
+            orbit,
+            spin: Spin::new(orbit.period, Angle::default()),
+            water_fraction: 0.0,
+            climate,
+            magnetic_field: false,
+            tiles,
+            adjacency,
+        }
+    }
+
+    pub fn with_mass(mut self, mass: Mass) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    pub fn with_spin(mut self, spin: Spin) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    pub fn with_water_fraction(mut self, water_fraction: f64) -> Self {
+        self.water_fraction = water_fraction;
+        self
+    }
+
+    pub fn with_magnetic_field(mut self, magnetic_field: bool) -> Self {
+        self.magnetic_field = magnetic_field;
+        self
+    }
+
+    pub fn tiles(&self) -> usize {
+        self.tiles
+    }
+
+    /// Acceleration due to gravity at the surface
+    pub fn surface_gravity(&self) -> Acceleration {
+        let radius = self.radius.value;
+        Acceleration::in_m_per_s2(G * self.mass.value / (radius * radius))
+    }
+
+    /// Speed needed to escape the planet's gravity from its surface,
+    /// which gases approach as they are lost to space
+    pub fn escape_velocity(&self) -> Velocity {
+        Velocity::in_m_per_s((2.0 * G * self.mass.value / self.radius.value).sqrt())
+    }
+
+    /// Mean density of the whole planet
+    pub fn bulk_density(&self) -> Density {
+        let volume = 4.0 / 3.0 * PI * self.radius.value.powi(3);
+        Density::in_kg_per_m3(self.mass.value / volume)
+    }
+
+    /// The neighbours of each tile
+    pub fn adjacency(&self) -> &[AdjArray] {
+        self.adjacency.get(self.tiles)
+    }
+
+    /// The centre of each tile as a unit vector, with the rotation axis along z
+    pub fn positions(&self) -> &[Position3] {
+        self.adjacency.positions(self.tiles)
+    }
+
+    /// The centre of a tile as a unit vector, with the rotation axis along z
+    pub fn position(&self, tile: usize) -> Position3 {
+        self.positions()[tile]
+    }
+
+    /// The fraction of the surface covered by each tile
+    pub fn relative_areas(&self) -> &[f64] {
+        self.adjacency.relative_areas(self.tiles)
+    }
+
+    /// The prevailing winds over each tile, see `Weather::new`
+    pub fn weather(&self) -> Weather {
+        Weather::new(
+            self.adjacency(),
+            self.positions(),
+            self.radius,
+            self.spin.period,
+        )
+    }
+
+    /// Generates the initial terrain and a uniform temperature
+    ///
+    /// # Panics
+    ///
+    /// If the terrain can't be generated, see `try_generate`
+    pub fn generate(&self, atmosphere: Atmosphere, initial: Temperature, seed: u64) -> PlanetState {
+        self.try_generate(atmosphere, initial, seed)
+            .expect("failed to generate terrain")
+    }
+
+    /// Generates the initial terrain and a uniform temperature,
+    /// or an error if the planet's tiles can't come close to its water fraction
+    pub fn try_generate(
+        &self,
+        atmosphere: Atmosphere,
+        initial: Temperature,
+        seed: u64,
+    ) -> Result<PlanetState, TileGenError> {
+        let tiles = self.tiles();
+
+        let rng = &mut crate::rng(seed);
+        let terrain = generate_terrain(tiles, self.water_fraction, &self.adjacency, rng)?;
+
+        let climate = ClimateModel::new(self.climate, tiles, initial)
+            .with_area(self.relative_areas().to_vec());
+
+        Ok(PlanetState {
+            terrain,
+            biosphere: Biosphere::new(&climate),
+            climate,
+            atmosphere,
+            glaciers: GlacierModel::new(tiles),
+            hydrology: Hydrology::new(tiles).with_wind(self.weather()),
+            sea_level: SeaLevel::default(),
+            summary: ClimateSummary::new(tiles),
+            mirrors: Power::in_w(0.0),
+        })
+    }
+}
+
+/// The parts of a planet that change during a simulation, and all that a snapshot needs to save
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanetState {
+    pub terrain: Vec<Terrain>,
+    pub climate: ClimateModel,
+    pub atmosphere: Atmosphere,
+    pub glaciers: GlacierModel,
+    pub hydrology: Hydrology,
+    /// Moves coastlines as land ice melts or grows
+    pub sea_level: SeaLevel,
+    /// Plant life, which manages the vegetation in `ClimateModel::cover` once anything is growing
+    pub biosphere: Biosphere,
+    /// The climate recorded by each step
+    pub summary: ClimateSummary,
+    /// Sunlight reflected onto the planet by orbital mirrors, spread evenly over the day side
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mirrors: Power,
+}
+
+impl PlanetState {
+    /// Applies the lapse rate to the simulated sea-level temperatures
+    pub fn surface_temperatures(&self) -> impl Iterator<Item = Temperature> + '_ {
+        self.climate
+            .temperature
+            .iter()
+            .zip(self.terrain.iter())
+            .map(|(temp, terrain)| terrain.surface_temperature(*temp))
+    }
+
+    /// Mean surface temperature weighted by tile area, see `crate::regime::RegimeTracker::update`
+    pub fn mean_temperature(&self) -> Temperature {
+        let area = &self.climate.area;
+        let sum = self
+            .surface_temperatures()
+            .zip(area)
+            .map(|(t, a)| t.value * a);
+        Temperature::in_k(sum.sum())
+    }
+
+    /// Fraction of the surface covered by glaciers or sea ice, see `crate::regime::TippingDetector`
+    pub fn ice_cover(&self) -> f64 {
+        self.area_fraction(|t| t.glacier.f64())
+    }
+
+    /// Fraction of the surface covered by ocean, frozen or not
+    pub fn ocean_fraction(&self) -> f64 {
+        self.area_fraction(|t| t.ocean.f64())
+    }
+
+    fn area_fraction(&self, fraction: fn(&Terrain) -> f64) -> f64 {
+        let area = &self.climate.area;
+        self.terrain
+            .iter()
+            .zip(area)
+            .map(|(t, a)| fraction(t) * a)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Planet {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::arc"))]
+    pub descriptor: Arc<PlanetDescriptor>,
+    pub state: PlanetState,
+}
+
+impl Planet {
+    pub fn new(descriptor: Arc<PlanetDescriptor>, state: PlanetState) -> Self {
+        assert_eq!(descriptor.tiles(), state.terrain.len());
+        Self { descriptor, state }
+    }
+
+    /// Earth today, starting from its observed mean of 288 K
+    pub fn earth() -> Self {
+        let orbit = Orbit {
+            semi_major_axis: AU,
+            eccentricity: 0.0167,
+            period: Duration::in_d(365.256),
+        };
+        let radius = Length::in_m(EARTH_RADIUS);
+        let descriptor =
+            PlanetDescriptor::new(radius, orbit, preset_tiles(radius), ClimateConfig::earth())
+                .with_mass(Mass::in_kg(EARTH_MASS))
+                .with_spin(Spin::new(Duration::in_d(0.99726968), Angle::in_deg(23.439)))
+                .with_water_fraction(0.71)
+                .with_magnetic_field(true);
+
+        Self::preset(descriptor, Atmosphere::earth(), Temperature::in_k(288.0))
+    }
+
+    /// Mars today, starting from its observed mean of 210 K
+    pub fn mars() -> Self {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(227.9392e9),
+            eccentricity: 0.0934,
+            period: Duration::in_d(686.980),
+        };
+        let radius = Length::in_m(3389.5e3);
+        let descriptor =
+            PlanetDescriptor::new(radius, orbit, preset_tiles(radius), ClimateConfig::mars())
+                .with_mass(Mass::in_kg(6.417e23))
+                .with_spin(Spin::new(Duration::in_d(1.025957), Angle::in_deg(25.19)));
+
+        Self::preset(descriptor, Atmosphere::mars(), Temperature::in_k(210.0))
+    }
+
+    /// Venus today, starting from its observed mean of 737 K.
+    /// Its retrograde rotation is given as a negative period,
+    /// which yields its solar day of 117 Earth days.
+    pub fn venus() -> Self {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(108.209e9),
+            eccentricity: 0.0068,
+            period: Duration::in_d(224.701),
+        };
+        let radius = Length::in_m(6051.8e3);
+        let descriptor =
+            PlanetDescriptor::new(radius, orbit, preset_tiles(radius), ClimateConfig::venus())
+                .with_mass(Mass::in_kg(4.867e24))
+                .with_spin(Spin::new(Duration::in_d(-243.025), Angle::in_deg(2.64)));
+
+        Self::preset(descriptor, Atmosphere::venus(), Temperature::in_k(737.0))
+    }
+
+    /// Titan, starting from its observed mean of 94 K.
+    /// It follows Saturn's orbit around the sun, with Saturn's tilt and its own rotation,
+    /// and is never eclipsed by Saturn.
+    pub fn titan() -> Self {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(1433.53e9),
+            eccentricity: 0.0565,
+            period: Duration::in_d(10759.22),
+        };
+        let radius = Length::in_m(2574.7e3);
+        let descriptor =
+            PlanetDescriptor::new(radius, orbit, preset_tiles(radius), ClimateConfig::titan())
+                .with_mass(Mass::in_kg(1.3452e23))
+                .with_spin(Spin::new(Duration::in_d(15.945), Angle::in_deg(26.73)));
+
+        Self::preset(descriptor, Atmosphere::titan(), Temperature::in_k(94.0))
+    }
+
+    /// The Moon, following Earth's orbit around the sun, starting from a mean of 250 K
+    pub fn moon() -> Self {
+        let orbit = Orbit {
+            semi_major_axis: AU,
+            eccentricity: 0.0167,
+            period: Duration::in_d(365.256),
+        };
+        let radius = Length::in_m(1737.4e3);
+        let descriptor = PlanetDescriptor::new(
+            radius,
+            orbit,
+            preset_tiles(radius),
+            ClimateConfig::airless(),
+        )
+        .with_mass(Mass::in_kg(7.342e22))
+        .with_spin(Spin::new(Duration::in_d(27.321661), Angle::in_deg(1.54)));
+
+        Self::preset(descriptor, Atmosphere::vacuum(), Temperature::in_k(250.0))
+    }
+
+    /// Mercury in its 3:2 resonance, starting from a mean of 340 K over its long days and nights
+    pub fn mercury() -> Self {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(57.909e9),
+            eccentricity: 0.2056,
+            period: Duration::in_d(87.969),
+        };
+        let radius = Length::in_m(2439.7e3);
+        let descriptor = PlanetDescriptor::new(
+            radius,
+            orbit,
+            preset_tiles(radius),
+            ClimateConfig::airless(),
+        )
+        .with_mass(Mass::in_kg(3.301e23))
+        .with_spin(Spin::resonance(3, 2, &orbit))
+        .with_magnetic_field(true);
+
+        Self::preset(descriptor, Atmosphere::vacuum(), Temperature::in_k(340.0))
+    }
+
+    /// Generates the terrain of a preset from a fixed seed, so every call gives the same planet
+    fn preset(descriptor: PlanetDescriptor, atmosphere: Atmosphere, initial: Temperature) -> Self {
+        let state = descriptor.generate(atmosphere, initial, 0);
+        Self::new(Arc::new(descriptor), state)
+    }
+
+    /// Advances the climate, water cycle, glaciers, and sea level, and records the climate in the summary
+    ///
+    /// # Arguments
+    ///
+    /// * `flux`: stellar flux density at the planet's distance
+    /// * `intensity`: the cosine of the sun's zenith angle for each tile, zero at night
+    pub fn step(&mut self, flux: FluxDensity, intensity: &[f64], dt: Duration) {
+        let gravity = self.surface_gravity();
+        let adjacency = self.descriptor.adjacency();
+        let state = &mut self.state;
+
+        // mirrors light the disc the planet presents to the star
+        let radius = self.descriptor.radius.value;
+        let flux = flux + FluxDensity::in_w_per_m2(state.mirrors.value / (PI * radius * radius));
+
+        state
+            .climate
+            .step(&state.terrain, adjacency, flux, intensity, dt);
+
+        let surface_temp = state.surface_temperatures().collect::<Vec<_>>();
+        state
+            .hydrology
+            .step(&mut state.terrain, &surface_temp, adjacency, dt);
+        state.glaciers.step(&mut state.terrain, &surface_temp, dt);
+        state.sea_level.step(&mut state.terrain, adjacency);
+
+        if state.biosphere.vegetation.iter().any(|v| *v > 0.0) {
+            state.biosphere.step(
+                &mut state.climate,
+                &state.terrain,
+                &mut state.atmosphere,
+                gravity,
+                dt,
+            );
+        }
+
+        state
+            .summary
+            .record(&surface_temp, &state.hydrology.precipitation, dt);
+        state.summary.record_insolation(flux, intensity, dt);
+
+        if let Some(split) = &state.climate.split {
+            let land = split
+                .land_temp
+                .iter()
+                .zip(&state.terrain)
+                .map(|(temp, terrain)| terrain.surface_temperature(*temp))
+                .collect::<Vec<_>>();
+            state.summary.record_split(&land, &split.ocean_temp, dt);
+        }
+
+        let relative_humidity = state.hydrology.relative_humidity(&surface_temp);
+        for (humidity, relative) in state.climate.humidity.iter_mut().zip(relative_humidity) {
+            *humidity = relative;
+        }
+    }
+
+    /// Releases gas into the atmosphere, or removes it if the mass is negative
+    pub fn vent_gas(&mut self, gas: Gas, mass: Mass) {
+        let radius = self.descriptor.radius.value;
+        let area = 4.0 * PI * radius * radius;
+        let pressure = mass.value * self.surface_gravity().value / area;
+
+        self.state
+            .atmosphere
+            .add_partial_pressure(gas, Pressure::in_pa(pressure));
+    }
+
+    /// Adds mirrors that reflect more sunlight onto the planet, see `PlanetState::mirrors`
+    pub fn deploy_orbital_mirrors(&mut self, power: Power) {
+        self.state.mirrors += power;
+    }
+
+    /// Plants life on a tile, which spreads if the climate suits it, see `Biosphere`
+    pub fn seed_biosphere(&mut self, tile: usize) {
+        self.state.biosphere.seed(tile, Biosphere::SEED);
+    }
+
+    /// Melts all of the ice on a tile, with the meltwater from land ice draining to the ocean
+    pub fn melt_glaciers(&mut self, tile: usize) {
+        let state = &mut self.state;
+        let terrain = &mut state.terrain[tile];
+        let land_ice = terrain.land_ice().u8() as f64;
+
+        terrain.glacier = FractionalU8::default();
+        state.hydrology.snowpack[tile] = 0.0;
+        state.glaciers.meltwater[tile] += land_ice * Hydrology::GLACIER_COVER / u8::MAX as f64;
+    }
+
+    /// The fraction of the solar day since local noon on a tile, 0.5 at local midnight,
+    /// see `Spin::rotation_phase`. Constant on a tidally locked planet.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed`: time since noon at the x axis of the tiles
+    pub fn local_solar_time(&self, tile: usize, elapsed: Duration) -> f64 {
+        let descriptor = &self.descriptor;
+        let position = descriptor.position(tile);
+        let longitude = position.y.atan2(position.x);
+
+        let phase = descriptor.spin.rotation_phase(&descriptor.orbit, elapsed);
+        (phase + longitude / TAU).rem_euclid(1.0)
+    }
+
+    /// The cosine of the sun's zenith angle on each tile, zero at night, see `insolation::intensity`.
+    /// Found from the spin and orbit alone, without stepping the climate,
+    /// to render the day side or find the output of solar panels.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed`: time since the northern spring equinox, when it was noon at the x axis of the tiles
+    pub fn illumination(&self, elapsed: Duration) -> Vec<f64> {
+        let descriptor = &self.descriptor;
+        let spin = &descriptor.spin;
+        let orbital_phase = (elapsed / descriptor.orbit.period).rem_euclid(1.0);
+        let rotation_phase = spin.rotation_phase(&descriptor.orbit, elapsed);
+
+        (0..descriptor.tiles())
+            .map(|tile| {
+                let position = descriptor.position(tile);
+                intensity(position, spin.axial_tilt, orbital_phase, rotation_phase)
+            })
+            .collect()
+    }
+
+    /// The time between sunrise and sunset on a tile, which is zero during polar night
+    /// and the whole solar day during polar day.
+    /// `None` on a tidally locked planet, where the sun never sets on the day side.
+    ///
+    /// # Arguments
+    ///
+    /// * `orbital_phase`: fraction of the year since the northern spring equinox
+    pub fn day_length(&self, tile: usize, orbital_phase: f64) -> Option<Duration> {
+        let descriptor = &self.descriptor;
+        let solar_day = descriptor.spin.solar_day(&descriptor.orbit)?;
+
+        let latitude = descriptor.position(tile).z.clamp(-1.0, 1.0).asin();
+        let declination = declination(descriptor.spin.axial_tilt, orbital_phase);
+
+        Some(solar_day * daylight_fraction(latitude, declination))
+    }
+
+    /// See `PlanetDescriptor::surface_gravity`
+    pub fn surface_gravity(&self) -> Acceleration {
+        self.descriptor.surface_gravity()
+    }
+
+    /// See `PlanetDescriptor::escape_velocity`
+    pub fn escape_velocity(&self) -> Velocity {
+        self.descriptor.escape_velocity()
+    }
+
+    /// See `PlanetDescriptor::bulk_density`
+    pub fn bulk_density(&self) -> Density {
+        self.descriptor.bulk_density()
+    }
+
+    /// A copy of the mutable state, leaving the shared descriptor behind
+    pub fn snapshot(&self) -> PlanetState {
+        self.state.clone()
+    }
+
+    pub fn restore(&mut self, state: PlanetState) {
+        assert_eq!(self.descriptor.tiles(), state.terrain.len());
+        self.state = state;
+    }
+}
+
+/// Tiles at the default density, as for generated planets and moons
+fn preset_tiles(radius: Length) -> usize {
+    TileDensity::default()
+        .tile_count(radius)
+        .max(Satellite::MIN_TILES)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn earth() -> Arc<PlanetDescriptor> {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(149.6e9),
+            eccentricity: 0.0167,
+            period: Duration::in_yr(1.0),
+        };
+        let descriptor = PlanetDescriptor::new(
+            Length::in_m(EARTH_RADIUS),
+            orbit,
+            24,
+            ClimateConfig::earth(),
+        )
+        .with_spin(Spin::new(Duration::in_d(0.99726968), Angle::in_deg(23.439)))
+        .with_water_fraction(0.7);
+
+        Arc::new(descriptor)
+    }
+
+    #[test]
+    fn generated_state_matches_layout() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+
+        assert_eq!(24, state.terrain.len());
+        assert_eq!(24, state.climate.temperature.len());
+    }
+
+    #[test]
+    fn clones_share_the_descriptor() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+        let planet = Planet::new(descriptor.clone(), state);
+        let copy = planet.clone();
+
+        assert!(Arc::ptr_eq(&planet.descriptor, &copy.descriptor));
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+        let mut planet = Planet::new(descriptor, state);
+        let snapshot = planet.snapshot();
+
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        planet.step(flux, &[1.0; 24], Duration::in_hr(1.0));
+        assert_ne!(snapshot.climate, planet.state.climate);
+
+        planet.restore(snapshot.clone());
+        assert_eq!(snapshot.climate, planet.state.climate);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn snapshot_survives_serde() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+        let mut planet = Planet::new(descriptor, state);
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        // the summary starts at infinite extremes, which JSON can't hold
+        planet.step(flux, &[1.0; 24], Duration::in_hr(1.0));
+
+        let json = serde_json::to_string(&*planet.descriptor).unwrap();
+        let descriptor = serde_json::from_str::<PlanetDescriptor>(&json).unwrap();
+        assert_eq!(*planet.descriptor, descriptor);
+
+        let json = serde_json::to_string(&planet.snapshot()).unwrap();
+        let state = serde_json::from_str::<PlanetState>(&json).unwrap();
+        assert_eq!(json, serde_json::to_string(&state).unwrap());
+
+        let mut copy = Planet::new(Arc::new(descriptor), state);
+        planet.step(flux, &[1.0; 24], Duration::in_hr(1.0));
+        copy.step(flux, &[1.0; 24], Duration::in_hr(1.0));
+        assert_eq!(planet.state.climate, copy.state.climate);
+    }
+
+    #[test]
+    fn bulk_properties() {
+        let descriptor = earth();
+
+        assert!((descriptor.surface_gravity().value - 9.82).abs() < 0.01);
+        assert!((descriptor.escape_velocity().value - 11_180.0).abs() < 10.0);
+        assert!((descriptor.bulk_density().value - PlanetDescriptor::EARTH_DENSITY).abs() < 1e-6);
+    }
+
+    #[test]
+    fn day_and_night() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+        let planet = Planet::new(descriptor.clone(), state);
+        let day = descriptor.spin.solar_day(&descriptor.orbit).unwrap();
+
+        let noon = planet.local_solar_time(5, Duration::default());
+        let later = planet.local_solar_time(5, day * 0.25);
+        assert!(((later - noon).rem_euclid(1.0) - 0.25).abs() < 1e-9);
+
+        for tile in 0..descriptor.tiles() {
+            let equinox = planet.day_length(tile, 0.0).unwrap() / day;
+            assert!((equinox - 0.5).abs() < 1e-9);
+        }
+
+        let z = |tile: &usize| descriptor.position(*tile).z;
+        let tiles = 0..descriptor.tiles();
+        let north = tiles.clone().max_by(|a, b| z(a).total_cmp(&z(b))).unwrap();
+        let south = tiles.min_by(|a, b| z(a).total_cmp(&z(b))).unwrap();
+        assert!(planet.day_length(north, 0.25) > planet.day_length(south, 0.25));
+    }
+
+    #[test]
+    fn illumination() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+        let planet = Planet::new(descriptor.clone(), state);
+
+        let lit = planet.illumination(Duration::in_d(40.3));
+        let mean = lit.iter().sum::<f64>() / lit.len() as f64;
+        assert!((mean - 0.25).abs() < 0.05, "{}", mean);
+        assert!(lit.contains(&0.0));
+
+        let locked =
+            PlanetDescriptor::clone(&descriptor).with_spin(Spin::tidally_locked(&descriptor.orbit));
+        let locked = Planet::new(Arc::new(locked), planet.snapshot());
+        assert_eq!(
+            locked.illumination(Duration::default()),
+            locked.illumination(Duration::in_d(100.0))
+        );
+    }
+
+    #[test]
+    fn terraforming() {
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::mars(), Temperature::in_c(-60.0), 0);
+        let mut planet = Planet::new(descriptor, state);
+        let before = planet.state.atmosphere.pressure;
+
+        // Earth's atmosphere has a mass of about 5.1e18 kg
+        planet.vent_gas(Gas::Nitrogen, Mass::in_kg(5.1e18));
+        let added = (planet.state.atmosphere.pressure - before) / Pressure::in_atm(1.0);
+        assert!((added - 1.0).abs() < 0.05, "{}", added);
+
+        planet.state.terrain[0].glacier = FractionalU8::new(255);
+        planet.melt_glaciers(0);
+        assert_eq!(0, planet.state.terrain[0].glacier.u8());
+
+        planet.seed_biosphere(1);
+        assert_eq!(Biosphere::SEED, planet.state.biosphere.vegetation[1]);
+
+        let mut lit = planet.clone();
+        lit.deploy_orbital_mirrors(Power::in_w(1e16));
+
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        planet.step(flux, &[1.0; 24], Duration::in_hr(1.0));
+        lit.step(flux, &[1.0; 24], Duration::in_hr(1.0));
+        assert!(lit.state.mean_temperature() > planet.state.mean_temperature());
+    }
+
+    /// Mean surface temperature over the second half of `days` of hourly steps,
+    /// lit by the sun from the planet's semi-major axis
+    fn simulated_mean(mut planet: Planet, days: usize) -> Temperature {
+        let au = planet.descriptor.orbit.semi_major_axis / AU;
+        let flux = FluxDensity::in_w_per_m2(1361.0 / (au * au));
+        let dt = Duration::in_hr(1.0);
+        let steps = 24 * days;
+
+        let mut elapsed = Duration::default();
+        let mut total = 0.0;
+        for step in 0..steps {
+            let intensity = planet.illumination(elapsed);
+            planet.step(flux, &intensity, dt);
+            elapsed += dt;
+
+            if step >= steps / 2 {
+                total += planet.state.mean_temperature().value;
+            }
+        }
+
+        Temperature::in_k(total / (steps - steps / 2) as f64)
+    }
+
+    fn assert_near(planet: Planet, days: usize, observed: f64, tolerance: f64) {
+        let mean = simulated_mean(planet, days).value;
+        assert!((mean - observed).abs() < tolerance, "{}", mean);
+    }
+
+    #[test]
+    fn presets() {
+        let earth = Planet::earth();
+        assert!((earth.surface_gravity().value - 9.82).abs() < 0.05);
+        assert!((earth.state.ocean_fraction() - 0.71).abs() < 0.05);
+        assert!((Planet::moon().surface_gravity().value - 1.62).abs() < 0.01);
+        assert!((Planet::mars().bulk_density().value - 3934.0).abs() < 10.0);
+
+        let venus = Planet::venus();
+        let solar_day = venus.descriptor.spin.solar_day(&venus.descriptor.orbit);
+        assert!((solar_day.unwrap() / Duration::in_d(116.75) - 1.0).abs() < 0.01);
+
+        assert_eq!(Planet::titan().state.terrain, Planet::titan().state.terrain);
+    }
+
+    #[test]
+    fn earth_and_mars_presets() {
+        assert_near(Planet::earth(), 120, 288.0, 10.0);
+        assert_near(Planet::mars(), 120, 210.0, 20.0);
+    }
+
+    /// Mean difference between the warmest and coldest hour of each tile
+    /// over the last of `days` solar days, weighted by tile area
+    fn diurnal_range(mut planet: Planet, days: usize) -> f64 {
+        let au = planet.descriptor.orbit.semi_major_axis / AU;
+        let flux = FluxDensity::in_w_per_m2(1361.0 / (au * au));
+        let dt = Duration::in_hr(1.0);
+        let descriptor = planet.descriptor.clone();
+        let solar_day = descriptor.spin.solar_day(&descriptor.orbit).unwrap();
+        let steps = (solar_day / dt).round() as usize;
+
+        let mut elapsed = Duration::default();
+        for day in 0..days {
+            if day + 1 == days {
+                planet.state.summary = ClimateSummary::new(descriptor.tiles());
+            }
+            for _ in 0..steps {
+                let intensity = planet.illumination(elapsed);
+                planet.step(flux, &intensity, dt);
+                elapsed += dt;
+            }
+        }
+
+        let summary = &planet.state.summary;
+        let area = &planet.state.climate.area;
+        (summary.min.iter().zip(&summary.max).zip(area))
+            .map(|((min, max), area)| (*max - *min).value * area)
+            .sum()
+    }
+
+    /// Mars's surface swings by 60–80 K between day and night. Its land holds as much heat here as Earth's,
+    /// so the simulated swing stays below that, and the regolith beneath narrows it further.
+    #[test]
+    fn mars_regolith_narrows_the_day_night_range() {
+        let mut bare = Planet::mars();
+        bare.state.climate.config.subsurface = None;
+
+        let range = diurnal_range(Planet::mars(), 10);
+        assert!(range < diurnal_range(bare, 10), "{}", range);
+        assert!(range > 1.0 && range < 60.0, "{}", range);
+    }
+
+    #[test]
+    fn venus_and_titan_presets() {
+        assert_near(Planet::venus(), 120, 737.0, 40.0);
+        assert_near(Planet::titan(), 64, 94.0, 10.0);
+    }
+
+    #[test]
+    fn airless_presets() {
+        assert_near(Planet::moon(), 59, 250.0, 25.0);
+        // two solar days, as the regolith takes most of the first to settle
+        assert_near(Planet::mercury(), 352, 340.0, 40.0);
+    }
+
+    /// Water gained by the ocean as the sea level changes, and held as ice and snow on land or in the air,
+    /// in kg per m² of the surface
+    fn water_inventory(state: &PlanetState) -> f64 {
+        use crate::sea_level::{land_ice, WATER_DENSITY};
+
+        let tiles = state.terrain.len() as f64;
+        let ocean = state.ocean_fraction() * state.sea_level.height * WATER_DENSITY;
+        let ice = land_ice(&state.terrain) / tiles;
+        let snow = state.hydrology.snowpack.iter().sum::<f64>() / tiles;
+        let vapour = state.hydrology.humidity.iter().sum::<f64>() / tiles;
+        ocean + ice + snow + vapour
+    }
+
+    fn atmospheric_mass(planet: &Planet) -> Mass {
+        let area = physics_types::Area::of_sphere(planet.descriptor.radius);
+        let pressure = planet.state.atmosphere.pressure;
+        Mass::in_kg(pressure.value * area.value / planet.surface_gravity().value)
+    }
+
+    /// Catches slow leaks from new processes, run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn thousand_years_without_drift() {
+        use crate::insolation::ClimateMode;
+
+        let descriptor = earth();
+        let state = descriptor.generate(Atmosphere::earth(), Temperature::in_c(15.0), 0);
+        let mut planet = Planet::new(descriptor.clone(), state);
+
+        let positions = descriptor.positions();
+        let water = water_inventory(&planet.state);
+        let air = atmospheric_mass(&planet);
+        let flux = FluxDensity::in_w_per_m2(1361.0);
+        let mut intensity = vec![0.0; descriptor.tiles()];
+
+        let mut annual_means = vec![];
+        for _ in 0..1000 {
+            let mut total = 0.0;
+            for day in 0..365 {
+                let orbital_phase = day as f64 / 365.0;
+                let tilt = descriptor.spin.axial_tilt;
+                ClimateMode::DailyMean.intensity(
+                    positions,
+                    tilt,
+                    orbital_phase,
+                    0.0,
+                    &mut intensity,
+                );
+                planet.step(flux, &intensity, Duration::in_d(1.0));
+                total += planet.state.mean_temperature().value;
+            }
+            annual_means.push(total / 365.0);
+        }
+
+        assert!(annual_means.iter().all(|t| t.is_finite()));
+
+        let century = |c: usize| annual_means[c * 100..(c + 1) * 100].iter().sum::<f64>() / 100.0;
+        assert!((century(9) - century(1)).abs() < 2.0);
+
+        let settled = &annual_means[100..];
+        let (min, max) = settled
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), t| {
+                (min.min(*t), max.max(*t))
+            });
+        assert!(max - min < 5.0);
+
+        let state = &planet.state;
+        // snow that hasn't yet become glacier, and coasts moving in whole increments
+        let drift = water_inventory(state) - water;
+        let increment = Hydrology::GLACIER_COVER / u8::MAX as f64;
+        assert!(drift.abs() < 2.0 * increment, "{}", drift);
+        let drift = atmospheric_mass(&planet) / air - 1.0;
+        assert!(drift.abs() < 1e-3, "{}", drift);
+
+        let saturated = Hydrology::CAPACITY * (Hydrology::CLAUSIUS_CLAPEYRON * 50.0).exp();
+        assert!(state
+            .hydrology
+            .humidity
+            .iter()
+            .all(|h| (0.0..saturated).contains(h)));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::constants::EARTH_MASS;
+use crate::planet::Planet;
+use crate::regime::RegimeThresholds;
+use physics_types::Pressure;
+use std::fmt::{Display, Formatter};
+
+/// The broad kind of a planet, for labelling it and for constraining what is generated
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlanetClass {
+    /// A rocky planet with too little air to hold heat or carry weather, like Mercury or the Moon
+    Airless,
+    /// Oceans and continents under a temperate atmosphere, like Earth
+    Terrestrial,
+    /// Ocean over almost all of the surface, with at most a few islands
+    OceanWorld,
+    /// Water frozen over most of the surface
+    IceWorld,
+    /// Little or no surface water, like Mars
+    Desert,
+    /// A thick atmosphere trapping enough heat to boil away any ocean, like Venus
+    Greenhouse,
+    /// Too light for its size to be solid rock, and so wrapped in a deep envelope of hydrogen and helium
+    GasDwarf,
+}
+
+impl PlanetClass {
+    /// Bulk density in kg/m³ below which a planet more massive than Earth must have a thick envelope
+    pub const GAS_DWARF_DENSITY: f64 = 3000.0;
+
+    /// Surface pressure below which a planet is airless
+    pub const AIRLESS: Pressure = Pressure::in_pa(100.0);
+
+    /// Surface pressure above which a hot planet is a greenhouse rather than a desert
+    pub const GREENHOUSE: Pressure = Pressure::in_pa(5.0 * 101_325.0);
+
+    /// Fraction of the surface under water above which a planet is an ocean world
+    pub const OCEAN: f64 = 0.9;
+
+    /// Fraction of the surface under water below which a planet is a desert
+    pub const DESERT: f64 = 0.1;
+
+    /// Fraction of the surface under ice, above which a planet is an ice world
+    pub const ICE: f64 = 0.5;
+}
+
+impl Display for PlanetClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            PlanetClass::Airless => "Airless",
+            PlanetClass::Terrestrial => "Terrestrial",
+            PlanetClass::OceanWorld => "Ocean world",
+            PlanetClass::IceWorld => "Ice world",
+            PlanetClass::Desert => "Desert",
+            PlanetClass::Greenhouse => "Greenhouse",
+            PlanetClass::GasDwarf => "Gas dwarf",
+        };
+        f.write_str(label)
+    }
+}
+
+/// The class of a planet from its bulk density, atmosphere, mean surface temperature,
+/// and how much of its surface is water or ice.
+/// Temperatures are divided as in `RegimeThresholds::default`.
+pub fn classify(planet: &Planet) -> PlanetClass {
+    let descriptor = &planet.descriptor;
+    let state = &planet.state;
+    let thresholds = RegimeThresholds::default();
+
+    let density = descriptor.bulk_density().value;
+    if descriptor.mass.value > EARTH_MASS && density < PlanetClass::GAS_DWARF_DENSITY {
+        return PlanetClass::GasDwarf;
+    }
+
+    let pressure = state.atmosphere.pressure;
+    if pressure < PlanetClass::AIRLESS {
+        return PlanetClass::Airless;
+    }
+
+    let temperature = state.mean_temperature();
+    let ocean = state.ocean_fraction();
+
+    if temperature > thresholds.greenhouse {
+        return if pressure > PlanetClass::GREENHOUSE {
+            PlanetClass::Greenhouse
+        } else {
+            PlanetClass::Desert
+        };
+    }
+
+    let frozen_ocean = temperature < thresholds.frozen && ocean > PlanetClass::DESERT;
+    if frozen_ocean || state.ice_cover() > PlanetClass::ICE {
+        PlanetClass::IceWorld
+    } else if ocean > PlanetClass::OCEAN {
+        PlanetClass::OceanWorld
+    } else if ocean < PlanetClass::DESERT {
+        PlanetClass::Desert
+    } else {
+        PlanetClass::Terrestrial
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::atmosphere::Atmosphere;
+    use crate::climate::ClimateConfig;
+    use crate::constants::EARTH_RADIUS;
+    use crate::planet::{Orbit, PlanetDescriptor};
+    use physics_types::{Duration, Length, Mass, Temperature};
+    use std::sync::Arc;
+
+    fn descriptor(radius: f64) -> PlanetDescriptor {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(149.6e9),
+            eccentricity: 0.0,
+            period: Duration::in_yr(1.0),
+        };
+        PlanetDescriptor::new(Length::in_m(radius), orbit, 48, ClimateConfig::earth())
+    }
+
+    fn class(descriptor: PlanetDescriptor, atmosphere: Atmosphere, celsius: f64) -> PlanetClass {
+        let state = descriptor.generate(atmosphere, Temperature::in_c(celsius), 0);
+        classify(&Planet::new(Arc::new(descriptor), state))
+    }
+
+    #[test]
+    fn rocky_planets() {
+        let earth = |water_fraction| descriptor(EARTH_RADIUS).with_water_fraction(water_fraction);
+
+        assert_eq!(
+            PlanetClass::Terrestrial,
+            class(earth(0.7), Atmosphere::earth(), 15.0)
+        );
+        assert_eq!(
+            PlanetClass::OceanWorld,
+            class(earth(0.95), Atmosphere::earth(), 15.0)
+        );
+        assert_eq!(
+            PlanetClass::IceWorld,
+            class(earth(0.7), Atmosphere::earth(), -40.0)
+        );
+        assert_eq!(
+            PlanetClass::Desert,
+            class(earth(0.0), Atmosphere::mars(), -60.0)
+        );
+        assert_eq!(
+            PlanetClass::Greenhouse,
+            class(earth(0.0), Atmosphere::venus(), 460.0)
+        );
+        assert_eq!(
+            PlanetClass::Airless,
+            class(earth(0.0), Atmosphere::vacuum(), -20.0)
+        );
+    }
+
+    #[test]
+    fn gas_dwarf() {
+        let puffy = descriptor(2.5 * EARTH_RADIUS).with_mass(Mass::in_kg(5.0 * EARTH_MASS));
+
+        assert_eq!(
+            PlanetClass::GasDwarf,
+            class(puffy, Atmosphere::earth(), 15.0)
+        );
+        assert_eq!("Gas dwarf", PlanetClass::GasDwarf.to_string());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::atmosphere::Atmosphere;
+use crate::biome::Biome;
+use crate::climate::ClimateSummary;
+use crate::colony_cost::{ColonyCost, Shielding};
+use crate::terrain::Terrain;
+use physics_types::{Pressure, Temperature};
+use std::ops::{BitOr, BitOrAssign};
+
+/// Notable resources found on a planet's surface
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResourceFlags(u8);
+
+impl ResourceFlags {
+    pub const WATER: Self = Self(1);
+    pub const ICE: Self = Self(1 << 1);
+    pub const ARABLE: Self = Self(1 << 2);
+    pub const MOUNTAINS: Self = Self(1 << 3);
+
+    pub fn contains(self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for ResourceFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ResourceFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A compact summary of a planet for evaluating many planets quickly, computed without allocating
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanetFacts {
+    pub has_liquid_water: bool,
+    pub breathable: bool,
+    /// Mean temperature over all tiles
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mean_temp: Temperature,
+    /// The colony cost tier of the least hospitable tile
+    pub max_colony_cost_tier: u8,
+    pub resource_flags: ResourceFlags,
+}
+
+impl PlanetFacts {
+    /// Water's triple point, below which it can't be liquid
+    const TRIPLE_POINT: f64 = 611.657 / 101_325.0;
+
+    pub fn new(
+        terrain: &[Terrain],
+        climate: &ClimateSummary,
+        atmosphere: &Atmosphere,
+        shielding: Shielding,
+    ) -> Self {
+        let pressure = atmosphere.pressure;
+        let liquid_possible = pressure > Pressure::in_atm(Self::TRIPLE_POINT);
+        let freezing = Temperature::in_c(0.0);
+
+        let mut has_liquid_water = false;
+        let mut total = 0.0;
+        let mut max_colony_cost_tier = 0;
+        let mut resource_flags = ResourceFlags::default();
+
+        for (i, tile) in terrain.iter().enumerate() {
+            let mean = climate.mean(i);
+            let (min, max) = (climate.min[i], climate.max[i]);
+            total += mean.value;
+
+            if tile.ocean.u8() > 0 {
+                resource_flags |= ResourceFlags::WATER;
+                has_liquid_water |= liquid_possible && max > freezing;
+            }
+
+            if tile.glacier.u8() > 0 {
+                resource_flags |= ResourceFlags::ICE;
+            }
+
+            if tile.mountains.u8() > 0 {
+                resource_flags |= ResourceFlags::MOUNTAINS;
+            }
+
+            let biome = Biome::classify(tile, max, mean, climate.annual_precipitation(i));
+            if let Biome::Grassland | Biome::TemperateForest | Biome::Savanna | Biome::Rainforest =
+                biome
+            {
+                resource_flags |= ResourceFlags::ARABLE;
+            }
+
+            let tier = ColonyCost::new(min..max, pressure, shielding).tier();
+            max_colony_cost_tier = max_colony_cost_tier.max(tier);
+        }
+
+        Self {
+            has_liquid_water,
+            breathable: atmosphere.is_breathable(),
+            mean_temp: Temperature::in_k(total / terrain.len().max(1) as f64),
+            max_colony_cost_tier,
+            resource_flags,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use physics_types::Duration;
+
+    fn summary(temperature: Temperature, precipitation: f64) -> ClimateSummary {
+        let mut summary = ClimateSummary::new(2);
+        let temperature = [temperature; 2];
+        summary.record(&temperature, &[precipitation; 2], Duration::in_yr(1.0));
+        summary
+    }
+
+    #[test]
+    fn earth_like() {
+        let terrain = [Terrain::new(255, 0, 0), Terrain::new(0, 30, 0)];
+        let climate = summary(Temperature::in_c(15.0), 1000.0);
+
+        let facts = PlanetFacts::new(
+            &terrain,
+            &climate,
+            &Atmosphere::earth(),
+            Shielding::Shielded,
+        );
+
+        assert!(facts.has_liquid_water);
+        assert!(facts.breathable);
+        assert_eq!(1, facts.max_colony_cost_tier);
+        assert!(facts
+            .resource_flags
+            .contains(ResourceFlags::WATER | ResourceFlags::ARABLE));
+        assert!(!facts.resource_flags.contains(ResourceFlags::ICE));
+    }
+
+    #[test]
+    fn frozen_and_airless() {
+        let terrain = [Terrain::new(255, 0, 255), Terrain::new(0, 30, 0)];
+        let climate = summary(Temperature::in_c(-60.0), 0.0);
+
+        let facts = PlanetFacts::new(
+            &terrain,
+            &climate,
+            &Atmosphere::vacuum(),
+            Shielding::Unshielded,
+        );
+
+        assert!(!facts.has_liquid_water);
+        assert!(!facts.breathable);
+        assert!(facts.max_colony_cost_tier > 1);
+        assert!(facts.resource_flags.contains(ResourceFlags::ICE));
+        assert!(!facts.resource_flags.contains(ResourceFlags::ARABLE));
+    }
+}
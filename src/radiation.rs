@@ -0,0 +1,36 @@
+use std::ops::{Add, Mul};
+
+/// Ionizing radiation dose rate, stored in Sv/yr
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DoseRate(f64);
+
+impl DoseRate {
+    pub const fn in_sv_per_yr(value: f64) -> Self {
+        Self(value)
+    }
+
+    pub const fn in_msv_per_yr(value: f64) -> Self {
+        Self(value * 1e-3)
+    }
+
+    pub fn sv_per_yr(self) -> f64 {
+        self.0
+    }
+}
+
+impl Add for DoseRate {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Mul<f64> for DoseRate {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
@@ -0,0 +1,311 @@
+use crate::hydrology::Hydrology;
+use crate::planet::PlanetState;
+use physics_types::Temperature;
+
+/// The broad state of a planet's climate
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClimateRegime {
+    IceCovered,
+    Temperate,
+    Greenhouse,
+}
+
+/// Mean surface temperatures dividing the regimes.
+/// A planet must pass a boundary by the margin to change regime, and pass back by the margin to return.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegimeThresholds {
+    /// Below this the planet is ice-covered
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub frozen: Temperature,
+    /// Above this the planet is in a runaway greenhouse
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub greenhouse: Temperature,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub margin: Temperature,
+}
+
+impl Default for RegimeThresholds {
+    fn default() -> Self {
+        Self {
+            frozen: Temperature::in_c(-20.0),
+            greenhouse: Temperature::in_c(60.0),
+            margin: Temperature::in_k(5.0),
+        }
+    }
+}
+
+impl RegimeThresholds {
+    /// The regime of a planet currently in `current`
+    pub fn classify(&self, current: ClimateRegime, mean_temp: Temperature) -> ClimateRegime {
+        let frozen = match current {
+            ClimateRegime::IceCovered => self.frozen + self.margin,
+            _ => self.frozen - self.margin,
+        };
+
+        let greenhouse = match current {
+            ClimateRegime::Greenhouse => self.greenhouse - self.margin,
+            _ => self.greenhouse + self.margin,
+        };
+
+        if mean_temp < frozen {
+            ClimateRegime::IceCovered
+        } else if mean_temp > greenhouse {
+            ClimateRegime::Greenhouse
+        } else {
+            ClimateRegime::Temperate
+        }
+    }
+}
+
+/// A planet entering a new climate regime
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegimeChange {
+    pub from: ClimateRegime,
+    pub to: ClimateRegime,
+}
+
+/// Follows a planet's climate regime, reporting each change once
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegimeTracker {
+    pub thresholds: RegimeThresholds,
+    regime: ClimateRegime,
+}
+
+impl RegimeTracker {
+    /// Classifies the initial temperature from the boundaries themselves, without the margin
+    pub fn new(thresholds: RegimeThresholds, mean_temp: Temperature) -> Self {
+        let regime = RegimeThresholds {
+            margin: Temperature::in_k(0.0),
+            ..thresholds
+        }
+        .classify(ClimateRegime::Temperate, mean_temp);
+
+        Self { thresholds, regime }
+    }
+
+    pub fn regime(&self) -> ClimateRegime {
+        self.regime
+    }
+
+    /// Updates the regime from the planet's current mean surface temperature,
+    /// returning the change if the planet has entered a new regime
+    pub fn update(&mut self, mean_temp: Temperature) -> Option<RegimeChange> {
+        let from = self.regime;
+        let to = self.thresholds.classify(from, mean_temp);
+        self.regime = to;
+
+        (from != to).then_some(RegimeChange { from, to })
+    }
+}
+
+/// A climate state that feeds on itself once entered
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TippingPoint {
+    /// Oceans have reached their boiling point and are evaporating into a steam atmosphere
+    RunawayGreenhouse,
+    /// Ice covers almost the whole planet, reflecting the sunlight that could melt it
+    Snowball,
+}
+
+/// A planet crossing a tipping point in either direction
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TippingEvent {
+    Entered(TippingPoint),
+    Recovered(TippingPoint),
+}
+
+/// When a planet enters and leaves each tipping point.
+/// Leaving takes a larger change than entering, so that events aren't repeated near the edge.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TippingThresholds {
+    /// Fraction of the surface under ice at which the planet becomes a snowball
+    pub snowball: f64,
+    /// Fraction of the surface under ice below which a snowball has thawed
+    pub thawed: f64,
+    /// How far below the boiling point the ocean must cool to recover from a runaway greenhouse
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub condensation_margin: Temperature,
+}
+
+impl Default for TippingThresholds {
+    fn default() -> Self {
+        Self {
+            snowball: 0.95,
+            thawed: 0.8,
+            condensation_margin: Temperature::in_k(10.0),
+        }
+    }
+}
+
+/// Follows a planet past its tipping points, reporting each crossing once
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TippingDetector {
+    pub thresholds: TippingThresholds,
+    runaway: bool,
+    snowball: bool,
+}
+
+impl TippingDetector {
+    pub fn new(thresholds: TippingThresholds) -> Self {
+        Self {
+            thresholds,
+            runaway: false,
+            snowball: false,
+        }
+    }
+
+    pub fn is_runaway(&self) -> bool {
+        self.runaway
+    }
+
+    pub fn is_snowball(&self) -> bool {
+        self.snowball
+    }
+
+    /// Checks the planet's ice cover and ocean temperature, returning any tipping points crossed.
+    /// A planet without an ocean keeps its current greenhouse state.
+    pub fn update(&mut self, state: &PlanetState) -> Vec<TippingEvent> {
+        let boiling = Hydrology::boiling_point(state.atmosphere.pressure);
+        let ocean = state.climate.ocean_temperature(&state.terrain);
+        self.observe(state.ice_cover(), ocean, boiling)
+    }
+
+    fn observe(
+        &mut self,
+        ice_cover: f64,
+        ocean: Option<Temperature>,
+        boiling: Temperature,
+    ) -> Vec<TippingEvent> {
+        let mut events = Vec::new();
+        let thresholds = self.thresholds;
+
+        let snowball = if self.snowball {
+            ice_cover >= thresholds.thawed
+        } else {
+            ice_cover >= thresholds.snowball
+        };
+        events.extend(crossing(
+            TippingPoint::Snowball,
+            &mut self.snowball,
+            snowball,
+        ));
+
+        if let Some(ocean) = ocean {
+            let runaway = if self.runaway {
+                ocean >= boiling - thresholds.condensation_margin
+            } else {
+                ocean >= boiling
+            };
+            events.extend(crossing(
+                TippingPoint::RunawayGreenhouse,
+                &mut self.runaway,
+                runaway,
+            ));
+        }
+
+        events
+    }
+}
+
+fn crossing(point: TippingPoint, current: &mut bool, next: bool) -> Option<TippingEvent> {
+    let before = std::mem::replace(current, next);
+    match (before, next) {
+        (false, true) => Some(TippingEvent::Entered(point)),
+        (true, false) => Some(TippingEvent::Recovered(point)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn initial_regime() {
+        let thresholds = RegimeThresholds::default();
+        let regime = |c: f64| RegimeTracker::new(thresholds, Temperature::in_c(c)).regime();
+
+        assert_eq!(ClimateRegime::IceCovered, regime(-30.0));
+        assert_eq!(ClimateRegime::Temperate, regime(15.0));
+        assert_eq!(ClimateRegime::Greenhouse, regime(100.0));
+    }
+
+    #[test]
+    fn changes_are_reported_once() {
+        let mut tracker = RegimeTracker::new(Default::default(), Temperature::in_c(15.0));
+
+        let change = tracker.update(Temperature::in_c(70.0));
+        assert_eq!(
+            Some(RegimeChange {
+                from: ClimateRegime::Temperate,
+                to: ClimateRegime::Greenhouse
+            }),
+            change
+        );
+        assert_eq!(None, tracker.update(Temperature::in_c(75.0)));
+    }
+
+    #[test]
+    fn hysteresis_prevents_flickering() {
+        let mut tracker = RegimeTracker::new(Default::default(), Temperature::in_c(-10.0));
+
+        for c in [-21.0, -19.0, -24.0, -16.0] {
+            assert_eq!(None, tracker.update(Temperature::in_c(c)));
+        }
+
+        assert!(tracker.update(Temperature::in_c(-26.0)).is_some());
+        for c in [-21.0, -19.0, -16.0] {
+            assert_eq!(None, tracker.update(Temperature::in_c(c)));
+        }
+
+        assert!(tracker.update(Temperature::in_c(-14.0)).is_some());
+        assert_eq!(ClimateRegime::Temperate, tracker.regime());
+    }
+
+    #[test]
+    fn snowball_with_hysteresis() {
+        let mut detector = TippingDetector::default();
+        let boiling = Temperature::in_c(100.0);
+        let ocean = Some(Temperature::in_c(-5.0));
+
+        assert!(detector.observe(0.9, ocean, boiling).is_empty());
+        assert_eq!(
+            vec![TippingEvent::Entered(TippingPoint::Snowball)],
+            detector.observe(0.97, ocean, boiling)
+        );
+        assert!(detector.observe(0.85, ocean, boiling).is_empty());
+        assert!(detector.is_snowball());
+        assert_eq!(
+            vec![TippingEvent::Recovered(TippingPoint::Snowball)],
+            detector.observe(0.7, ocean, boiling)
+        );
+    }
+
+    #[test]
+    fn runaway_when_oceans_boil() {
+        let mut detector = TippingDetector::default();
+        let boiling = Hydrology::boiling_point(physics_types::Pressure::in_atm(1.0));
+
+        assert!((boiling - Temperature::in_c(100.0)).value.abs() < 0.01);
+        assert!(detector
+            .observe(0.0, Some(Temperature::in_c(95.0)), boiling)
+            .is_empty());
+        assert_eq!(
+            vec![TippingEvent::Entered(TippingPoint::RunawayGreenhouse)],
+            detector.observe(0.0, Some(Temperature::in_c(101.0)), boiling)
+        );
+        assert!(detector.observe(0.0, None, boiling).is_empty());
+        assert!(detector
+            .observe(0.0, Some(Temperature::in_c(95.0)), boiling)
+            .is_empty());
+        assert!(detector.is_runaway());
+    }
+}
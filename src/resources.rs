@@ -0,0 +1,125 @@
+use crate::adjacency::AdjArray;
+use crate::terrain::Terrain;
+use gen_id_enum_derive::multi_enum_array;
+use rand::Rng;
+
+multi_enum_array! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub enum Resource {
+        Iron,
+        RareMetals,
+        Fissiles,
+        WaterIce,
+    }
+}
+
+/// Richness of a tile's deposits of each resource, from 0 for none to 1 for the richest
+pub type Deposits = ResourceArray<f64>;
+
+/// Sea floor depth in m at which nodules are richest
+const ABYSS: f64 = 4000.0;
+
+/// Land elevation in m above which the ground holds the most ice
+const PERMAFROST: f64 = 5000.0;
+
+/// Places deposits on each tile according to its terrain.
+/// Mountains are richest in ore, the deep ocean floor holds nodules of iron and rare metals,
+/// and ice is buried under high ground. Deposits are spread across neighbouring tiles
+/// so that they form regions rather than scattered tiles.
+///
+/// Draws the same number of values for each tile, so the same terrain and rng always give the same deposits.
+pub fn generate_resources<R: Rng>(
+    terrain: &[Terrain],
+    adjacency: &[AdjArray],
+    rng: &mut R,
+) -> Vec<Deposits> {
+    let seeded = terrain
+        .iter()
+        .map(|terrain| {
+            let land = 1.0 - terrain.ocean.f64();
+            let mountains = terrain.mountains.f64();
+            let elevation = terrain.elevation.m();
+            let abyss = (-elevation / ABYSS).clamp(0.0, 1.0) * terrain.ocean.f64();
+            let highland = (elevation / PERMAFROST).clamp(0.0, 1.0) * land;
+
+            let mut deposits = Deposits::default();
+            deposits[Resource::Iron] =
+                (0.3 * land + 0.7 * mountains + 0.4 * abyss) * rng.gen_range(0.5..1.0);
+            deposits[Resource::RareMetals] =
+                (0.6 * mountains + 0.5 * abyss) * rng.gen::<f64>().powi(2);
+            deposits[Resource::Fissiles] =
+                (0.2 * land + 0.5 * mountains) * rng.gen::<f64>().powi(4);
+            deposits[Resource::WaterIce] = highland * rng.gen_range(0.5..1.0);
+            deposits
+        })
+        .collect::<Vec<_>>();
+
+    seeded
+        .iter()
+        .zip(adjacency)
+        .zip(terrain)
+        .map(|((own, adj), terrain)| {
+            let count = adj.len().max(1) as f64;
+            let mut deposits = Deposits::default();
+
+            for resource in Resource::iter() {
+                let neighbours = adj.iter().map(|n| seeded[n][resource]).sum::<f64>() / count;
+                deposits[resource] = (0.5 * own[resource] + 0.5 * neighbours).clamp(0.0, 1.0);
+            }
+
+            // ice spread toward the coast stays on land
+            deposits[Resource::WaterIce] *= 1.0 - terrain.ocean.f64();
+            deposits
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::terrain::Elevation;
+
+    const TILES: usize = 40;
+
+    fn deposits(terrain: &[Terrain], seed: u64) -> Vec<Deposits> {
+        let mut adjacency = Adjacency::default();
+        adjacency.register(TILES);
+        generate_resources(terrain, adjacency.get(TILES), &mut crate::rng(seed))
+    }
+
+    fn flatten(deposits: &[Deposits]) -> Vec<f64> {
+        deposits
+            .iter()
+            .flat_map(|d| Resource::iter().map(move |r| d[r]))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_deposits() {
+        let terrain = [Terrain::new(0, 100, 0); TILES];
+        let a = flatten(&deposits(&terrain, 7));
+        assert_eq!(a, flatten(&deposits(&terrain, 7)));
+        assert_ne!(a, flatten(&deposits(&terrain, 8)));
+    }
+
+    #[test]
+    fn deposits_follow_terrain() {
+        let total = |terrain: Terrain, resource: Resource| {
+            deposits(&[terrain; TILES], 1)
+                .iter()
+                .map(|d| d[resource])
+                .sum::<f64>()
+        };
+
+        let plains = Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(200.0));
+        let mountains = Terrain::new(0, 200, 0).with_elevation(Elevation::in_m(3000.0));
+        let sea_floor = Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-4000.0));
+
+        assert!(total(mountains, Resource::Iron) > total(plains, Resource::Iron));
+        assert!(total(mountains, Resource::WaterIce) > total(plains, Resource::WaterIce));
+        assert!(total(sea_floor, Resource::RareMetals) > total(plains, Resource::RareMetals));
+        assert_eq!(0.0, total(sea_floor, Resource::WaterIce));
+        assert_eq!(0.0, total(sea_floor, Resource::Fissiles));
+    }
+}
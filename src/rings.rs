@@ -0,0 +1,248 @@
+use crate::adjacency::units::Position3;
+use crate::insolation::{intensity, sun_direction, ClimateMode};
+use physics_types::{Angle, Length};
+use std::f64::consts::{PI, TAU};
+
+/// A ring of ice and rock in the planet's equatorial plane, like Saturn's,
+/// which shades the winter hemisphere and lights up the night of the summer hemisphere
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rings {
+    /// Distance of the inner edge from the centre of the planet
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub inner: Length,
+    /// Distance of the outer edge from the centre of the planet
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub outer: Length,
+    /// Optical depth to light passing straight through the ring plane
+    pub optical_depth: f64,
+}
+
+impl Rings {
+    /// Fraction of the light striking the rings that they scatter back, close to Saturn's icy rings
+    pub const ALBEDO: f64 = 0.5;
+
+    /// Saturn's main rings, from the inner edge of the C ring to the outer edge of the A ring
+    pub fn saturn() -> Self {
+        Self {
+            inner: Length::in_m(74_500e3),
+            outer: Length::in_m(136_800e3),
+            optical_depth: 1.0,
+        }
+    }
+
+    /// Fraction of sunlight that reaches a point on the surface through the rings
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: unit vector in the planet's frame, with the rotation axis along z
+    /// * `sun`: unit vector toward the sun, see `insolation::sun_direction`
+    pub fn transmission(&self, position: Position3, radius: Length, sun: [f64; 3]) -> f64 {
+        // sunlight only crosses the ring plane on its way to the hemisphere facing away from the sun
+        if position.z * sun[2] >= 0.0 {
+            return 1.0;
+        }
+
+        let (inner, outer) = self.edges(radius);
+        let t = -position.z / sun[2];
+        let x = position.x + t * sun[0];
+        let y = position.y + t * sun[1];
+        let r = x.hypot(y);
+
+        if (inner..=outer).contains(&r) {
+            (-self.optical_depth / sun[2].abs()).exp()
+        } else {
+            1.0
+        }
+    }
+
+    /// Sunlight scattered by each unit area of the lit face of the rings,
+    /// relative to a surface facing the sun.
+    /// Rings lit edge-on at the equinoxes are dark.
+    pub fn brightness(&self, sun: [f64; 3]) -> f64 {
+        let elevation = sun[2].abs();
+        if elevation < f64::EPSILON {
+            return 0.0;
+        }
+
+        let absorbed = 1.0 - (-self.optical_depth / elevation).exp();
+        Self::ALBEDO * elevation * absorbed
+    }
+
+    /// Fraction of the light leaving one face of the rings that reaches a point on the surface,
+    /// which is zero on the equator, where the rings are seen edge-on,
+    /// and near the poles, where they are below the horizon
+    pub fn view_factor(&self, position: Position3, radius: Length) -> f64 {
+        const RADII: usize = 16;
+        const AZIMUTHS: usize = 64;
+
+        let (inner, outer) = self.edges(radius);
+        let dr = (outer - inner) / RADII as f64;
+        let dphi = TAU / AZIMUTHS as f64;
+        let p = [position.x, position.y, position.z];
+
+        let mut total = 0.0;
+        for i in 0..RADII {
+            let r = inner + (i as f64 + 0.5) * dr;
+            for j in 0..AZIMUTHS {
+                let (sin, cos) = ((j as f64 + 0.5) * dphi).sin_cos();
+                let v = [r * cos - p[0], r * sin - p[1], -p[2]];
+                let d2 = v[0] * v[0] + v[1] * v[1] + v[2] * v[2];
+
+                // the dot product with the surface normal is negative below the horizon
+                let up = v[0] * p[0] + v[1] * p[1] + v[2] * p[2];
+                if up > 0.0 {
+                    total += up * v[2].abs() / (PI * d2 * d2) * r * dr * dphi;
+                }
+            }
+        }
+        total
+    }
+
+    /// Caches the view factor of each tile, see `RingShading::apply`
+    pub fn shading(&self, positions: &[Position3], radius: Length) -> RingShading {
+        RingShading {
+            rings: *self,
+            radius,
+            view: positions
+                .iter()
+                .map(|p| self.view_factor(*p, radius))
+                .collect(),
+            positions: positions.to_vec(),
+        }
+    }
+
+    /// Edges of the rings in planetary radii
+    fn edges(&self, radius: Length) -> (f64, f64) {
+        (self.inner / radius, self.outer / radius)
+    }
+}
+
+/// Shadows and reflected light of a planet's rings on each of its tiles
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RingShading {
+    rings: Rings,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    radius: Length,
+    positions: Vec<Position3>,
+    view: Vec<f64>,
+}
+
+impl RingShading {
+    /// Shades the intensity of sunlight from `ClimateMode::intensity` behind the rings,
+    /// then adds the light they scatter onto the hemisphere facing their lit side.
+    /// The planet's shadow on the rings is ignored.
+    pub fn apply(
+        &self,
+        mode: ClimateMode,
+        axial_tilt: Angle,
+        orbital_phase: f64,
+        rotation_phase: f64,
+        intensity: &mut [f64],
+    ) {
+        const HOURS: usize = 24;
+
+        let rotation_phase = match mode {
+            ClimateMode::Locked => 0.0,
+            ClimateMode::Diurnal | ClimateMode::DailyMean => rotation_phase,
+        };
+        let sun = sun_direction(axial_tilt, orbital_phase, rotation_phase);
+        let brightness = self.rings.brightness(sun);
+
+        let tiles = intensity.iter_mut().zip(&self.positions).zip(&self.view);
+        for ((intensity, p), view) in tiles {
+            match mode {
+                ClimateMode::Diurnal | ClimateMode::Locked => {
+                    *intensity *= self.rings.transmission(*p, self.radius, sun);
+                }
+                ClimateMode::DailyMean => {
+                    // the fraction of the day's sunlight that passes the rings
+                    let (open, shaded) = (0..HOURS)
+                        .map(|h| {
+                            let phase = h as f64 / HOURS as f64;
+                            let sun = sun_direction(axial_tilt, orbital_phase, phase);
+                            let open = self::intensity(*p, axial_tilt, orbital_phase, phase);
+                            (open, open * self.rings.transmission(*p, self.radius, sun))
+                        })
+                        .fold((0.0, 0.0), |(a, b), (open, shaded)| (a + open, b + shaded));
+
+                    if open > 0.0 {
+                        *intensity *= shaded / open;
+                    }
+                }
+            }
+
+            if p.z * sun[2] > 0.0 {
+                *intensity += brightness * view;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SATURN: f64 = 58_232e3;
+
+    fn tilt() -> Angle {
+        Angle::in_deg(26.7)
+    }
+
+    fn at(latitude: f64) -> Position3 {
+        let (z, x) = latitude.to_radians().sin_cos();
+        Position3 { x, y: 0.0, z }
+    }
+
+    fn shaded(mode: ClimateMode, orbital_phase: f64, rotation_phase: f64) -> Vec<f64> {
+        let positions = [at(20.0), at(-20.0), at(30.0), at(-30.0)];
+        let shading = Rings::saturn().shading(&positions, Length::in_m(SATURN));
+
+        let mut intensity = vec![0.0; positions.len()];
+        mode.intensity(
+            &positions,
+            tilt(),
+            orbital_phase,
+            rotation_phase,
+            &mut intensity,
+        );
+        shading.apply(mode, tilt(), orbital_phase, rotation_phase, &mut intensity);
+        intensity
+    }
+
+    #[test]
+    fn shadow_on_the_winter_hemisphere() {
+        let radius = Length::in_m(SATURN);
+        let rings = Rings::saturn();
+        let northern_winter = sun_direction(tilt(), 0.75, 0.0);
+        let equinox = sun_direction(tilt(), 0.0, 0.0);
+
+        assert!(rings.transmission(at(20.0), radius, northern_winter) < 0.5);
+        assert_eq!(1.0, rings.transmission(at(-20.0), radius, northern_winter));
+        assert_eq!(1.0, rings.transmission(at(20.0), radius, equinox));
+
+        let noon = shaded(ClimateMode::Diurnal, 0.75, 0.0);
+        assert!(noon[0] < noon[1]);
+
+        let daily = shaded(ClimateMode::DailyMean, 0.75, 0.0);
+        let mut open = vec![0.0; 4];
+        let positions = [at(20.0), at(-20.0), at(30.0), at(-30.0)];
+        ClimateMode::DailyMean.intensity(&positions, tilt(), 0.75, 0.0, &mut open);
+        assert!(daily[0] < open[0]);
+    }
+
+    #[test]
+    fn ringshine_at_night() {
+        let midnight = shaded(ClimateMode::Diurnal, 0.25, 0.5);
+
+        assert!(midnight[2] > 0.0);
+        assert!(midnight[2] < 0.1);
+        assert_eq!(0.0, midnight[3]);
+
+        let radius = Length::in_m(SATURN);
+        let rings = Rings::saturn();
+        assert_eq!(0.0, rings.view_factor(at(0.0), radius));
+        assert_eq!(0.0, rings.view_factor(at(90.0), radius));
+    }
+}
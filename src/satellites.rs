@@ -0,0 +1,223 @@
+use crate::adjacency::TileDensity;
+use crate::climate::ClimateConfig;
+use crate::constants::EARTH_MASS;
+use crate::dynamics::{hill_radius, roche_limit, Body};
+use crate::moon::{Moon, Primary};
+use crate::planet::{Orbit, PlanetDescriptor};
+use crate::spin::Spin;
+use physics_types::{Angle, Length, Mass};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// A moon generated by `generate_satellites`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Satellite {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    pub moon: Moon,
+}
+
+impl Satellite {
+    /// Fewest tiles given to a moon, however small
+    pub(crate) const MIN_TILES: usize = 12;
+
+    /// Mean density in kg/m³
+    pub fn density(&self) -> f64 {
+        self.body().density()
+    }
+
+    pub fn body(&self) -> Body {
+        Body {
+            mass: self.mass,
+            radius: self.moon.radius,
+        }
+    }
+
+    /// The moon as a body of its own, tidally locked to its primary and sharing the primary's orbit
+    /// around the star, whose terrain and climate can be generated with `PlanetDescriptor::generate`
+    pub fn descriptor(
+        &self,
+        primary: &Primary,
+        orbit: Orbit,
+        climate: ClimateConfig,
+    ) -> PlanetDescriptor {
+        let tiles = TileDensity::default()
+            .tile_count(self.moon.radius)
+            .max(Self::MIN_TILES);
+        let day = self.moon.orbital_period(primary);
+
+        PlanetDescriptor::new(self.moon.radius, orbit, tiles, climate)
+            .with_mass(self.mass)
+            .with_spin(Spin::new(day, Angle::default()))
+    }
+}
+
+/// Generates a plausible system of regular moons, ordered from the innermost outward.
+///
+/// Giant planets get several moons with a total mass near 10⁻⁴ of the planet's, like Jupiter's and Saturn's.
+/// Rocky planets get at most two, which can be as large as Earth's moon, relative to the planet.
+/// Moons orbit beyond the Roche limit and within half of the Hill radius,
+/// past which the star would pull them away.
+///
+/// # Arguments
+///
+/// * `star_mass`: the mass of the star that the primary orbits along `orbit`
+pub fn generate_satellites<R: Rng>(
+    primary: &Primary,
+    star_mass: Mass,
+    orbit: &Orbit,
+    rng: &mut R,
+) -> Vec<Satellite> {
+    let earths = primary.mass.value / EARTH_MASS;
+    let giant = earths > 10.0;
+
+    let count = if giant {
+        rng.gen_range(2..=6)
+    } else {
+        rng.gen_range(0..=2)
+    };
+
+    let outer = 0.5 * hill_radius(orbit, primary.mass, star_mass).value;
+
+    let mut satellites = Vec::<Satellite>::with_capacity(count);
+
+    for _ in 0..count {
+        let ratio = if giant {
+            1.0e-4 / count as f64 * rng.gen_range(0.2..2.0)
+        } else {
+            10f64.powf(rng.gen_range(-4.0..-1.9))
+        };
+        let mass = primary.mass.value * ratio;
+
+        // ice and rock, down to mostly rock
+        let density = rng.gen_range(1800.0..3500.0);
+        let radius = (mass / (4.0 / 3.0 * PI * density)).cbrt();
+
+        // with a margin for the moon to survive tides
+        let secondary = Body {
+            mass: Mass::in_kg(mass),
+            radius: Length::in_m(radius),
+        };
+        let roche = roche_limit(Body::from(*primary), secondary).value;
+
+        let distance = match satellites.last() {
+            None => 1.5 * roche * rng.gen_range(1.0..4.0),
+            // spaced like the Galilean moons
+            Some(inner) => {
+                (inner.moon.semi_major_axis.value * rng.gen_range(1.4..2.0)).max(1.5 * roche)
+            }
+        };
+
+        if distance > outer {
+            break;
+        }
+
+        satellites.push(Satellite {
+            mass: secondary.mass,
+            moon: Moon {
+                radius: secondary.radius,
+                semi_major_axis: Length::in_m(distance),
+                eccentricity: rng.gen_range(0.0..0.01),
+                tidal_response: 0.01,
+            },
+        });
+    }
+
+    satellites
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::{EARTH_RADIUS, SOLAR_MASS};
+    use crate::radiation::DoseRate;
+    use physics_types::{Duration, Temperature};
+
+    fn planet(mass: f64, radius: f64) -> Primary {
+        Primary {
+            mass: Mass::in_kg(mass),
+            radius: Length::in_m(radius),
+            belt_dose: DoseRate::default(),
+        }
+    }
+
+    fn orbit(au: f64, years: f64) -> Orbit {
+        Orbit {
+            semi_major_axis: Length::in_m(au * 149.6e9),
+            eccentricity: 0.05,
+            period: Duration::in_yr(years),
+        }
+    }
+
+    #[test]
+    fn moons_between_roche_limit_and_hill_sphere() {
+        let jupiter = planet(1.898e27, 69_911e3);
+        let orbit = orbit(5.2, 11.86);
+
+        for seed in 0..20 {
+            let moons = generate_satellites(
+                &jupiter,
+                Mass::in_kg(SOLAR_MASS),
+                &orbit,
+                &mut crate::rng(seed),
+            );
+            assert!(!moons.is_empty());
+
+            for pair in moons.windows(2) {
+                assert!(pair[0].moon.semi_major_axis < pair[1].moon.semi_major_axis);
+            }
+            for moon in &moons {
+                let a = moon.moon.semi_major_axis;
+                assert!(a > roche_limit(jupiter.into(), moon.body()));
+                assert!(a < hill_radius(&orbit, jupiter.mass, Mass::in_kg(SOLAR_MASS)) * 0.5);
+                assert!(moon.mass.value < 1e-3 * jupiter.mass.value);
+            }
+        }
+    }
+
+    #[test]
+    fn rocky_planets_have_few_moons() {
+        let earth = planet(EARTH_MASS, EARTH_RADIUS);
+        let orbit = orbit(1.0, 1.0);
+
+        for seed in 0..20 {
+            let moons = generate_satellites(
+                &earth,
+                Mass::in_kg(SOLAR_MASS),
+                &orbit,
+                &mut crate::rng(seed),
+            );
+            assert!(moons.len() <= 2);
+            assert!(moons
+                .iter()
+                .all(|m| m.mass.value < 0.013 * earth.mass.value));
+        }
+    }
+
+    #[test]
+    fn moon_descriptor_generates() {
+        let jupiter = planet(1.898e27, 69_911e3);
+        let orbit = orbit(5.2, 11.86);
+        let moons = generate_satellites(
+            &jupiter,
+            Mass::in_kg(SOLAR_MASS),
+            &orbit,
+            &mut crate::rng(1),
+        );
+
+        let descriptor = moons[0].descriptor(&jupiter, orbit, ClimateConfig::mars());
+        let state = descriptor.generate(
+            crate::atmosphere::Atmosphere::vacuum(),
+            Temperature::in_k(120.0),
+            0,
+        );
+
+        assert_eq!(descriptor.tiles(), state.terrain.len());
+        assert_eq!(moons[0].mass, descriptor.mass);
+        assert_eq!(
+            moons[0].moon.orbital_period(&jupiter),
+            descriptor.spin.period
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::adjacency::AdjArray;
+use crate::hydrology::Hydrology;
+use crate::terrain::Terrain;
+use fractional_int::FractionalU8;
+
+/// Density of liquid water in kg/m³
+pub(crate) const WATER_DENSITY: f64 = 1000.0;
+
+/// Global sea level, which rises as land ice melts into the ocean and falls as glaciers grow.
+/// Changes in level move the coastlines of low-lying coastal tiles,
+/// flooding plains before mountains and exposing plains as the sea retreats.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeaLevel {
+    /// Height of the sea above the level the terrain was generated at, in m
+    pub height: f64,
+    /// Land ice at the last step, see `land_ice`
+    land_ice: Option<f64>,
+    /// Change in height that hasn't yet moved the coastline by a whole increment, in m
+    pending: f64,
+}
+
+impl SeaLevel {
+    /// Height in m over which the land of a coastal tile rises from the shore,
+    /// so that a rise of this much floods all of it
+    pub const COASTAL_RELIEF: f64 = 20.0;
+
+    /// Compares the land ice with that of the last step and moves the coastlines by the resulting change in sea level.
+    /// The first step only records the land ice.
+    pub fn step(&mut self, terrain: &mut [Terrain], adjacency: &[AdjArray]) {
+        let land_ice = land_ice(terrain);
+        let melted = match self.land_ice.replace(land_ice) {
+            Some(previous) => previous - land_ice,
+            None => return,
+        };
+
+        let ocean = terrain.iter().map(|t| t.ocean.f64()).sum::<f64>();
+        if ocean <= 0.0 {
+            return;
+        }
+
+        let rise = melted / (WATER_DENSITY * ocean);
+        self.height += rise;
+        self.pending += rise;
+
+        let increment = Self::COASTAL_RELIEF / u8::MAX as f64;
+        let change = (self.pending / increment).trunc();
+        if change.abs() < 1.0 {
+            return;
+        }
+        self.pending -= change * increment;
+
+        let coastal = (0..terrain.len())
+            .filter(|i| self.is_coastal(terrain, adjacency, *i, change > 0.0))
+            .collect::<Vec<_>>();
+
+        let change = change.clamp(-255.0, 255.0) as i16;
+        for i in coastal {
+            terrain[i] = shift_coast(terrain[i], change);
+        }
+
+        // flooded land ice becomes sea ice without melting
+        self.land_ice = Some(self::land_ice(terrain));
+    }
+
+    /// True if the sea can reach the tile, and it lies low enough to flood if `rising`,
+    /// or shallow enough to drain if not
+    fn is_coastal(
+        &self,
+        terrain: &[Terrain],
+        adjacency: &[AdjArray],
+        i: usize,
+        rising: bool,
+    ) -> bool {
+        let tile = &terrain[i];
+        let ocean = tile.ocean.u8();
+
+        if rising {
+            let reachable = ocean > 0 || adjacency[i].iter().any(|n| terrain[n].ocean.u8() > 0);
+            ocean < u8::MAX && reachable && tile.elevation.m() < self.height + Self::COASTAL_RELIEF
+        } else {
+            let reachable =
+                ocean < u8::MAX || adjacency[i].iter().any(|n| terrain[n].ocean.u8() < u8::MAX);
+            ocean > 0 && reachable && tile.elevation.m() > self.height - Self::COASTAL_RELIEF
+        }
+    }
+}
+
+/// Mass of ice resting on land in kg per m² of a tile, summed over tiles of equal area
+pub fn land_ice(terrain: &[Terrain]) -> f64 {
+    let increment = Hydrology::GLACIER_COVER / u8::MAX as f64;
+    terrain
+        .iter()
+        .map(|t| t.land_ice().u8() as f64 * increment)
+        .sum()
+}
+
+/// Floods the tile's plains and then its mountains if `change` is positive,
+/// or returns the ocean to plains if negative, in increments of the tile
+fn shift_coast(tile: Terrain, change: i16) -> Terrain {
+    let ocean = (tile.ocean.u8() as i16 + change).clamp(0, u8::MAX as i16) as u8;
+    let mountains = tile.mountains.u8().min(u8::MAX - ocean);
+
+    let tile = Terrain {
+        ocean: FractionalU8::new(ocean),
+        mountains: FractionalU8::new(mountains),
+        plains: FractionalU8::new(u8::MAX - ocean - mountains),
+        ..tile
+    };
+
+    debug_assert!(tile.is_valid(), "{:?}", tile);
+    tile
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terrain::Elevation;
+    use std::iter::FromIterator;
+
+    fn coast() -> ([Terrain; 3], [AdjArray; 3]) {
+        let terrain = [
+            Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-1000.0)),
+            Terrain::new(100, 50, 0).with_elevation(Elevation::in_m(5.0)),
+            Terrain::new(0, 100, 255).with_elevation(Elevation::in_m(2000.0)),
+        ];
+        let adjacency = [
+            AdjArray::from_iter(vec![1]),
+            AdjArray::from_iter(vec![0, 2]),
+            AdjArray::from_iter(vec![1]),
+        ];
+        (terrain, adjacency)
+    }
+
+    #[test]
+    fn melting_floods_the_coast() {
+        let (mut terrain, adjacency) = coast();
+        let mut sea_level = SeaLevel::default();
+        sea_level.step(&mut terrain, &adjacency);
+
+        terrain[2].glacier = FractionalU8::default();
+        sea_level.step(&mut terrain, &adjacency);
+
+        assert!(sea_level.height > 0.0);
+        assert!(terrain[1].ocean.u8() > 100);
+        assert!(terrain[1].is_valid());
+        assert_eq!(0, terrain[2].ocean.u8());
+    }
+
+    #[test]
+    fn glaciation_exposes_the_shelf() {
+        let (mut terrain, adjacency) = coast();
+        terrain[2].glacier = FractionalU8::default();
+        let mut sea_level = SeaLevel::default();
+        sea_level.step(&mut terrain, &adjacency);
+
+        terrain[2].glacier = FractionalU8::new(255);
+        sea_level.step(&mut terrain, &adjacency);
+
+        assert!(sea_level.height < 0.0);
+        assert!(terrain[1].ocean.u8() < 100);
+        assert!(terrain[1].is_valid());
+        assert_eq!(255, terrain[0].ocean.u8());
+    }
+}
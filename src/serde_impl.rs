@@ -0,0 +1,310 @@
+//! Serde helpers for fields whose types don't implement serde themselves
+
+pub mod fractional_u8 {
+    use fractional_int::FractionalU8;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &FractionalU8,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.u8().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<FractionalU8, D::Error> {
+        u8::deserialize(deserializer).map(FractionalU8::new)
+    }
+}
+
+/// Hash maps are written with their keys in ascending order so that the output is stable
+pub mod sorted_map {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+    use std::hash::{BuildHasher, Hash};
+
+    pub fn serialize<K, V, H, S>(map: &HashMap<K, V, H>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Ord + Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K, V, H, D>(deserializer: D) -> Result<HashMap<K, V, H>, D::Error>
+    where
+        K: Eq + Hash + Deserialize<'de>,
+        V: Deserialize<'de>,
+        H: BuildHasher + Default,
+        D: Deserializer<'de>,
+    {
+        HashMap::<K, V, H>::deserialize(deserializer)
+    }
+}
+
+/// Physical quantities, written as their value in SI units
+pub trait Quantity: Copy {
+    fn si(self) -> f64;
+    fn from_si(value: f64) -> Self;
+}
+
+macro_rules! quantity {
+    ($($ty:ident => $new:ident),* $(,)?) => {
+        $(
+            impl Quantity for physics_types::$ty {
+                fn si(self) -> f64 {
+                    self.value
+                }
+
+                fn from_si(value: f64) -> Self {
+                    Self::$new(value)
+                }
+            }
+        )*
+    };
+}
+
+quantity!(
+    Acceleration => in_m_per_s2,
+    Angle => in_rad,
+    Area => in_m2,
+    Duration => in_s,
+    FluxDensity => in_w_per_m2,
+    Length => in_m,
+    Mass => in_kg,
+    Power => in_w,
+    Pressure => in_pa,
+    Temperature => in_k,
+);
+
+/// Times are written in seconds since the epoch
+impl Quantity for physics_types::TimeFloat {
+    fn si(self) -> f64 {
+        (self - Self::default()).value
+    }
+
+    fn from_si(value: f64) -> Self {
+        Self::default() + physics_types::Duration::in_s(value)
+    }
+}
+
+/// A quantity written as its value in SI units, see `Quantity`
+pub mod quantity {
+    use super::Quantity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<Q: Quantity, S: Serializer>(
+        value: &Q,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.si().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, Q: Quantity, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Q, D::Error> {
+        f64::deserialize(deserializer).map(Q::from_si)
+    }
+}
+
+/// A `Vec` of quantities written as their values in SI units
+pub mod quantities {
+    use super::Quantity;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<Q: Quantity, S: Serializer>(
+        values: &[Q],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(|q| q.si()))
+    }
+
+    pub fn deserialize<'de, Q: Quantity, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Q>, D::Error> {
+        let values = Vec::<f64>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(Q::from_si).collect())
+    }
+}
+
+/// An optional quantity written as its value in SI units, or null
+pub mod optional_quantity {
+    use super::Quantity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<Q: Quantity, S: Serializer>(
+        value: &Option<Q>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(Q::si).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, Q: Quantity, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Q>, D::Error> {
+        Option::<f64>::deserialize(deserializer).map(|value| value.map(Q::from_si))
+    }
+}
+
+/// Written as the value for each gas in the order of `Gas::iter`
+pub mod gas_array {
+    use crate::solar_radiation::{Gas, GasArray};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        array: &GasArray<f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(array.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<GasArray<f64>, D::Error> {
+        let values = Vec::<f64>::deserialize(deserializer)?;
+        if values.len() != Gas::iter().count() {
+            return Err(D::Error::invalid_length(
+                values.len(),
+                &"a value for each gas",
+            ));
+        }
+
+        let mut array = GasArray::default();
+        for (value, read) in array.iter_mut().zip(values) {
+            *value = read;
+        }
+        Ok(array)
+    }
+}
+
+/// Written as the value it shares, which is no longer shared once read back
+pub mod arc {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::sync::Arc;
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        value: &Arc<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_ref().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<T>, D::Error> {
+        T::deserialize(deserializer).map(Arc::new)
+    }
+}
+
+/// Written as its position in `Gas::iter`
+pub mod gas {
+    use crate::solar_radiation::Gas;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(gas: &Gas, serializer: S) -> Result<S::Ok, S::Error> {
+        let index = Gas::iter()
+            .position(|g| g == *gas)
+            .expect("every gas is in Gas::iter");
+        index.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Gas, D::Error> {
+        let index = usize::deserialize(deserializer)?;
+        Gas::iter()
+            .nth(index)
+            .ok_or_else(|| D::Error::custom(format!("no gas at index {}", index)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::adjacency::TileDensity;
+    use crate::climate::{ClimateConfig, ClimateModel};
+    use crate::constants::SOLAR_LUMINOSITY;
+    use crate::forcing::Forcing;
+    use crate::frost::FrostModel;
+    use crate::moon::MoonOverrides;
+    use crate::planet::{Orbit, Planet};
+    use crate::solar_radiation::Gas;
+    use crate::star::Star;
+    use crate::storms::{StormEvent, StormKind};
+    use crate::tile_gen::{Cratering, TileGen};
+    use physics_types::{
+        Angle, Area, Duration, FluxDensity, Length, Power, Pressure, Temperature, TimeFloat, AU,
+    };
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::fmt::Debug;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(value, serde_json::from_str::<T>(&json).unwrap());
+    }
+
+    #[test]
+    fn quantities_round_trip() {
+        round_trip(TileDensity::Area(Area::in_m2(5.0e12)));
+        round_trip(TileDensity::Spacing(Length::in_m(2.0e6)));
+        round_trip(TileGen {
+            glacier_latitude: Angle::in_deg(60.0),
+            cratering: Some(Cratering {
+                pressure: Pressure::in_pa(600.0),
+                age: Duration::in_yr(4.0e9),
+            }),
+            ..TileGen::new(0.7)
+        });
+
+        let orbit = Orbit {
+            semi_major_axis: AU,
+            eccentricity: 0.0167,
+            period: Duration::in_yr(1.0),
+        };
+        let perihelion = TimeFloat::default() + Duration::in_d(3.0);
+        round_trip(Forcing::new(
+            Power::in_w(SOLAR_LUMINOSITY),
+            orbit,
+            perihelion,
+        ));
+
+        let sun = Star::sun();
+        round_trip(sun);
+        round_trip(sun.habitable_zone());
+
+        round_trip(MoonOverrides {
+            rotation_period: Some(Duration::in_d(27.3)),
+            tidal_heating: Some(FluxDensity::in_w_per_m2(0.1)),
+            ..MoonOverrides::default()
+        });
+        round_trip(StormEvent {
+            kind: StormKind::DustStorm,
+            tile: 3,
+            start: Duration::in_d(100.0),
+            duration: Duration::in_d(20.0),
+        });
+
+        let climate = ClimateModel::new(ClimateConfig::mars(), 4, Temperature::in_k(150.0));
+        round_trip(FrostModel::new(Gas::CarbonDioxide, &climate));
+    }
+
+    #[test]
+    fn planet_round_trip() {
+        let mut planet = Planet::moon();
+        let tiles = planet.descriptor.tiles();
+        // the summary starts at infinite extremes, which JSON can't hold
+        planet.step(
+            FluxDensity::in_w_per_m2(1361.0),
+            &vec![0.5; tiles],
+            Duration::in_hr(1.0),
+        );
+
+        let json = serde_json::to_string(&planet).unwrap();
+        let copy = serde_json::from_str::<Planet>(&json).unwrap();
+
+        assert_eq!(planet.descriptor, copy.descriptor);
+        assert_eq!(json, serde_json::to_string(&copy).unwrap());
+    }
+}
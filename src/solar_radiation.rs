@@ -1,6 +1,7 @@
+use crate::constants::GAS_CONSTANT;
 use fractional_int::FractionalU8;
 use iter_context::ContextualIterator;
-use physics_types::{Duration, FluxDensity, MolecularMass};
+use physics_types::{Duration, FluxDensity, MolecularMass, Pressure, Temperature};
 use std::ops::{Mul, Not};
 
 // TODO incorporate chemicals that increase albedo
@@ -19,12 +20,15 @@ use std::ops::{Mul, Not};
 ///     Mars
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Element {
     Hydrogen,
     Helium,
     Carbon,
     Oxygen,
     Nitrogen,
+    Sulfur,
+    Argon,
 }
 
 impl Element {
@@ -35,6 +39,8 @@ impl Element {
             Element::Carbon => 12.011,
             Element::Oxygen => 15.999,
             Element::Nitrogen => 14.007,
+            Element::Sulfur => 32.06,
+            Element::Argon => 39.948,
         };
         MolecularMass::in_g_per_mol(grams_per_mole)
     }
@@ -45,6 +51,8 @@ pub const HE: Element = Element::Hydrogen;
 pub const C: Element = Element::Carbon;
 pub const O: Element = Element::Oxygen;
 pub const N: Element = Element::Nitrogen;
+pub const S: Element = Element::Sulfur;
+pub const AR: Element = Element::Argon;
 
 use gen_id_enum_derive::multi_enum_array;
 
@@ -58,6 +66,10 @@ multi_enum_array! {
         Water,
         Methane,
         CarbonDioxide,
+        Argon,
+        Ammonia,
+        SulfurDioxide,
+        Ozone,
     }
 }
 
@@ -71,15 +83,33 @@ impl Gas {
             Gas::Water => H.mass() * 2.0 + O.mass(),
             Gas::Methane => C.mass() + H.mass() * 4.0,
             Gas::CarbonDioxide => C.mass() + O.mass() * 2.0,
+            Gas::Argon => AR.mass(),
+            Gas::Ammonia => N.mass() + H.mass() * 3.0,
+            Gas::SulfurDioxide => S.mass() + O.mass() * 2.0,
+            Gas::Ozone => O.mass() * 3.0,
         }
     }
 
     /// https://en.wikipedia.org/wiki/Global_warming_potential#Values
+    /// Ozone, ammonia, and sulfur dioxide break down within weeks on Earth and have no accepted value,
+    /// so theirs are estimates for an atmosphere that resupplies them, as on Venus or the early Earth
     pub fn co2_equivalence(&self) -> f64 {
         match self {
             Gas::CarbonDioxide => 1.0,
             Gas::Methane => 84.0,
             Gas::Water => 0.39,
+            Gas::Ozone => 1000.0,
+            Gas::Ammonia => 20.0,
+            Gas::SulfurDioxide => 10.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Optical depth to UV-B per atm of partial pressure, averaged over the column.
+    /// Only ozone absorbs strongly enough to matter, giving Earth's ozone layer an optical depth near 3.
+    pub fn uv_optical_depth(&self) -> f64 {
+        match self {
+            Gas::Ozone => 8.0e6,
             _ => 0.0,
         }
     }
@@ -97,12 +127,123 @@ impl Gas {
         }
     }
 
+    /// Temperature and pressure at which the solid, liquid, and gas coexist,
+    /// or `None` for helium, which stays liquid down to absolute zero at low pressure
+    pub fn triple_point(&self) -> Option<(Temperature, Pressure)> {
+        let (temperature, pascals) = match self {
+            Gas::Hydrogen => (13.80, 7.04e3),
+            Gas::Helium => return None,
+            Gas::Nitrogen => (63.15, 12.52e3),
+            Gas::Oxygen => (54.36, 146.3),
+            Gas::Water => (273.16, 611.657),
+            Gas::Methane => (90.69, 11.70e3),
+            Gas::CarbonDioxide => (216.55, 517.0e3),
+            Gas::Argon => (83.81, 68.9e3),
+            Gas::Ammonia => (195.4, 6.06e3),
+            Gas::SulfurDioxide => (197.69, 1.67e3),
+            Gas::Ozone => (80.7, 1.14),
+        };
+        Some((Temperature::in_k(temperature), Pressure::in_pa(pascals)))
+    }
+
+    /// Two points on the boiling curve: the triple point or lowest liquid point,
+    /// and the normal boiling point or the critical point where there is none
+    fn boiling_curve(&self) -> ((f64, f64), (f64, f64)) {
+        const ATM: f64 = 101_325.0;
+        match self {
+            Gas::Hydrogen => ((13.80, 7.04e3), (20.27, ATM)),
+            Gas::Helium => ((4.22, ATM), (5.19, 227.5e3)),
+            Gas::Nitrogen => ((63.15, 12.52e3), (77.36, ATM)),
+            Gas::Oxygen => ((54.36, 146.3), (90.19, ATM)),
+            Gas::Water => ((273.16, 611.657), (373.15, ATM)),
+            Gas::Methane => ((90.69, 11.70e3), (111.65, ATM)),
+            Gas::CarbonDioxide => ((216.55, 517.0e3), (304.13, 7.3773e6)),
+            Gas::Argon => ((83.81, 68.9e3), (87.30, ATM)),
+            Gas::Ammonia => ((195.4, 6.06e3), (239.8, ATM)),
+            Gas::SulfurDioxide => ((197.69, 1.67e3), (263.1, ATM)),
+            Gas::Ozone => ((80.7, 1.14), (161.8, ATM)),
+        }
+    }
+
+    /// Latent heat of sublimation near the triple point in J/mol
+    fn sublimation_enthalpy(&self) -> Option<f64> {
+        match self {
+            Gas::Hydrogen => Some(1.0e3),
+            Gas::Helium => None,
+            Gas::Nitrogen => Some(6.8e3),
+            Gas::Oxygen => Some(8.2e3),
+            Gas::Water => Some(51.06e3),
+            Gas::Methane => Some(9.7e3),
+            Gas::CarbonDioxide => Some(25.2e3),
+            Gas::Argon => Some(7.7e3),
+            Gas::Ammonia => Some(29.0e3),
+            Gas::SulfurDioxide => Some(32.3e3),
+            Gas::Ozone => Some(17.3e3),
+        }
+    }
+
+    /// The temperature at which the liquid boils under the given pressure, by an Antoine-style fit
+    /// of `ln p = A - B / T` through two points on the boiling curve.
+    /// Below the triple point pressure the liquid doesn't form, see `frost_point`.
+    pub fn boiling_point(&self, pressure: Pressure) -> Temperature {
+        let ((t1, p1), (t2, p2)) = self.boiling_curve();
+        let b = (p2 / p1).ln() / (t1.recip() - t2.recip());
+        let inverse = t1.recip() - (pressure.value / p1).ln() / b;
+        Temperature::in_k(inverse.recip().max(0.0))
+    }
+
+    /// The temperature below which the gas freezes onto the surface at the given partial pressure,
+    /// by the Clausius–Clapeyron relation through the triple point,
+    /// or `None` for helium, which doesn't freeze at low pressure
+    pub fn frost_point(&self, partial_pressure: Pressure) -> Option<Temperature> {
+        let (triple, triple_pressure) = self.triple_point()?;
+        let latent = self.sublimation_enthalpy()?;
+        let ratio = partial_pressure / triple_pressure;
+        let inverse = triple.value.recip() - GAS_CONSTANT * ratio.ln() / latent;
+        Some(Temperature::in_k(inverse.recip().max(0.0)))
+    }
+
+    /// Heat released when the gas freezes from vapour, in J/kg
+    pub fn latent_heat(&self) -> Option<f64> {
+        Some(self.sublimation_enthalpy()? / self.molecular_mass().value)
+    }
+
+    /// Whether the substance is solid, liquid, or gas at the given temperature and pressure.
+    /// The melting curve is taken as the triple point temperature at all pressures,
+    /// and a fluid above the critical point counts as gas.
+    pub fn phase(&self, temperature: Temperature, pressure: Pressure) -> Phase {
+        let boiling = self.boiling_point(pressure);
+
+        match self.triple_point() {
+            None if temperature < boiling => Phase::Liquid,
+            None => Phase::Gas,
+            Some((_, triple_pressure)) if pressure < triple_pressure => {
+                match self.frost_point(pressure) {
+                    Some(frost) if temperature < frost => Phase::Solid,
+                    _ => Phase::Gas,
+                }
+            }
+            Some((triple, _)) if temperature < triple => Phase::Solid,
+            Some(_) if temperature < boiling => Phase::Liquid,
+            Some(_) => Phase::Gas,
+        }
+    }
+
     pub fn annual_decay_multiplier(&self) -> Option<f64> {
         self.half_life()
             .map(|t| 0.5_f64.powf(Duration::in_yr(1.0) / t))
     }
 }
 
+/// The state of matter of a substance
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    Solid,
+    Liquid,
+    Gas,
+}
+
 impl GasArray<f64> {
     pub fn molecular_mass(&self) -> MolecularMass {
         let mut value_sum = 0f64;
@@ -127,6 +268,7 @@ impl GasArray<f64> {
 
 /// Earth's emissivity: https://phzoe.com/2019/11/05/what-is-earths-surface-emissivity/
 #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Emissivity(f64);
 
 impl Emissivity {
@@ -139,6 +281,7 @@ impl Emissivity {
 
 /// radiative absorption = 1 - albedo
 #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RadiativeAbsorption(pub f64);
 
 impl RadiativeAbsorption {
@@ -203,6 +346,7 @@ impl Mul<RadiativeAbsorption> for FluxDensity {
 
 /// https://en.wikipedia.org/wiki/Albedo
 #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Albedo(pub f64);
 
 impl Albedo {
@@ -231,6 +375,7 @@ impl const std::ops::Not for Albedo {
 
 /// infrared transparency = 1 - fraction reflected back to surface
 #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InfraredTransparency(pub f64);
 
 impl InfraredTransparency {
@@ -334,4 +479,50 @@ mod test {
             array.molecular_mass()
         );
     }
+
+    #[test]
+    fn water_phases_on_earth_and_mars() {
+        let atm = Pressure::in_atm(1.0);
+        let phase = |c: f64, p: Pressure| Gas::Water.phase(Temperature::in_c(c), p);
+
+        assert!((Gas::Water.boiling_point(atm).value - 373.15).abs() < 1e-9);
+        assert_eq!(Phase::Solid, phase(-10.0, atm));
+        assert_eq!(Phase::Liquid, phase(15.0, atm));
+        assert_eq!(Phase::Gas, phase(110.0, atm));
+        assert_eq!(Phase::Gas, phase(5.0, Pressure::in_atm(0.006)));
+    }
+
+    #[test]
+    fn titan_and_mars() {
+        let titan = Pressure::in_atm(1.45);
+        assert_eq!(
+            Phase::Liquid,
+            Gas::Methane.phase(Temperature::in_k(94.0), titan)
+        );
+        assert_eq!(
+            Phase::Gas,
+            Gas::Nitrogen.phase(Temperature::in_k(94.0), titan)
+        );
+
+        let mars = Pressure::in_atm(0.006);
+        let co2 = |k: f64| Gas::CarbonDioxide.phase(Temperature::in_k(k), mars);
+        assert_eq!(Phase::Solid, co2(140.0));
+        assert_eq!(Phase::Gas, co2(210.0));
+
+        let sublimation = Gas::CarbonDioxide
+            .frost_point(Pressure::in_atm(1.0))
+            .unwrap();
+        assert!((sublimation.value - 194.7).abs() < 1.0);
+        assert_eq!(None, Gas::Helium.frost_point(mars));
+    }
+
+    #[test]
+    fn new_gas_masses() {
+        let grams = |gas: Gas| gas.molecular_mass() / MolecularMass::in_g_per_mol(1.0);
+
+        assert!((grams(Gas::Argon) - 39.948).abs() < 1e-9);
+        assert!((grams(Gas::Ammonia) - 17.031).abs() < 1e-3);
+        assert!((grams(Gas::SulfurDioxide) - 64.058).abs() < 1e-3);
+        assert!((grams(Gas::Ozone) - 47.997).abs() < 1e-3);
+    }
 }
@@ -0,0 +1,179 @@
+use crate::adjacency::units::Position3;
+use crate::planet::Orbit;
+use orbital_mechanics::pga::{line, motor, origin, point, Bivector, RightComp, Sandwich};
+use orbital_mechanics::Rotation;
+use physics_types::{Angle, Duration};
+use std::f64::consts::TAU;
+
+/// A planet's rotation about its axis, and the tilt of that axis from the orbit normal
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spin {
+    /// Sidereal rotation period, measured against the stars rather than the sun
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub period: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub axial_tilt: Angle,
+    /// Period over which the tilted axis sweeps around the orbit normal, if it precesses
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_impl::optional_quantity")
+    )]
+    pub precession: Option<Duration>,
+}
+
+impl Spin {
+    pub fn new(period: Duration, axial_tilt: Angle) -> Self {
+        Self {
+            period,
+            axial_tilt,
+            precession: None,
+        }
+    }
+
+    /// Rotates once per orbit without tilt, keeping one hemisphere facing the star
+    pub fn tidally_locked(orbit: &Orbit) -> Self {
+        Self::new(orbit.period, Angle::default())
+    }
+
+    /// Rotates `rotations` times every `orbits` orbits without tilt, e.g., 3:2 for Mercury
+    pub fn resonance(rotations: u32, orbits: u32, orbit: &Orbit) -> Self {
+        assert!(rotations > 0 && orbits > 0);
+        Self::new(
+            orbit.period * (orbits as f64 / rotations as f64),
+            Angle::default(),
+        )
+    }
+
+    /// The time between noons, or `None` if the planet is tidally locked and the sun never moves
+    pub fn solar_day(&self, orbit: &Orbit) -> Option<Duration> {
+        // rotations per second relative to the star
+        let rate = self.period.value.recip() - orbit.period.value.recip();
+        (rate.abs() > 1e-9 * self.period.value.recip()).then(|| Duration::in_s(rate.recip().abs()))
+    }
+
+    /// The fraction of the solar day since noon at the x axis, see `insolation::intensity`.
+    /// Always zero for a tidally locked planet, whose substellar point stays on the x axis.
+    pub fn rotation_phase(&self, orbit: &Orbit, elapsed: Duration) -> f64 {
+        match self.solar_day(orbit) {
+            Some(day) => (elapsed / day).rem_euclid(1.0),
+            None => 0.0,
+        }
+    }
+
+    pub fn with_precession(mut self, period: Duration) -> Self {
+        self.precession = Some(period);
+        self
+    }
+
+    /// The direction the axis leans around the orbit normal in radians, zero toward the x axis
+    pub fn precession_angle(&self, elapsed: Duration) -> f64 {
+        match self.precession {
+            Some(period) => TAU * (elapsed / period),
+            None => 0.0,
+        }
+    }
+
+    /// The rotation axis as a unit vector in the orbital frame, with the orbit normal along z
+    pub fn axis(&self, elapsed: Duration) -> Position3 {
+        let (sin_tilt, cos_tilt) = self.axial_tilt.value.sin_cos();
+        let (sin_az, cos_az) = self.precession_angle(elapsed).sin_cos();
+
+        Position3 {
+            x: sin_tilt * cos_az,
+            y: sin_tilt * sin_az,
+            z: cos_tilt,
+        }
+    }
+
+    /// The rotation of the planet at a time since the reference epoch
+    pub fn rotation(&self, elapsed: Duration) -> Rotation {
+        let axis = self.axis(elapsed);
+
+        Rotation {
+            sidereal_speed: Angle::TAU / self.period,
+            axis: line(origin(), point(axis.x, axis.y, axis.z)),
+        }
+    }
+
+    /// The surface of each tile centred on `positions`, tilted into the orbital frame,
+    /// see `Adjacency::positions`. Apply the motor of `rotation` for the rotation about the axis.
+    pub fn surfaces(&self, positions: &[Position3], elapsed: Duration) -> Vec<Bivector> {
+        let tilt = motor(
+            line(origin(), point(0.0, 1.0, 0.0)),
+            0.0,
+            self.axial_tilt.value,
+        );
+        let precession = motor(
+            line(origin(), point(0.0, 0.0, 1.0)),
+            0.0,
+            self.precession_angle(elapsed),
+        );
+
+        positions
+            .iter()
+            .map(|p| line(origin(), point(p.x, p.y, p.z)).r_comp())
+            .map(|s| precession.sandwich(tilt.sandwich(s)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::{Spiral, Tiling};
+
+    #[test]
+    fn axis_leans_by_the_tilt() {
+        let spin = Spin::new(Duration::in_d(1.0), Angle::in_deg(23.44));
+        let axis = spin.axis(Duration::in_yr(100.0));
+
+        assert!((axis.z - Angle::in_deg(23.44).value.cos()).abs() < 1e-12);
+        assert!(axis.y.abs() < 1e-12);
+    }
+
+    #[test]
+    fn axis_precesses() {
+        let spin = Spin::new(Duration::in_d(1.0), Angle::in_deg(23.44))
+            .with_precession(Duration::in_yr(25772.0));
+
+        let start = spin.axis(Duration::default());
+        let half = spin.axis(Duration::in_yr(25772.0 / 2.0));
+
+        assert!((start.x + half.x).abs() < 1e-9);
+        assert!((start.z - half.z).abs() < 1e-12);
+    }
+
+    fn orbit() -> Orbit {
+        Orbit {
+            semi_major_axis: physics_types::Length::in_m(57.9e9),
+            eccentricity: 0.2056,
+            period: Duration::in_d(87.97),
+        }
+    }
+
+    #[test]
+    fn tidally_locked_has_no_solar_day() {
+        let spin = Spin::tidally_locked(&orbit());
+
+        assert_eq!(orbit().period, spin.period);
+        assert_eq!(None, spin.solar_day(&orbit()));
+        assert_eq!(0.0, spin.rotation_phase(&orbit(), Duration::in_d(40.0)));
+    }
+
+    #[test]
+    fn mercury_resonance() {
+        let spin = Spin::resonance(3, 2, &orbit());
+        let day = spin.solar_day(&orbit()).unwrap();
+
+        assert!((spin.period / Duration::in_d(58.646) - 1.0).abs() < 1e-3);
+        assert!((day / Duration::in_d(175.94) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn surface_for_each_tile() {
+        let spin = Spin::new(Duration::in_d(1.0), Angle::in_deg(23.44));
+        let positions = Spiral.positions(48);
+        assert_eq!(48, spin.surfaces(&positions, Duration::default()).len());
+    }
+}
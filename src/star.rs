@@ -0,0 +1,239 @@
+use crate::constants::{SOLAR_LUMINOSITY, SOLAR_MASS};
+use physics_types::{Duration, Length, Mass, Power, Temperature, AU};
+use std::f64::consts::PI;
+
+/// Wien's displacement constant in m·K
+const WIEN: f64 = 2.897771955e-3;
+
+/// A main-sequence star as it is at `age`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Star {
+    /// Effective temperature of the photosphere
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub temperature: Temperature,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    /// Age at which the star has this temperature and radius
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub age: Duration,
+}
+
+impl Star {
+    /// Luminosity grows as `1 / (1 - BRIGHTENING * age / lifetime)`,
+    /// fitted to the sun having brightened by about 40% since it formed
+    const BRIGHTENING: f64 = 0.625;
+
+    /// Temperature below which water ice condenses from the disc around a young star
+    pub const SNOW_LINE_TEMPERATURE: Temperature = Temperature::in_k(170.0);
+
+    pub fn new(temperature: Temperature, radius: Length, mass: Mass, age: Duration) -> Self {
+        Self {
+            temperature,
+            radius,
+            mass,
+            age,
+        }
+    }
+
+    pub fn sun() -> Self {
+        Self::new(
+            Temperature::in_k(5772.0),
+            Length::in_m(695.7e6),
+            Mass::in_kg(SOLAR_MASS),
+            Duration::in_yr(4.57e9),
+        )
+    }
+
+    /// Luminosity of a black body with the star's temperature and radius
+    pub fn luminosity(&self) -> Power {
+        Power::blackbody(self.temperature, self.radius)
+    }
+
+    /// Time spent on the main sequence, about 10 billion years for the sun and shorter for heavier stars
+    pub fn main_sequence_lifetime(&self) -> Duration {
+        let mass = self.mass.value / SOLAR_MASS;
+        Duration::in_yr(1.0e10 * mass.powf(-2.5))
+    }
+
+    /// Luminosity at any age on the main sequence, brightening as hydrogen is burned following
+    /// Gough (1981), so a young sun is fainter and an old one drives a runaway greenhouse.
+    /// Ages outside the main sequence are clamped to its start or end.
+    pub fn luminosity_at_age(&self, age: Duration) -> Power {
+        let lifetime = self.main_sequence_lifetime();
+        let dimming = |age: Duration| {
+            let fraction = (age / lifetime).clamp(0.0, 1.0);
+            1.0 - Self::BRIGHTENING * fraction
+        };
+
+        self.luminosity() * (dimming(self.age) / dimming(age))
+    }
+
+    /// The habitable zone at the star's current luminosity
+    pub fn habitable_zone(&self) -> HabitableZone {
+        HabitableZone::new(self)
+    }
+
+    /// The distance beyond which water ice condenses in the protoplanetary disc,
+    /// where the disc's temperature falls below `SNOW_LINE_TEMPERATURE`.
+    /// Uses the luminosity when the star formed, so it marks where giant planets and icy bodies formed.
+    pub fn snow_line(&self) -> Length {
+        // disc temperature falls from 280 K at 1 AU around the sun as the square root of distance
+        let luminosity = self.luminosity_at_age(Duration::default()).value / SOLAR_LUMINOSITY;
+        let ratio = 280.0 / Self::SNOW_LINE_TEMPERATURE.value;
+        AU * (ratio * ratio * luminosity.sqrt())
+    }
+
+    /// The wavelength at which the star's spectrum is brightest
+    pub fn peak_wavelength(&self) -> Length {
+        Length::in_m(WIEN / self.temperature.value)
+    }
+
+    /// The fraction of the star's light between two wavelengths,
+    /// e.g., 400–700 nm for photosynthetically active radiation
+    pub fn spectral_fraction(&self, shortest: Length, longest: Length) -> f64 {
+        planck_fraction(self.temperature, longest) - planck_fraction(self.temperature, shortest)
+    }
+}
+
+/// The orbits at which an Earth-like planet could hold liquid water on its surface
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HabitableZone {
+    /// Where Venus may have lost its water, about 1 Gyr ago
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub recent_venus: Length,
+    /// Inner edge of the conservative zone, where oceans would boil away
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub runaway_greenhouse: Length,
+    /// Outer edge of the conservative zone, beyond which added CO2 cools more than it warms
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub maximum_greenhouse: Length,
+    /// Where Mars may have been habitable, about 3.8 Gyr ago
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub early_mars: Length,
+}
+
+impl HabitableZone {
+    /// The effective stellar flux relative to Earth's at each edge, by the polynomial fits of
+    /// Kopparapu et al. (2014) for an Earth-mass planet around stars of 2600–7200 K
+    fn flux(temperature: Temperature, coefficients: [f64; 5]) -> f64 {
+        let t = temperature.value.clamp(2600.0, 7200.0) - 5780.0;
+        let [s, a, b, c, d] = coefficients;
+        s + t * (a + t * (b + t * (c + t * d)))
+    }
+
+    pub fn new(star: &Star) -> Self {
+        let luminosity = star.luminosity().value / SOLAR_LUMINOSITY;
+        let edge = |coefficients| {
+            let flux = Self::flux(star.temperature, coefficients);
+            AU * (luminosity / flux).sqrt()
+        };
+
+        Self {
+            recent_venus: edge([1.776, 2.136e-4, 2.533e-8, -1.332e-11, -3.097e-15]),
+            runaway_greenhouse: edge([1.107, 1.332e-4, 1.580e-8, -8.308e-12, -1.931e-15]),
+            maximum_greenhouse: edge([0.356, 6.171e-5, 1.698e-9, -3.198e-12, -5.575e-16]),
+            early_mars: edge([0.320, 5.547e-5, 1.526e-9, -2.874e-12, -5.011e-16]),
+        }
+    }
+
+    pub fn is_conservative(&self, distance: Length) -> bool {
+        self.runaway_greenhouse <= distance && distance <= self.maximum_greenhouse
+    }
+
+    pub fn is_optimistic(&self, distance: Length) -> bool {
+        self.recent_venus <= distance && distance <= self.early_mars
+    }
+}
+
+/// Fraction of black-body emission at wavelengths shorter than `wavelength`,
+/// by the series expansion of the integrated Planck function,
+/// or its expansion for long wavelengths where the first converges slowly
+fn planck_fraction(temperature: Temperature, wavelength: Length) -> f64 {
+    // second radiation constant hc/k in m·K
+    const C2: f64 = 1.438777e-2;
+
+    let x = C2 / (wavelength.value * temperature.value);
+    if !x.is_finite() {
+        return 0.0;
+    }
+
+    if x < 1.0 {
+        let x2 = x * x;
+        let series = 1.0 / 3.0 - x / 8.0 + x2 / 60.0 - x2 * x2 / 5040.0 + x2 * x2 * x2 / 272160.0
+            - x2 * x2 * x2 * x2 / 13305600.0;
+        return (1.0 - 15.0 / PI.powi(4) * x2 * x * series).clamp(0.0, 1.0);
+    }
+
+    let sum = (1..=64)
+        .map(|n| {
+            let n = n as f64;
+            (-n * x).exp() / n * (x.powi(3) + 3.0 * x * x / n + 6.0 * x / (n * n) + 6.0 / n.powi(3))
+        })
+        .sum::<f64>();
+
+    (15.0 / PI.powi(4) * sum).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sun_luminosity() {
+        let sun = Star::sun();
+        assert!((sun.luminosity().value / SOLAR_LUMINOSITY - 1.0).abs() < 0.01);
+        assert!((sun.peak_wavelength().value - 502e-9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn faint_young_sun() {
+        let sun = Star::sun();
+        let young = sun.luminosity_at_age(Duration::default()) / sun.luminosity();
+        let old = sun.luminosity_at_age(Duration::in_yr(9.0e9)) / sun.luminosity();
+
+        assert!((young - 0.71).abs() < 0.01, "{}", young);
+        assert!(old > 1.5);
+        assert_eq!(sun.luminosity(), sun.luminosity_at_age(sun.age));
+    }
+
+    #[test]
+    fn sun_habitable_zone() {
+        let zone = Star::sun().habitable_zone();
+        let au = |d: Length| d / AU;
+
+        assert!((au(zone.runaway_greenhouse) - 0.95).abs() < 0.01);
+        assert!((au(zone.maximum_greenhouse) - 1.67).abs() < 0.01);
+        assert!(zone.is_conservative(AU));
+        assert!(zone.is_optimistic(AU * 1.52));
+        assert!(!zone.is_optimistic(AU * 0.72));
+    }
+
+    #[test]
+    fn snow_line_beyond_the_asteroid_belt() {
+        let sun = Star::sun();
+        let red_dwarf = Star::new(
+            Temperature::in_k(3100.0),
+            Length::in_m(0.15 * 695.7e6),
+            Mass::in_kg(0.12 * SOLAR_MASS),
+            Duration::in_yr(1.0e9),
+        );
+
+        assert!((sun.snow_line() / AU - 2.3).abs() < 0.1);
+        assert!(red_dwarf.snow_line() < AU * 0.2);
+        assert!(red_dwarf.habitable_zone().early_mars < sun.habitable_zone().recent_venus);
+    }
+
+    #[test]
+    fn visible_light_fraction() {
+        let sun = Star::sun();
+        let visible = sun.spectral_fraction(Length::in_m(400e-9), Length::in_m(700e-9));
+        let all = sun.spectral_fraction(Length::in_m(1e-9), Length::in_m(1.0));
+
+        assert!((visible - 0.37).abs() < 0.02, "{}", visible);
+        assert!((all - 1.0).abs() < 1e-6);
+    }
+}
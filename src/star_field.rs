@@ -0,0 +1,239 @@
+use crate::adjacency::units::Position3;
+use crate::star;
+use physics_types::{Duration, FluxDensity, Length, Power, TimeFloat};
+use std::f64::consts::PI;
+
+/// The former name of `LightSource`, before `star::Star` described the star itself
+#[deprecated(note = "renamed to `LightSource`")]
+pub type Star = LightSource;
+
+/// A star's light and where it is at any time, in metres in the system's frame
+pub struct LightSource {
+    pub luminosity: Power,
+    pub radius: Length,
+    position: Box<dyn Fn(TimeFloat) -> Position3 + Send + Sync>,
+}
+
+impl LightSource {
+    pub fn new(
+        luminosity: Power,
+        radius: Length,
+        position: impl Fn(TimeFloat) -> Position3 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            luminosity,
+            radius,
+            position: Box::new(position),
+        }
+    }
+
+    /// The light of a star at the given age, see `Star::luminosity_at_age`
+    pub fn from_star(
+        star: &star::Star,
+        age: Duration,
+        position: impl Fn(TimeFloat) -> Position3 + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(star.luminosity_at_age(age), star.radius, position)
+    }
+
+    pub fn position(&self, time: TimeFloat) -> Position3 {
+        (self.position)(time)
+    }
+}
+
+impl std::fmt::Debug for LightSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LightSource")
+            .field("luminosity", &self.luminosity)
+            .field("radius", &self.radius)
+            .finish()
+    }
+}
+
+/// The light reaching a planet from one star
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StarLight {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub flux: FluxDensity,
+    /// Unit vector from the planet toward the star
+    pub direction: Position3,
+}
+
+/// Every star lighting a planet, for circumbinary planets or wide binaries.
+/// Stars passing in front of one another dim the light of the farther star.
+#[derive(Debug, Default)]
+pub struct StarField {
+    pub stars: Vec<LightSource>,
+}
+
+impl StarField {
+    pub fn with_star(mut self, star: LightSource) -> Self {
+        self.stars.push(star);
+        self
+    }
+
+    /// The light from each star at a planet's position, in the same order as `stars`
+    pub fn light(&self, planet: Position3, time: TimeFloat) -> Vec<StarLight> {
+        // direction, distance, and angular radius of each star
+        let discs = self
+            .stars
+            .iter()
+            .map(|star| {
+                let p = star.position(time);
+                let offset = [p.x - planet.x, p.y - planet.y, p.z - planet.z];
+                let distance = dot(offset, offset).sqrt();
+                let direction = offset.map(|c| c / distance);
+                let angular_radius = (star.radius.value / distance).min(1.0).asin();
+                (direction, distance, angular_radius)
+            })
+            .collect::<Vec<_>>();
+
+        self.stars
+            .iter()
+            .zip(&discs)
+            .map(|(star, &(direction, distance, radius))| {
+                let visible = discs
+                    .iter()
+                    .filter(|(_, d, _)| *d < distance)
+                    .map(|&(other, _, other_radius)| {
+                        let separation = dot(direction, other).clamp(-1.0, 1.0).acos();
+                        overlap(radius, other_radius, separation) / (PI * radius * radius)
+                    })
+                    .fold(1.0, |visible: f64, hidden| visible - hidden)
+                    .max(0.0);
+
+                let flux = star.luminosity.value / (4.0 * PI * distance * distance);
+                StarLight {
+                    flux: FluxDensity::in_w_per_m2(flux * visible),
+                    direction: Position3 {
+                        x: direction[0],
+                        y: direction[1],
+                        z: direction[2],
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Combines the light of every star into the flux and intensity used by `ClimateModel::step`,
+    /// so that each tile absorbs the sum of the flux from each star above its horizon.
+    ///
+    /// # Arguments
+    ///
+    /// * `normals`: unit vector of each tile's surface in the system's frame at this time
+    pub fn intensity(
+        &self,
+        planet: Position3,
+        time: TimeFloat,
+        normals: &[Position3],
+        intensity: &mut [f64],
+    ) -> FluxDensity {
+        let light = self.light(planet, time);
+        let total = light.iter().map(|l| l.flux.value).sum::<f64>();
+
+        for (intensity, n) in intensity.iter_mut().zip(normals) {
+            let normal = [n.x, n.y, n.z];
+            let weighted = light
+                .iter()
+                .map(|l| {
+                    let d = [l.direction.x, l.direction.y, l.direction.z];
+                    l.flux.value * dot(normal, d).max(0.0)
+                })
+                .sum::<f64>();
+
+            *intensity = if total > 0.0 { weighted / total } else { 0.0 };
+        }
+
+        FluxDensity::in_w_per_m2(total)
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// The area where two discs overlap, treating small angles as flat
+pub(crate) fn overlap(r1: f64, r2: f64, separation: f64) -> f64 {
+    let d = separation;
+    if d >= r1 + r2 {
+        return 0.0;
+    }
+    if d <= (r1 - r2).abs() {
+        let r = r1.min(r2);
+        return PI * r * r;
+    }
+
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let kite = 0.5 * ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).sqrt();
+
+    r1 * r1 * a1 + r2 * r2 * a2 - kite
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use physics_types::AU;
+
+    fn star(x: f64) -> LightSource {
+        LightSource::from_star(&star::Star::sun(), star::Star::sun().age, move |_| {
+            Position3 { x, y: 0.0, z: 0.0 }
+        })
+    }
+
+    #[test]
+    fn binary_stars_on_either_side() {
+        let field = StarField::default()
+            .with_star(star(AU.value))
+            .with_star(star(-AU.value));
+        let normals = [
+            Position3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            Position3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+        ];
+        let mut intensity = [0.0; 2];
+
+        let flux = field.intensity(
+            Position3::default(),
+            TimeFloat::default(),
+            &normals,
+            &mut intensity,
+        );
+
+        assert!((flux.value - 2.0 * 1361.0).abs() < 2.0);
+        assert!((intensity[0] - 0.5).abs() < 1e-12);
+        assert!(intensity[1].abs() < 1e-12);
+    }
+
+    #[test]
+    fn nearer_star_hides_farther_star() {
+        let field = StarField::default()
+            .with_star(star(2.0 * AU.value))
+            .with_star(star(AU.value));
+
+        let light = field.light(Position3::default(), TimeFloat::default());
+
+        assert_eq!(0.0, light[0].flux.value);
+        assert!((light[1].flux.value - 1361.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn partial_overlap() {
+        let half = overlap(1.0, 1.0, 1.0) / PI;
+        assert!(half > 0.0 && half < 1.0);
+        assert_eq!(0.0, overlap(1.0, 1.0, 2.5));
+        assert!((overlap(1.0, 0.5, 0.1) - PI * 0.25).abs() < 1e-12);
+    }
+}
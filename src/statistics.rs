@@ -0,0 +1,76 @@
+/// Running weighted mean and extremes of a series of values
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightedStats {
+    weight: f64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for WeightedStats {
+    fn default() -> Self {
+        Self {
+            weight: 0.0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl WeightedStats {
+    pub fn add(&mut self, value: f64, weight: f64) {
+        debug_assert!(weight >= 0.0);
+        self.weight += weight;
+        self.sum += value * weight;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Returns `None` if no weight has been added
+    pub fn mean(&self) -> Option<f64> {
+        if self.weight > 0.0 {
+            Some(self.sum / self.weight)
+        } else {
+            None
+        }
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min.is_finite().then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max.is_finite().then_some(self.max)
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weighted_mean() {
+        let mut stats = WeightedStats::default();
+        stats.add(1.0, 3.0);
+        stats.add(5.0, 1.0);
+
+        assert_eq!(Some(2.0), stats.mean());
+        assert_eq!(Some(1.0), stats.min());
+        assert_eq!(Some(5.0), stats.max());
+    }
+
+    #[test]
+    fn empty() {
+        let stats = WeightedStats::default();
+
+        assert_eq!(None, stats.mean());
+        assert_eq!(None, stats.min());
+        assert_eq!(None, stats.max());
+    }
+}
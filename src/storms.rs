@@ -0,0 +1,255 @@
+use crate::planet::Planet;
+use crate::planet_class::PlanetClass;
+use crate::weather::Weather;
+use crate::SeededRng;
+use physics_types::{Duration, Pressure, Temperature};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// The kinds of severe weather raised by `StormGenerator`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StormKind {
+    /// A hurricane or typhoon, fed by evaporation from warm tropical ocean
+    TropicalCyclone,
+    /// Wind lifting dust over a dry region
+    DustStorm,
+    /// Dust lifted high enough to shroud the whole planet for months, as on Mars
+    GlobalDustStorm,
+}
+
+/// A storm forming on a tile
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StormEvent {
+    pub kind: StormKind,
+    /// Where the storm formed, which a global dust storm spreads out from
+    pub tile: usize,
+    /// Time since the generator was created
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub start: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub duration: Duration,
+}
+
+/// Raises storms at random from the simulated climate,
+/// and sends each one to every subscriber as well as returning it from `step`
+#[derive(Debug)]
+pub struct StormGenerator {
+    rng: SeededRng,
+    elapsed: Duration,
+    subscribers: Vec<Sender<StormEvent>>,
+}
+
+impl StormGenerator {
+    /// Tropical cyclones per year per m² of warm ocean, about 85 a year on Earth
+    pub const CYCLONE_RATE: f64 = 5.7e-13;
+    /// Sea-level temperature needed to fuel a tropical cyclone
+    pub const CYCLONE_TEMPERATURE: Temperature = Temperature::in_c(26.5);
+    /// Surface pressure needed to carry a tropical cyclone
+    pub const CYCLONE_PRESSURE: Pressure = Pressure::in_pa(10_000.0);
+
+    /// Regional dust storms per year per m² of dry land in the prevailing winds, about Mars's
+    pub const DUST_STORM_RATE: f64 = 1.0e-12;
+    /// Annual precipitation in kg/m² below which land is dry enough to raise dust
+    pub const DRY: f64 = 250.0;
+
+    /// Global dust storms per year on a desert planet, about one every three Mars years
+    pub const GLOBAL_DUST_STORM_RATE: f64 = 0.18;
+    /// Surface pressure above which the air is too thick and damp for dust to shroud the planet
+    pub const GLOBAL_DUST_PRESSURE: Pressure = Pressure::in_pa(10_000.0);
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: crate::rng(seed),
+            elapsed: Duration::default(),
+            subscribers: vec![],
+        }
+    }
+
+    /// Receives every storm raised from now on.
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe(&mut self) -> Receiver<StormEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Raises storms for a step, with at most one forming on each tile.
+    /// Cyclones form over warm tropical ocean, and dust storms over land
+    /// that the planet's climate summary records as dry.
+    pub fn step(&mut self, planet: &Planet, dt: Duration) -> Vec<StormEvent> {
+        let descriptor = &planet.descriptor;
+        let state = &planet.state;
+        let years = dt / Duration::in_yr(1.0);
+
+        let radius = descriptor.radius.value;
+        let tile_area = 4.0 * PI * radius * radius / descriptor.tiles() as f64;
+        let pressure = state.atmosphere.pressure;
+        let summary = &state.summary;
+        let recorded = summary.duration() > Duration::default();
+
+        let mut events = vec![];
+
+        for (i, terrain) in state.terrain.iter().enumerate() {
+            let latitude = descriptor
+                .position(i)
+                .z
+                .clamp(-1.0, 1.0)
+                .asin()
+                .to_degrees();
+            let temperature = state.climate.temperature[i];
+            let open_ocean = (!terrain.glacier).min(terrain.ocean).f64();
+
+            let cyclones = if open_ocean > 0.5
+                && pressure >= Self::CYCLONE_PRESSURE
+                && (5.0..30.0).contains(&latitude.abs())
+            {
+                let warmth = (temperature - Self::CYCLONE_TEMPERATURE).value / 3.0;
+                Self::CYCLONE_RATE * tile_area * warmth.clamp(0.0, 2.0)
+            } else {
+                0.0
+            };
+
+            let dry = recorded && summary.annual_precipitation(i) < Self::DRY;
+            let dust_storms = if terrain.ocean.f64() < 0.5 && dry && pressure > PlanetClass::AIRLESS
+            {
+                let wind = match &state.hydrology.wind {
+                    Some(weather) => weather.wind[i].speed() / Weather::SPEED,
+                    None => 1.0,
+                };
+                Self::DUST_STORM_RATE * tile_area * wind
+            } else {
+                0.0
+            };
+
+            if self.occurs(cyclones * years) {
+                let days = self.rng.gen_range(3.0..14.0);
+                events.push(self.event(StormKind::TropicalCyclone, i, dt, days));
+            } else if self.occurs(dust_storms * years) {
+                let days = self.rng.gen_range(1.0..10.0);
+                events.push(self.event(StormKind::DustStorm, i, dt, days));
+            }
+        }
+
+        let desert = state.ocean_fraction() < PlanetClass::DESERT;
+        let thin = pressure > PlanetClass::AIRLESS && pressure < Self::GLOBAL_DUST_PRESSURE;
+        if desert && thin && self.occurs(Self::GLOBAL_DUST_STORM_RATE * years) {
+            let tile = self.rng.gen_range(0..state.terrain.len());
+            let days = self.rng.gen_range(30.0..90.0);
+            events.push(self.event(StormKind::GlobalDustStorm, tile, dt, days));
+        }
+
+        self.elapsed += dt;
+
+        // a failed send means the receiver was dropped
+        self.subscribers
+            .retain(|subscriber| events.iter().all(|event| subscriber.send(*event).is_ok()));
+
+        events
+    }
+
+    /// True with the probability of at least one event when `expected` are due
+    fn occurs(&mut self, expected: f64) -> bool {
+        expected > 0.0 && self.rng.gen_bool(1.0 - (-expected).exp())
+    }
+
+    fn event(&mut self, kind: StormKind, tile: usize, dt: Duration, days: f64) -> StormEvent {
+        StormEvent {
+            kind,
+            tile,
+            start: self.elapsed + dt * self.rng.gen_range(0.0..1.0),
+            duration: Duration::in_d(days),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::atmosphere::Atmosphere;
+    use crate::climate::ClimateConfig;
+    use crate::constants::EARTH_RADIUS;
+    use crate::planet::{Orbit, PlanetDescriptor};
+    use crate::terrain::Terrain;
+    use physics_types::Length;
+    use std::sync::Arc;
+
+    const N: usize = 48;
+
+    fn planet(atmosphere: Atmosphere, terrain: Terrain, celsius: f64) -> Planet {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(149.6e9),
+            eccentricity: 0.0,
+            period: Duration::in_yr(1.0),
+        };
+        let descriptor =
+            PlanetDescriptor::new(Length::in_m(EARTH_RADIUS), orbit, N, ClimateConfig::earth());
+        let mut state = descriptor.generate(atmosphere, Temperature::in_c(celsius), 0);
+        state.terrain = vec![terrain; N];
+        state
+            .summary
+            .record(&state.climate.temperature, &[0.0; N], Duration::in_yr(1.0));
+        Planet::new(Arc::new(descriptor), state)
+    }
+
+    fn storms(planet: &Planet, years: usize) -> Vec<StormEvent> {
+        let mut generator = StormGenerator::new(0);
+        (0..years)
+            .flat_map(|_| generator.step(planet, Duration::in_yr(1.0)))
+            .collect()
+    }
+
+    #[test]
+    fn cyclones_over_warm_tropical_ocean() {
+        let ocean = Terrain::new(255, 0, 0);
+        let warm = planet(Atmosphere::earth(), ocean, 29.0);
+        let cool = planet(Atmosphere::earth(), ocean, 20.0);
+
+        let events = storms(&warm, 10);
+        assert!(!events.is_empty());
+        for event in &events {
+            assert_eq!(StormKind::TropicalCyclone, event.kind);
+            let latitude = warm.descriptor.position(event.tile).z.asin().to_degrees();
+            assert!((5.0..30.0).contains(&latitude.abs()), "{}", latitude);
+        }
+
+        assert!(storms(&cool, 10).is_empty());
+    }
+
+    #[test]
+    fn global_dust_storms_on_desert_planets() {
+        let desert = Terrain::new(0, 0, 0);
+        let mars = planet(Atmosphere::mars(), desert, -60.0);
+        let earth = planet(Atmosphere::earth(), desert, -60.0);
+
+        let global = |events: Vec<StormEvent>| {
+            events
+                .iter()
+                .filter(|e| e.kind == StormKind::GlobalDustStorm)
+                .count()
+        };
+
+        assert!(global(storms(&mars, 100)) > 0);
+        assert_eq!(0, global(storms(&earth, 100)));
+        assert!(storms(&earth, 1)
+            .iter()
+            .all(|e| e.kind == StormKind::DustStorm));
+    }
+
+    #[test]
+    fn subscribers_receive_each_storm() {
+        let ocean = Terrain::new(255, 0, 0);
+        let warm = planet(Atmosphere::earth(), ocean, 29.0);
+        let mut generator = StormGenerator::new(0);
+        let receiver = generator.subscribe();
+        drop(generator.subscribe());
+
+        let events = generator.step(&warm, Duration::in_yr(1.0));
+
+        assert!(!events.is_empty());
+        assert_eq!(events, receiver.try_iter().collect::<Vec<_>>());
+        assert_eq!(1, generator.subscribers.len());
+    }
+}
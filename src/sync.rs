@@ -0,0 +1,147 @@
+use physics_types::Temperature;
+
+/// Delta compression of per-tile temperatures for keeping clients in step with a server.
+///
+/// Temperatures are quantized, and only tiles whose quantized value has changed since the last
+/// message are sent. A message is a bitmap of the changed tiles followed by their values
+/// as little-endian `u16`. The server and each client keep their own codec,
+/// and the first message after `new` carries every tile.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClimateCodec {
+    /// Kelvin per quantization step
+    resolution: f64,
+    /// The quantized temperatures as of the last message
+    reference: Vec<Option<u16>>,
+}
+
+impl ClimateCodec {
+    /// Default quantization step in kelvin, covering temperatures up to 3276 K
+    pub const RESOLUTION: f64 = 0.05;
+
+    pub fn new(tiles: usize) -> Self {
+        Self::with_resolution(tiles, Self::RESOLUTION)
+    }
+
+    pub fn with_resolution(tiles: usize, resolution: f64) -> Self {
+        assert!(
+            resolution > 0.0,
+            "resolution must be positive: {}",
+            resolution
+        );
+        Self {
+            resolution,
+            reference: vec![None; tiles],
+        }
+    }
+
+    fn quantize(&self, temperature: Temperature) -> u16 {
+        (temperature.value / self.resolution)
+            .round()
+            .clamp(0.0, u16::MAX as f64) as u16
+    }
+
+    fn bitmap_len(&self) -> usize {
+        (self.reference.len() + 7) / 8
+    }
+
+    /// Writes the tiles that changed since the last message, and remembers them for the next
+    pub fn encode(&mut self, temperature: &[Temperature]) -> Vec<u8> {
+        assert_eq!(self.reference.len(), temperature.len());
+
+        let mut message = vec![0u8; self.bitmap_len()];
+
+        for (i, temp) in temperature.iter().enumerate() {
+            let value = self.quantize(*temp);
+            if self.reference[i] != Some(value) {
+                self.reference[i] = Some(value);
+                message[i / 8] |= 1 << (i % 8);
+                message.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        message
+    }
+
+    /// Applies a message to the client's temperatures, returning `None` if it is malformed.
+    /// Tiles not in the message are left unchanged.
+    pub fn decode(&mut self, message: &[u8], temperature: &mut [Temperature]) -> Option<()> {
+        assert_eq!(self.reference.len(), temperature.len());
+
+        let bitmap_len = self.bitmap_len();
+        let (bitmap, mut values) = (message.get(..bitmap_len)?, message.get(bitmap_len..)?);
+
+        let changed = (0..self.reference.len()).filter(|i| bitmap[i / 8] & (1 << (i % 8)) != 0);
+        if values.len() != 2 * changed.clone().count() {
+            return None;
+        }
+
+        for i in changed {
+            let value = u16::from_le_bytes([values[0], values[1]]);
+            values = &values[2..];
+
+            self.reference[i] = Some(value);
+            temperature[i] = Temperature::in_k(value as f64 * self.resolution);
+        }
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const N: usize = 20;
+
+    #[test]
+    fn round_trip_within_resolution() {
+        let mut server = ClimateCodec::new(N);
+        let mut client = ClimateCodec::new(N);
+
+        let temperature = (0..N)
+            .map(|i| Temperature::in_c(i as f64 * 1.234))
+            .collect::<Vec<_>>();
+        let mut received = vec![Temperature::in_k(0.0); N];
+
+        let message = server.encode(&temperature);
+        assert_eq!(3 + 2 * N, message.len());
+        client.decode(&message, &mut received).unwrap();
+
+        for (sent, received) in temperature.iter().zip(&received) {
+            assert!((sent.value - received.value).abs() <= 0.5 * ClimateCodec::RESOLUTION);
+        }
+    }
+
+    #[test]
+    fn only_changed_tiles_are_sent() {
+        let mut server = ClimateCodec::new(N);
+        let mut client = ClimateCodec::new(N);
+        let mut temperature = vec![Temperature::in_c(15.0); N];
+        let mut received = vec![Temperature::in_k(0.0); N];
+
+        client
+            .decode(&server.encode(&temperature), &mut received)
+            .unwrap();
+        assert_eq!(3, server.encode(&temperature).len());
+
+        temperature[7] = Temperature::in_c(16.0);
+        let message = server.encode(&temperature);
+        assert_eq!(3 + 2, message.len());
+
+        client.decode(&message, &mut received).unwrap();
+        assert!((received[7].value - temperature[7].value).abs() < ClimateCodec::RESOLUTION);
+        assert!((received[6].value - temperature[6].value).abs() < ClimateCodec::RESOLUTION);
+    }
+
+    #[test]
+    fn malformed_messages() {
+        let mut server = ClimateCodec::new(N);
+        let mut client = ClimateCodec::new(N);
+        let mut received = vec![Temperature::in_k(0.0); N];
+        let message = server.encode(&[Temperature::in_c(15.0); N]);
+
+        assert_eq!(None, client.decode(&message[..2], &mut received));
+        assert_eq!(None, client.decode(&message[..9], &mut received));
+    }
+}
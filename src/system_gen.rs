@@ -0,0 +1,284 @@
+use crate::adjacency::TileDensity;
+use crate::atmosphere::Atmosphere;
+use crate::climate::ClimateConfig;
+use crate::constants::{EARTH_MASS, EARTH_RADIUS, G, SIGMA, SOLAR_LUMINOSITY, SOLAR_MASS};
+use crate::dynamics::{hill_radius, tidal_lock_timescale, Body};
+use crate::magnetosphere::{Core, Magnetosphere};
+use crate::moon::Primary;
+use crate::planet::{Orbit, Planet, PlanetDescriptor};
+use crate::radiation::DoseRate;
+use crate::satellites::{generate_satellites, Satellite};
+use crate::spin::Spin;
+use crate::star::Star;
+use crate::system_report::SystemReport;
+use crate::tile_gen::TileGenError;
+use physics_types::{Angle, Duration, Length, Mass, Temperature, AU};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// What a planet is mostly made of, decided by where it formed relative to the snow line
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Composition {
+    /// Rock and metal, formed inside the snow line
+    Rocky,
+    /// Water, ammonia, and methane ices under a thin envelope of gas, like Uranus and Neptune
+    IceGiant,
+    /// Mostly hydrogen and helium gathered around an icy core, like Jupiter and Saturn
+    GasGiant,
+}
+
+/// A planet in a generated [`StarSystem`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemBody {
+    pub orbit: Orbit,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub mass: Mass,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub radius: Length,
+    pub composition: Composition,
+    /// Innermost first, see `generate_satellites`
+    pub moons: Vec<Satellite>,
+    /// Tiles, terrain, and climate, generated only for rocky planets
+    pub surface: Option<Planet>,
+}
+
+/// A star and the planets that formed around it, innermost first
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StarSystem {
+    pub star: Star,
+    pub bodies: Vec<SystemBody>,
+}
+
+impl StarSystem {
+    pub const MAX_PLANETS: usize = 10;
+
+    /// Neighbouring planets are kept this many mutual Hill radii apart, close to the limit for stable orbits
+    pub const SPACING: f64 = 8.0;
+
+    /// Albedo assumed for the starting temperature of rocky planets
+    const ALBEDO: f64 = 0.3;
+
+    /// Places planets out to the edge of the disc in orbits spaced like the solar system's.
+    /// Rocky planets form inside the snow line, the first planet beyond it is a gas giant,
+    /// and the rest are gas or ice giants.
+    /// Each rocky planet is given terrain by `PlanetDescriptor::try_generate`
+    /// and an atmosphere by its place in the habitable zone.
+    ///
+    /// Returns an error if the terrain of a rocky planet can't be generated.
+    pub fn generate(star: Star, seed: u64) -> Result<Self, TileGenError> {
+        let rng = &mut crate::rng(seed);
+
+        let star_mass = star.mass.value / SOLAR_MASS;
+        let luminosity = star.luminosity().value / SOLAR_LUMINOSITY;
+        let snow_line = star.snow_line().value;
+        let disc_edge = 30.0 * star_mass * AU.value;
+
+        // in metres
+        let mut distance = 0.3 * luminosity.sqrt() * rng.gen_range(0.7..1.3) * AU.value;
+        let mut bodies = Vec::<SystemBody>::new();
+
+        while distance < disc_edge && bodies.len() < Self::MAX_PLANETS {
+            let giant = bodies
+                .iter()
+                .any(|b| b.composition == Composition::GasGiant);
+            let (composition, earths) = if distance < snow_line {
+                (Composition::Rocky, 10f64.powf(rng.gen_range(-1.3..0.5)))
+            } else if !giant || rng.gen_bool(0.4) {
+                (Composition::GasGiant, rng.gen_range(30.0..500.0))
+            } else {
+                (Composition::IceGiant, rng.gen_range(10.0..25.0))
+            };
+
+            let mass = Mass::in_kg(earths * EARTH_MASS);
+            let radius = Length::in_m(EARTH_RADIUS * radius_in_earths(composition, earths));
+            let orbit = Orbit {
+                semi_major_axis: Length::in_m(distance),
+                eccentricity: rng.gen_range(0.0..0.1),
+                period: orbital_period(Length::in_m(distance), star.mass),
+            };
+
+            let too_close = bodies.last().map_or(false, |inner| {
+                let inner_distance = inner.orbit.semi_major_axis.value;
+                let mean = Orbit {
+                    semi_major_axis: Length::in_m(0.5 * (distance + inner_distance)),
+                    eccentricity: 0.0,
+                    period: orbit.period,
+                };
+                let mutual = hill_radius(&mean, mass + inner.mass, star.mass).value;
+                distance - inner_distance < Self::SPACING * mutual
+            });
+
+            if too_close {
+                distance *= 1.2;
+                continue;
+            }
+
+            let primary = Primary {
+                mass,
+                radius,
+                belt_dose: DoseRate::default(),
+            };
+            let moons = generate_satellites(&primary, star.mass, &orbit, rng);
+
+            let surface = match composition {
+                Composition::Rocky => Some(rocky_planet(&star, orbit, mass, radius, rng)?),
+                Composition::IceGiant | Composition::GasGiant => None,
+            };
+
+            bodies.push(SystemBody {
+                orbit,
+                mass,
+                radius,
+                composition,
+                moons,
+                surface,
+            });
+
+            distance *= rng.gen_range(1.4..2.0);
+        }
+
+        Ok(Self { star, bodies })
+    }
+
+    /// The rocky planets with generated surfaces, innermost first
+    pub fn planets(&self) -> Vec<Planet> {
+        self.bodies
+            .iter()
+            .filter_map(|b| b.surface.clone())
+            .collect()
+    }
+
+    /// The system view of the rocky planets, see `SystemReport::new`
+    pub fn report(&self) -> SystemReport {
+        SystemReport::new(&self.planets())
+    }
+}
+
+/// Planet radius from its mass, after the mass-radius relations of Chen & Kipping (2017)
+fn radius_in_earths(composition: Composition, earths: f64) -> f64 {
+    match composition {
+        Composition::Rocky => earths.powf(0.28),
+        Composition::IceGiant => 3.9 * (earths / 15.0).powf(0.59),
+        // giants are held up by degeneracy pressure and barely grow with mass
+        Composition::GasGiant => 11.2 * (earths / 318.0).powf(-0.04),
+    }
+}
+
+fn orbital_period(semi_major_axis: Length, star_mass: Mass) -> Duration {
+    let a = semi_major_axis.value;
+    Duration::in_s(2.0 * PI * (a.powi(3) / (G * star_mass.value)).sqrt())
+}
+
+fn rocky_planet<R: Rng>(
+    star: &Star,
+    orbit: Orbit,
+    mass: Mass,
+    radius: Length,
+    rng: &mut R,
+) -> Result<Planet, TileGenError> {
+    let earths = mass.value / EARTH_MASS;
+    let distance = orbit.semi_major_axis;
+    let zone = star.habitable_zone();
+
+    let (atmosphere, water_fraction) = if earths < 0.1 {
+        // too small to hold onto an atmosphere
+        (Atmosphere::vacuum(), 0.0)
+    } else if distance < zone.runaway_greenhouse {
+        (Atmosphere::venus(), 0.0)
+    } else if zone.is_conservative(distance) {
+        (Atmosphere::earth(), rng.gen_range(0.3..0.8))
+    } else {
+        (Atmosphere::mars(), rng.gen_range(0.0..0.3))
+    };
+
+    let flux = star.luminosity().value / (4.0 * PI * distance.value * distance.value);
+    let temperature =
+        Temperature::in_k((flux * (1.0 - StarSystem::ALBEDO) / (4.0 * SIGMA)).powf(0.25));
+
+    let spin = Spin::new(
+        Duration::in_hr(rng.gen_range(10.0..40.0)),
+        Angle::in_deg(rng.gen_range(0.0..30.0)),
+    );
+    let body = Body { mass, radius };
+    let lock = tidal_lock_timescale(body, distance, star.mass, spin.period, 1e-3);
+    let spin = if lock < star.age {
+        Spin::tidally_locked(&orbit)
+    } else {
+        spin
+    };
+
+    let core = if earths > 0.5 {
+        Core::Convecting
+    } else {
+        Core::Solid
+    };
+    let magnetosphere = Magnetosphere::new(mass, spin.period, core);
+
+    let tiles = TileDensity::default()
+        .tile_count(radius)
+        .max(Satellite::MIN_TILES);
+    let climate = ClimateConfig::earth().with_atmosphere(&atmosphere, temperature);
+    let descriptor = PlanetDescriptor::new(radius, orbit, tiles, climate)
+        .with_mass(mass)
+        .with_spin(spin)
+        .with_water_fraction(water_fraction)
+        .with_magnetic_field(magnetosphere.is_protective());
+
+    let state = descriptor.try_generate(atmosphere, temperature, rng.gen())?;
+    Ok(Planet::new(Arc::new(descriptor), state))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rocky_planets_inside_the_snow_line() {
+        let sun = Star::sun();
+
+        for seed in 0..4 {
+            let system = StarSystem::generate(sun, seed).unwrap();
+            assert!(!system.bodies.is_empty());
+
+            for pair in system.bodies.windows(2) {
+                assert!(pair[0].orbit.semi_major_axis < pair[1].orbit.semi_major_axis);
+            }
+
+            for body in &system.bodies {
+                let inside = body.orbit.semi_major_axis < sun.snow_line();
+                assert_eq!(inside, body.composition == Composition::Rocky);
+
+                match &body.surface {
+                    Some(planet) => {
+                        assert_eq!(Composition::Rocky, body.composition);
+                        assert_eq!(body.mass, planet.descriptor.mass);
+                        assert_eq!(planet.descriptor.tiles(), planet.state.terrain.len());
+                    }
+                    None => assert_ne!(Composition::Rocky, body.composition),
+                }
+            }
+
+            let rocky = system.planets().len();
+            assert_eq!(rocky, system.report().planets.len());
+        }
+    }
+
+    #[test]
+    fn same_seed_same_system() {
+        let orbits = |seed| {
+            StarSystem::generate(Star::sun(), seed)
+                .unwrap()
+                .bodies
+                .iter()
+                .map(|b| (b.orbit, b.mass, b.composition, b.moons.len()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(orbits(3), orbits(3));
+        assert_ne!(orbits(3), orbits(4));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::colony_cost::{evaluate_system, ColonyCost, Shielding};
+use crate::planet::Planet;
+use crate::planet_facts::{PlanetFacts, ResourceFlags};
+use crate::regime::{ClimateRegime, RegimeThresholds};
+use std::cmp::Ordering;
+
+/// A planet's entry in a [`SystemReport`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanetReport {
+    pub facts: PlanetFacts,
+    pub regime: ClimateRegime,
+    /// The tiles with the lowest colony cost including terrain, cheapest first
+    pub best_sites: Vec<(usize, ColonyCost)>,
+}
+
+impl PlanetReport {
+    pub fn is_habitable(&self) -> bool {
+        self.facts.breathable && self.facts.has_liquid_water
+    }
+
+    pub fn best_site(&self) -> Option<(usize, ColonyCost)> {
+        self.best_sites.first().copied()
+    }
+}
+
+/// A summary of every planet in a star system for the system view,
+/// built from each planet's recorded climate without running any models
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemReport {
+    /// In the same order as the planets
+    pub planets: Vec<PlanetReport>,
+    /// Resources found anywhere in the system
+    pub resource_flags: ResourceFlags,
+}
+
+impl SystemReport {
+    /// Colony sites listed for each planet
+    pub const SITES: usize = 3;
+
+    pub fn new(planets: &[Planet]) -> Self {
+        let thresholds = RegimeThresholds::default();
+        let mut resource_flags = ResourceFlags::default();
+
+        let planets = planets
+            .iter()
+            .zip(evaluate_system(planets))
+            .map(|(planet, costs)| {
+                let state = &planet.state;
+                let shielding =
+                    Shielding::new(planet.descriptor.magnetic_field, state.atmosphere.pressure);
+                let facts =
+                    PlanetFacts::new(&state.terrain, &state.summary, &state.atmosphere, shielding);
+                resource_flags |= facts.resource_flags;
+
+                let mut sites = costs
+                    .into_iter()
+                    .zip(&state.terrain)
+                    .map(|(cost, terrain)| cost.with_terrain(terrain))
+                    .enumerate()
+                    .collect::<Vec<_>>();
+                sites.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+                sites.truncate(Self::SITES);
+
+                PlanetReport {
+                    facts,
+                    regime: thresholds.classify(ClimateRegime::Temperate, facts.mean_temp),
+                    best_sites: sites,
+                }
+            })
+            .collect();
+
+        Self {
+            planets,
+            resource_flags,
+        }
+    }
+
+    /// The planet with the cheapest colony site
+    pub fn best_planet(&self) -> Option<usize> {
+        self.planets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, planet)| Some((i, planet.best_site()?.1)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .map(|(i, _)| i)
+    }
+
+    pub fn habitable_planets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.planets
+            .iter()
+            .enumerate()
+            .filter(|(_, planet)| planet.is_habitable())
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::atmosphere::Atmosphere;
+    use crate::climate::ClimateConfig;
+    use crate::planet::{Orbit, PlanetDescriptor};
+    use physics_types::{Duration, FluxDensity, Length, Temperature};
+    use std::sync::Arc;
+
+    fn planet(climate: ClimateConfig, atmosphere: Atmosphere, water: f64) -> Planet {
+        let orbit = Orbit {
+            semi_major_axis: Length::in_m(149.6e9),
+            eccentricity: 0.0,
+            period: Duration::in_yr(1.0),
+        };
+        let descriptor = PlanetDescriptor::new(Length::in_m(6e6), orbit, 16, climate)
+            .with_water_fraction(water)
+            .with_magnetic_field(true);
+        let state = descriptor.generate(atmosphere, Temperature::in_c(15.0), 1);
+        let mut planet = Planet::new(Arc::new(descriptor), state);
+
+        let intensity = (0..16).map(|i| i as f64 / 16.0).collect::<Vec<_>>();
+        for _ in 0..10 {
+            let flux = FluxDensity::in_w_per_m2(1361.0);
+            planet.step(flux, &intensity, Duration::in_hr(1.0));
+        }
+        planet
+    }
+
+    #[test]
+    fn earth_is_the_best_planet() {
+        let planets = [
+            planet(ClimateConfig::mars(), Atmosphere::mars(), 0.0),
+            planet(ClimateConfig::earth(), Atmosphere::earth(), 0.7),
+        ];
+        let report = SystemReport::new(&planets);
+
+        assert_eq!(2, report.planets.len());
+        assert_eq!(Some(1), report.best_planet());
+        assert_eq!(vec![1], report.habitable_planets().collect::<Vec<_>>());
+        assert!(report.resource_flags.contains(ResourceFlags::WATER));
+    }
+
+    #[test]
+    fn sites_are_cheapest_first() {
+        let report = SystemReport::new(&[planet(ClimateConfig::earth(), Atmosphere::earth(), 0.7)]);
+        let sites = &report.planets[0].best_sites;
+
+        assert_eq!(SystemReport::SITES, sites.len());
+        assert!(sites.windows(2).all(|w| w[0].1 <= w[1].1));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::adjacency::units::{Distance3, Position3};
+use crate::adjacency::{get_tile_spacing, AdjArray};
+use crate::terrain::{Elevation, Terrain};
+use physics_types::{Duration, Length};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// A rigid plate rotating about an axis through the planet's centre
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plate {
+    /// Unit vector of the rotation axis in the planet's frame
+    pub pole: [f64; 3],
+    /// Angular drift rate in radians per year
+    pub rate: f64,
+}
+
+impl Plate {
+    /// Drift speeds of Earth's plates are 1 to 10 cm/yr
+    pub fn random<R: Rng>(radius: Length, rng: &mut R) -> Self {
+        let z = rng.gen_range(-1.0..1.0f64);
+        let theta = rng.gen_range(0.0..2.0 * PI);
+        let r = (1.0 - z * z).sqrt();
+        let speed = rng.gen_range(0.01..0.1);
+
+        Self {
+            pole: [r * theta.cos(), r * theta.sin(), z],
+            rate: speed / radius.value,
+        }
+    }
+
+    /// Velocity in m/yr of the surface at the given unit position
+    fn velocity(&self, p: Position3, radius: f64) -> [f64; 3] {
+        let [x, y, z] = self.pole;
+        let scale = self.rate * radius;
+        [
+            scale * (y * p.z - z * p.y),
+            scale * (z * p.x - x * p.z),
+            scale * (x * p.y - y * p.x),
+        ]
+    }
+}
+
+/// Assignment of tiles to drifting plates, which reshape the terrain along their boundaries
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tectonics {
+    pub plates: Vec<Plate>,
+    /// Index into `plates` for each tile
+    pub plate: Vec<u8>,
+    /// The centre of each tile, see `Adjacency::positions`
+    positions: Vec<Position3>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    radius: Length,
+    /// Drift of the neighbouring plate into each tile, as a fraction of the tile spacing
+    progress: Vec<f64>,
+}
+
+impl Tectonics {
+    /// Metres of uplift or subsidence per metre of convergence or divergence
+    pub const UPLIFT: f64 = 0.05;
+    /// Highest elevation that uplift can build, in metres
+    pub const MAX_ELEVATION: f64 = 9000.0;
+    /// Deepest trench that subsidence can carve, in metres
+    pub const MIN_ELEVATION: f64 = -11000.0;
+
+    pub fn new(
+        plates: Vec<Plate>,
+        plate: Vec<u8>,
+        positions: Vec<Position3>,
+        radius: Length,
+    ) -> Self {
+        assert!(plate.iter().all(|p| (*p as usize) < plates.len()));
+        assert_eq!(plate.len(), positions.len());
+
+        Self {
+            progress: vec![0.0; plate.len()],
+            plates,
+            plate,
+            positions,
+            radius,
+        }
+    }
+
+    /// Divides the tiles into randomly grown plates with random drift
+    pub fn generate<R: Rng>(
+        adjacency: &[AdjArray],
+        positions: &[Position3],
+        plates: usize,
+        radius: Length,
+        rng: &mut R,
+    ) -> Self {
+        let nodes = adjacency.len();
+        assert!(plates > 0 && plates <= nodes && plates <= u8::MAX as usize);
+
+        let mut plate = vec![None; nodes];
+        let mut frontier = vec![];
+
+        for p in 0..plates {
+            let tile = loop {
+                let tile = rng.gen_range(0..nodes);
+                if plate[tile].is_none() {
+                    break tile;
+                }
+            };
+            plate[tile] = Some(p as u8);
+            frontier.push(tile);
+        }
+
+        while !frontier.is_empty() {
+            let i = rng.gen_range(0..frontier.len());
+            let tile = frontier[i];
+
+            let open = adjacency[tile].iter().filter(|n| plate[*n].is_none());
+            match open.min_by_key(|_| rng.gen::<u32>()) {
+                Some(n) => {
+                    plate[n] = plate[tile];
+                    frontier.push(n);
+                }
+                None => {
+                    frontier.swap_remove(i);
+                }
+            }
+        }
+
+        let plates = (0..plates).map(|_| Plate::random(radius, rng)).collect();
+        let plate = plate.into_iter().map(Option::unwrap).collect();
+
+        Self::new(plates, plate, positions.to_vec(), radius)
+    }
+
+    /// Drifts the plates, raising mountains where they converge and opening oceans where they part.
+    /// Ocean tiles at a convergent boundary are subducted into trenches.
+    pub fn step(&mut self, terrain: &mut [Terrain], adjacency: &[AdjArray], dt: Duration) {
+        let nodes = self.plate.len();
+        let radius = self.radius.value;
+        let spacing = get_tile_spacing(self.radius, nodes).value;
+        let years = dt / Duration::in_yr(1.0);
+
+        let position = |i: usize| self.positions[i];
+
+        let changes = (0..nodes)
+            .map(|i| {
+                let p_i = position(i);
+                let plate_i = &self.plates[self.plate[i] as usize];
+
+                let mut convergence = 0.0;
+                let mut advance = 0.0;
+                let mut invader = None;
+
+                for n in adjacency[i].iter() {
+                    if self.plate[n] == self.plate[i] {
+                        continue;
+                    }
+
+                    let p_n = position(n);
+                    let plate_n = &self.plates[self.plate[n] as usize];
+                    let d = unit(p_n - p_i);
+
+                    let v_i = plate_i.velocity(p_i, radius);
+                    let v_n = plate_n.velocity(p_n, radius);
+
+                    // positive when the plates approach each other
+                    convergence += (dot(v_i, d) - dot(v_n, d)) * years;
+
+                    let push = -dot(v_n, d) * years;
+                    if push > advance {
+                        advance = push;
+                        invader = Some(self.plate[n]);
+                    }
+                }
+
+                (convergence, advance, invader)
+            })
+            .collect::<Vec<_>>();
+
+        for (i, (convergence, advance, invader)) in changes.into_iter().enumerate() {
+            if let Some(invader) = invader {
+                self.progress[i] += advance / spacing;
+                if self.progress[i] >= 1.0 {
+                    self.plate[i] = invader;
+                    self.progress[i] = 0.0;
+                }
+            }
+
+            let tile = &mut terrain[i];
+            let fraction = (convergence.abs() / spacing).min(1.0);
+
+            if convergence > 0.0 && tile.ocean.f64() < 0.5 {
+                *tile = uplift(*tile, convergence * Self::UPLIFT, fraction);
+            } else if convergence != 0.0 {
+                *tile = subside(*tile, convergence.abs() * Self::UPLIFT, fraction);
+            }
+        }
+    }
+}
+
+fn uplift(tile: Terrain, height: f64, fraction: f64) -> Terrain {
+    let ocean = tile.ocean.u8();
+    let land = 255 - ocean;
+    let added = (fraction * land as f64).round() as u8;
+    let mountains = tile.mountains.u8().saturating_add(added).min(land);
+
+    let elevation = (tile.elevation.m() + height).min(Tectonics::MAX_ELEVATION);
+    Terrain::new(ocean, mountains, tile.glacier.u8()).with_elevation(Elevation::in_m(elevation))
+}
+
+fn subside(tile: Terrain, depth: f64, fraction: f64) -> Terrain {
+    let added = (fraction * 255.0).round() as u8;
+    let ocean = tile.ocean.u8().saturating_add(added);
+    let mountains = tile.mountains.u8().min(255 - ocean);
+
+    let elevation = (tile.elevation.m() - depth).max(Tectonics::MIN_ELEVATION);
+    Terrain::new(ocean, mountains, tile.glacier.u8()).with_elevation(Elevation::in_m(elevation))
+}
+
+fn unit(d: Distance3) -> [f64; 3] {
+    let length = (d.x * d.x + d.y * d.y + d.z * d.z).sqrt();
+    [d.x / length, d.y / length, d.z / length]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::constants::EARTH_RADIUS;
+
+    const N: usize = 96;
+
+    fn adjacency() -> Adjacency {
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        adj
+    }
+
+    fn radius() -> Length {
+        Length::in_m(EARTH_RADIUS)
+    }
+
+    /// Northern and southern plates rotating in opposite directions about the x axis
+    fn hemispheres(rate: f64) -> Tectonics {
+        let positions = adjacency().positions(N).to_vec();
+        let plate = positions.iter().map(|p| (p.z < 0.0) as u8).collect();
+
+        let plates = vec![
+            Plate {
+                pole: [1.0, 0.0, 0.0],
+                rate: -rate,
+            },
+            Plate {
+                pole: [1.0, 0.0, 0.0],
+                rate,
+            },
+        ];
+
+        Tectonics::new(plates, plate, positions, radius())
+    }
+
+    #[test]
+    fn generate_assigns_every_tile() {
+        let adj = adjacency();
+        let rng = &mut crate::rng(0);
+        let tectonics = Tectonics::generate(adj.get(N), adj.positions(N), 6, radius(), rng);
+
+        assert_eq!(N, tectonics.plate.len());
+        for p in 0..6 {
+            assert!(tectonics.plate.contains(&p));
+        }
+    }
+
+    /// On the +y side the plates converge, on the -y side they diverge
+    #[test]
+    fn boundaries_raise_mountains_and_open_oceans() {
+        let adj = adjacency();
+        let mut tectonics = hemispheres(1e-8);
+        let mut terrain = vec![Terrain::new(0, 0, 0); N];
+
+        tectonics.step(&mut terrain, adj.get(N), Duration::in_yr(1e6));
+
+        let positions = adj.positions(N);
+        let side = |sign: f64| {
+            terrain
+                .iter()
+                .zip(positions)
+                .filter(move |(_, p)| p.y * sign > 0.3)
+                .map(|(t, _)| t)
+        };
+
+        assert!(side(1.0).all(|t| t.elevation.m() >= 0.0));
+        assert!(side(1.0).any(|t| t.mountains.u8() > 0));
+        assert!(side(-1.0).all(|t| t.elevation.m() <= 0.0));
+        assert!(side(-1.0).any(|t| t.ocean.u8() > 0));
+    }
+
+    #[test]
+    fn plates_drift_across_tiles() {
+        let adj = adjacency();
+        let rng = &mut crate::rng(1);
+        let mut tectonics = Tectonics::generate(adj.get(N), adj.positions(N), 6, radius(), rng);
+        let mut terrain = vec![Terrain::new(128, 0, 0); N];
+        let before = tectonics.plate.clone();
+
+        for _ in 0..100 {
+            tectonics.step(&mut terrain, adj.get(N), Duration::in_yr(1e6));
+        }
+
+        assert_ne!(before, tectonics.plate);
+    }
+}
@@ -1,18 +1,30 @@
+use crate::adjacency::AdjArray;
 use crate::solar_radiation::RadiativeAbsorption;
 use fractional_int::FractionalU8;
+use physics_types::{EnergyPerTemperature, Length, Temperature, J, K};
 use std::ops::Sub;
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Terrain {
     /// The fraction covered by ocean, counted from the 'left'
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
     pub ocean: FractionalU8,
     /// The fraction covered by ocean, counted from the 'right'
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
     pub mountains: FractionalU8,
     /// The fraction covered by plains, counted oceans on the 'left' and mountains on the 'right'
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
     pub plains: FractionalU8,
     /// The fraction covered by glacier, counted from the 'right'
     /// Mountains will be covered before plains, which are covered before oceans.
+    /// Glacier beyond the land fraction is sea ice, see `Terrain::land_ice` and `Terrain::sea_ice`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
     pub glacier: FractionalU8,
+    pub elevation: Elevation,
+    /// The fraction of the tile's land drained by rivers
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::fractional_u8"))]
+    pub river: FractionalU8,
 }
 
 impl Terrain {
@@ -22,10 +34,14 @@ impl Terrain {
     ///
     /// * `ocean`: the fraction of the tile covered by water
     /// * `mountain`: the fraction of the land covered by mountains
-    /// * `glacier`: the fraction of the tile covered by glacier.
+    /// * `glacier`: the fraction of the tile covered by glacier, any beyond the land being sea ice.
     ///
     /// returns: Terrain
     ///
+    /// # Panics
+    ///
+    /// In debug builds, if any fraction is outside 0 to 1
+    ///
     /// # Examples
     ///
     /// ```
@@ -35,6 +51,16 @@ impl Terrain {
     /// let arctic = Terrain::new_fraction(0.8, 0.5, 0.8);
     /// ```
     pub fn new_fraction(ocean: f64, mountains: f64, glacier: f64) -> Self {
+        debug_assert!(
+            [ocean, mountains, glacier]
+                .iter()
+                .all(|f| (0.0..=1.0).contains(f)),
+            "fractions must be between 0 and 1: ocean {}, mountains {}, glacier {}",
+            ocean,
+            mountains,
+            glacier
+        );
+
         let ocean = FractionalU8::new_f64(ocean);
         let land = ocean.inverse();
 
@@ -43,14 +69,17 @@ impl Terrain {
 
         let glacier = FractionalU8::new_f64(glacier);
 
-        debug_assert_eq!(u8::MAX, ocean.u8() + plains.u8() + mountains.u8());
-
-        Self {
+        let terrain = Self {
             ocean,
             plains,
             mountains,
             glacier,
-        }
+            elevation: Elevation::default(),
+            river: FractionalU8::default(),
+        };
+
+        debug_assert!(terrain.is_valid(), "{:?}", terrain);
+        terrain
     }
 
     #[inline]
@@ -60,12 +89,78 @@ impl Terrain {
             .checked_sub(ocean)
             .expect("mountains + ocean > 255");
 
-        Self {
+        let terrain = Self {
             ocean: FractionalU8::new(ocean),
             mountains: FractionalU8::new(mountains),
             plains: FractionalU8::new(plains),
             glacier: FractionalU8::new(glacier),
-        }
+            elevation: Elevation::default(),
+            river: FractionalU8::default(),
+        };
+
+        debug_assert!(terrain.is_valid(), "{:?}", terrain);
+        terrain
+    }
+
+    #[inline]
+    pub fn with_elevation(mut self, elevation: Elevation) -> Self {
+        self.elevation = elevation;
+        self
+    }
+
+    /// True if the ocean, plains, and mountains exactly cover the tile,
+    /// and any glacier beyond the land is sea ice on the ocean.
+    /// Code that modifies these fields directly should preserve this.
+    pub fn is_valid(&self) -> bool {
+        let cover = self.ocean.u8() as u16 + self.plains.u8() as u16 + self.mountains.u8() as u16;
+        cover == u8::MAX as u16 && self.sea_ice().u8() <= self.ocean.u8()
+    }
+
+    /// The glacier resting on mountains and plains
+    pub fn land_ice(&self) -> FractionalU8 {
+        let land = u8::MAX - self.ocean.u8();
+        FractionalU8::new(self.glacier.u8().min(land))
+    }
+
+    /// The glacier floating on the ocean, which melts in place without running off
+    pub fn sea_ice(&self) -> FractionalU8 {
+        FractionalU8::new(self.glacier.u8() - self.land_ice().u8())
+    }
+
+    pub fn has_river(&self) -> bool {
+        self.river.u8() > 0
+    }
+
+    /// Multiplier on the cost of building a colony, 1 for open plains.
+    /// Building at sea, on glaciers, or in mountains costs more.
+    pub fn colony_modifier(&self) -> f64 {
+        let ice = self.glacier.f64();
+        let ocean = self.ocean.f64();
+        let mountains = self.mountains.f64();
+
+        1.0 + 2.0 * ocean + 0.5 * mountains + 1.5 * ice
+    }
+
+    /// Heat capacity of land in J/K/m²
+    pub const LAND_HEAT_CAPACITY: f64 = 1.0e6;
+    /// Heat capacity of the ocean's mixed layer in J/K/m²
+    pub const OCEAN_HEAT_CAPACITY: f64 = 1.0e7;
+
+    /// Heat capacity of one square metre of the tile's surface
+    pub fn heat_capacity(&self) -> EnergyPerTemperature {
+        let ocean = self.ocean.f64();
+        let land = 1.0 - ocean;
+        (land * Self::LAND_HEAT_CAPACITY + ocean * Self::OCEAN_HEAT_CAPACITY) * J / K
+    }
+
+    /// Dry adiabatic lapse rate in K/m
+    pub const LAPSE_RATE: f64 = 9.8e-3;
+
+    /// The air temperature at the surface of the tile given the temperature at sea level.
+    /// Submerged tiles are at sea level.
+    pub fn surface_temperature(&self, sea_level: Temperature) -> Temperature {
+        let height = self.elevation.m().max(0.0);
+        sea_level - Temperature::in_k(Self::LAPSE_RATE * height)
     }
 
     pub fn absorption(
@@ -80,18 +175,94 @@ impl Terrain {
         let ocean = RadiativeAbsorption::WATER * iceless_ocean;
         let land = ground * iceless_ground;
 
-        let surface = glacier.add(ocean).add(land) * !clouds;
+        let surface = (glacier + ocean + land) * !clouds;
         let clouds = RadiativeAbsorption::CLOUD * clouds;
 
         surface.add(clouds)
     }
 }
 
+/// Mean elevation of a tile relative to sea level, stored in whole metres.
+/// Submerged tiles have a negative elevation, the depth of the sea floor.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Elevation(i16);
+
+impl Elevation {
+    pub fn in_m(metres: f64) -> Self {
+        let metres = metres.round().clamp(i16::MIN as f64, i16::MAX as f64);
+        Self(metres as i16)
+    }
+
+    pub fn m(self) -> f64 {
+        self.0 as f64
+    }
+
+    pub fn length(self) -> Length {
+        Length::in_m(self.m())
+    }
+}
+
+/// Rise over run from tile `a` to its neighbour `b`, positive when `b` is higher
+pub fn slope(terrain: &[Terrain], a: usize, b: usize, spacing: Length) -> f64 {
+    (terrain[b].elevation.m() - terrain[a].elevation.m()) / spacing.value
+}
+
+/// The slope to each neighbour of each tile, in the same order as `adjacency`
+pub fn slopes(
+    terrain: &[Terrain],
+    adjacency: &[AdjArray],
+    spacing: Length,
+) -> Vec<[f64; AdjArray::CAPACITY]> {
+    adjacency
+        .iter()
+        .enumerate()
+        .map(|(i, adj)| {
+            let mut slopes = [0.0; AdjArray::CAPACITY];
+            for (slope, n) in slopes.iter_mut().zip(adj.iter()) {
+                *slope = self::slope(terrain, i, n, spacing);
+            }
+            slopes
+        })
+        .collect()
+}
+
+/// Terrain ruggedness index: the root of the summed squared elevation differences
+/// between each tile and its neighbours, in metres
+pub fn ruggedness(terrain: &[Terrain], adjacency: &[AdjArray]) -> Vec<f64> {
+    adjacency
+        .iter()
+        .enumerate()
+        .map(|(i, adj)| {
+            adj.iter()
+                .map(|n| terrain[n].elevation.m() - terrain[i].elevation.m())
+                .map(|d| d * d)
+                .sum::<f64>()
+                .sqrt()
+        })
+        .collect()
+}
+
+/// The neighbour down the steepest slope from a tile, if any are lower
+pub fn downhill(terrain: &[Terrain], adjacency: &[AdjArray], tile: usize) -> Option<usize> {
+    adjacency[tile]
+        .iter()
+        .filter(|n| terrain[*n].elevation < terrain[tile].elevation)
+        .min_by_key(|n| terrain[*n].elevation)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::solar_radiation::Albedo;
 
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn glacier_beyond_the_tile_is_invalid() {
+        Terrain::new_fraction(0.5, 0.5, 1.5);
+    }
+
     #[test]
     fn new_fraction_fuzz() {
         use rand::{thread_rng, Rng};
@@ -126,4 +297,114 @@ mod test {
         assert!(absorption < max, "{:.2} < {:.2}", absorption.0, max.0);
         assert!(absorption > min, "{:.2} > {:.2}", absorption.0, min.0);
     }
+
+    #[test]
+    fn land_and_sea_ice() {
+        let coast = Terrain::new(100, 55, 200);
+
+        assert!(coast.is_valid());
+        assert_eq!(155, coast.land_ice().u8());
+        assert_eq!(45, coast.sea_ice().u8());
+
+        let mut invalid = coast;
+        invalid.plains = FractionalU8::new(0);
+        assert!(!invalid.is_valid());
+    }
+
+    #[test]
+    fn colony_modifier() {
+        let plains = Terrain::new(0, 0, 0);
+        let highlands = Terrain::new(0, 200, 0);
+        let atoll = Terrain::new_fraction(0.97, 0.0, 0.0);
+        let ice_sheet = Terrain::new(0, 0, 255);
+
+        assert_eq!(1.0, plains.colony_modifier());
+        assert!(highlands.colony_modifier() > plains.colony_modifier());
+        assert!(atoll.colony_modifier() > highlands.colony_modifier());
+        assert!(ice_sheet.colony_modifier() > highlands.colony_modifier());
+    }
+
+    #[test]
+    fn ocean_heat_capacity() {
+        let ocean = Terrain::new(255, 0, 0);
+        let coast = Terrain::new(128, 50, 0);
+        let land = Terrain::new(0, 50, 0);
+
+        assert!(ocean.heat_capacity() > coast.heat_capacity());
+        assert!(coast.heat_capacity() > land.heat_capacity());
+    }
+
+    fn ridge() -> (Vec<Terrain>, Vec<AdjArray>) {
+        use std::iter::FromIterator;
+
+        let terrain = [0.0, 500.0, 2000.0, 500.0]
+            .iter()
+            .map(|m| Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(*m)))
+            .collect();
+        let adjacency = vec![
+            AdjArray::from_iter(vec![1]),
+            AdjArray::from_iter(vec![0, 2]),
+            AdjArray::from_iter(vec![1, 3]),
+            AdjArray::from_iter(vec![2]),
+        ];
+
+        (terrain, adjacency)
+    }
+
+    #[test]
+    fn slope_between_neighbours() {
+        let (terrain, adjacency) = ridge();
+        let spacing = Length::in_m(1000.0);
+
+        assert_eq!(0.5, slope(&terrain, 0, 1, spacing));
+        assert_eq!(-1.5, slope(&terrain, 2, 3, spacing));
+
+        let slopes = slopes(&terrain, &adjacency, spacing);
+        assert_eq!([-0.5, 1.5], slopes[1][..2]);
+    }
+
+    #[test]
+    fn ruggedness_and_downhill() {
+        let (mut terrain, adjacency) = ridge();
+        let ruggedness = ruggedness(&terrain, &adjacency);
+
+        assert_eq!(500.0, ruggedness[0]);
+        assert!(ruggedness[2] > ruggedness[1]);
+
+        // break the tie between the two slopes below the peak
+        terrain[3] = terrain[3].with_elevation(Elevation::in_m(300.0));
+        assert_eq!(Some(0), downhill(&terrain, &adjacency, 1));
+        assert_eq!(Some(3), downhill(&terrain, &adjacency, 2));
+        assert_eq!(None, downhill(&terrain, &adjacency, 0));
+    }
+
+    #[test]
+    fn elevation_saturates() {
+        assert_eq!(Elevation(i16::MAX), Elevation::in_m(1e9));
+        assert_eq!(Elevation(i16::MIN), Elevation::in_m(-1e9));
+    }
+
+    #[test]
+    fn mountains_are_colder() {
+        let sea_level = Temperature::in_c(15.0);
+        let lowland = Terrain::new_fraction(0.0, 0.1, 0.0);
+        let highland = lowland.with_elevation(Elevation::in_m(2000.0));
+        let seabed = lowland.with_elevation(Elevation::in_m(-2000.0));
+
+        assert_eq!(sea_level, lowland.surface_temperature(sea_level));
+        assert_eq!(sea_level, seabed.surface_temperature(sea_level));
+        assert!(highland.surface_temperature(sea_level) < sea_level);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let terrain =
+            Terrain::new_fraction(0.7, 0.24, 0.03).with_elevation(Elevation::in_m(-120.0));
+
+        let json = serde_json::to_string(&terrain).unwrap();
+        let actual = serde_json::from_str::<Terrain>(&json).unwrap();
+
+        assert_eq!(terrain, actual);
+    }
 }
@@ -1,27 +1,281 @@
-use crate::adjacency::{get_tile_count, AdjArray, Adjacency};
-use crate::terrain::Terrain;
-use physics_types::Length;
+use crate::adjacency::{get_tile_count, AdjArray, Adjacency, TileDensity};
+use crate::resources::{generate_resources, Deposits};
+use crate::terrain::{downhill, Elevation, Terrain};
+use fractional_int::FractionalU8;
+use physics_types::{Angle, Duration, Length, Pressure};
 use rand::distributions::Bernoulli;
 use rand::prelude::{Distribution, Rng, SliceRandom};
-use std::collections::HashSet;
-use std::ops::AddAssign;
+use std::fmt::{Display, Formatter};
+use std::ops::{AddAssign, Range, RangeInclusive};
 
-#[derive(Debug, Default, Copy, Clone)]
+/// Parameters for generating terrain, see `TileGen::new` for the defaults.
+/// Fewer continents make supercontinents, and a higher island chance makes archipelagos.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileGen {
     pub water_fraction: f64,
+    /// Scales the mountain cover of land tiles, 1 for Earth-like ranges
+    pub mountains: f64,
+    /// Chance of an island on an ocean tile along the coast, halved on the open ocean
+    pub island_chance: f64,
+    /// Latitude beyond which tiles start covered by glacier
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub glacier_latitude: Angle,
+    /// Number of continental plates, drawn from the range
+    pub continents: Range<usize>,
+    /// Scales the elevation of land tiles
+    pub roughness: f64,
+    /// Tiles for the radius passed to `generate`
+    pub density: TileDensity,
+    /// Impact basins for airless or thinly covered worlds, applied once the terrain is eroded
+    pub cratering: Option<Cratering>,
+}
+
+impl Default for TileGen {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
 }
 
 impl TileGen {
+    /// Earth-like continents and islands, with no glacier
+    pub fn new(water_fraction: f64) -> Self {
+        Self {
+            water_fraction,
+            mountains: 1.0,
+            island_chance: 0.4,
+            glacier_latitude: Angle::in_deg(90.0),
+            continents: 10..14,
+            roughness: 1.0,
+            density: TileDensity::default(),
+            cratering: None,
+        }
+    }
+
     pub fn generate<R: Rng>(
         &self,
         radius: Length,
         adjacency: &Adjacency,
         rng: &mut R,
-    ) -> Vec<Terrain> {
-        generate_terrain_from_radius(radius, self.water_fraction, adjacency, rng)
+    ) -> Result<Vec<Terrain>, TileGenError> {
+        let mut terrain = vec![Terrain::default(); self.density.tile_count(radius)];
+        let scratch = &mut TileGenScratch::default();
+        self.generate_into(&mut terrain, adjacency, scratch, rng)?;
+        Ok(terrain)
+    }
+
+    /// Generates terrain using `crate::rng`,
+    /// so the same seed always yields the same terrain
+    pub fn generate_seeded(
+        &self,
+        radius: Length,
+        adjacency: &Adjacency,
+        seed: u64,
+    ) -> Result<Vec<Terrain>, TileGenError> {
+        let rng = &mut crate::rng(seed);
+        self.generate(radius, adjacency, rng)
+    }
+
+    /// Generates terrain as in `generate_seeded`, then the deposits of each tile from the same rng,
+    /// so the terrain is unchanged and the same seed always yields the same deposits
+    pub fn generate_with_resources(
+        &self,
+        radius: Length,
+        adjacency: &Adjacency,
+        seed: u64,
+    ) -> Result<(Vec<Terrain>, Vec<Deposits>), TileGenError> {
+        let rng = &mut crate::rng(seed);
+        let terrain = self.generate(radius, adjacency, rng)?;
+        let deposits = generate_resources(&terrain, adjacency.get(terrain.len()), rng);
+        Ok((terrain, deposits))
+    }
+
+    /// Fills `terrain`, which must be a registered size in `adjacency`, reusing the scratch buffers
+    pub fn generate_into<R: Rng>(
+        &self,
+        terrain: &mut [Terrain],
+        adjacency: &Adjacency,
+        scratch: &mut TileGenScratch,
+        rng: &mut R,
+    ) -> Result<(), TileGenError> {
+        generate_with(self, terrain, adjacency, scratch, rng, &mut |_: f64| true)
+    }
+
+    /// Generates terrain as in `generate`, reporting progress so that a UI can show it
+    /// or cancel generation, which returns `TileGenError::Cancelled`
+    pub fn generate_with_progress<R: Rng, P: Progress>(
+        &self,
+        radius: Length,
+        adjacency: &Adjacency,
+        rng: &mut R,
+        progress: &mut P,
+    ) -> Result<Vec<Terrain>, TileGenError> {
+        let mut terrain = vec![Terrain::default(); self.density.tile_count(radius)];
+        let scratch = &mut TileGenScratch::default();
+        generate_with(self, &mut terrain, adjacency, scratch, rng, progress)?;
+        Ok(terrain)
+    }
+}
+
+/// Broad orbital region of a planet relative to its star's habitable zone
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrbitalZone {
+    Inner,
+    Habitable,
+    Outer,
+}
+
+/// Constraints across all of the planets in a system, so that generated systems aren't monotonous
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationConstraints {
+    pub min_ocean_worlds: usize,
+    pub min_desert_worlds: usize,
+    pub inner_water: RangeInclusive<f64>,
+    pub habitable_water: RangeInclusive<f64>,
+    pub outer_water: RangeInclusive<f64>,
+}
+
+impl Default for GenerationConstraints {
+    fn default() -> Self {
+        Self {
+            min_ocean_worlds: 1,
+            min_desert_worlds: 1,
+            inner_water: 0.0..=0.2,
+            habitable_water: 0.0..=1.0,
+            outer_water: 0.0..=0.6,
+        }
+    }
+}
+
+impl GenerationConstraints {
+    pub const OCEAN_WORLD: RangeInclusive<f64> = 0.9..=1.0;
+    pub const DESERT_WORLD: RangeInclusive<f64> = 0.0..=0.05;
+
+    pub fn water_bounds(&self, zone: OrbitalZone) -> RangeInclusive<f64> {
+        match zone {
+            OrbitalZone::Inner => self.inner_water.clone(),
+            OrbitalZone::Habitable => self.habitable_water.clone(),
+            OrbitalZone::Outer => self.outer_water.clone(),
+        }
+    }
+
+    /// Picks a water fraction for each planet within its zone's bounds,
+    /// then adjusts the planets nearest to qualifying until the minimum counts are met.
+    pub fn tile_gens<R: Rng>(
+        &self,
+        zones: &[OrbitalZone],
+        rng: &mut R,
+    ) -> Result<Vec<TileGen>, UnsatisfiableConstraint> {
+        let bounds = zones
+            .iter()
+            .map(|zone| self.water_bounds(*zone))
+            .collect::<Vec<_>>();
+
+        let mut water = bounds
+            .iter()
+            .map(|b| rng.gen_range(b.clone()))
+            .collect::<Vec<f64>>();
+
+        let mut fixed = vec![false; zones.len()];
+
+        let mut requirements = [
+            (
+                self.min_desert_worlds,
+                Self::DESERT_WORLD,
+                UnsatisfiableConstraint::DesertWorlds,
+            ),
+            (
+                self.min_ocean_worlds,
+                Self::OCEAN_WORLD,
+                UnsatisfiableConstraint::OceanWorlds,
+            ),
+        ];
+
+        // the requirement that the fewest planets can meet goes first, so that another doesn't claim them
+        let eligible = |target: &RangeInclusive<f64>| {
+            bounds
+                .iter()
+                .filter(|b| b.start() <= target.end() && target.start() <= b.end())
+                .count()
+        };
+        requirements.sort_by_key(|(_, target, _)| eligible(target));
+
+        for (count, target, error) in requirements {
+            if !require(count, &mut water, &mut fixed, &bounds, target, rng) {
+                return Err(error);
+            }
+        }
+
+        Ok(water.into_iter().map(TileGen::new).collect())
+    }
+}
+
+fn require<R: Rng>(
+    count: usize,
+    water: &mut [f64],
+    fixed: &mut [bool],
+    bounds: &[RangeInclusive<f64>],
+    target: RangeInclusive<f64>,
+    rng: &mut R,
+) -> bool {
+    let mut satisfied = 0;
+
+    // planets that already qualify count toward the requirement
+    for (water, fixed) in water.iter().zip(fixed.iter_mut()) {
+        if satisfied < count && !*fixed && target.contains(water) {
+            *fixed = true;
+            satisfied += 1;
+        }
+    }
+
+    let distance = |w: f64| (target.start() - w).max(w - target.end()).max(0.0);
+
+    let mut candidates = (0..water.len())
+        .filter(|i| !fixed[*i])
+        .filter(|i| bounds[*i].start() <= target.end() && target.start() <= bounds[*i].end())
+        .collect::<Vec<_>>();
+
+    // stable sort keeps ties in planet order
+    candidates.sort_by(|a, b| {
+        distance(water[*a])
+            .partial_cmp(&distance(water[*b]))
+            .unwrap()
+    });
+
+    for i in candidates.into_iter().take(count.saturating_sub(satisfied)) {
+        let min = bounds[i].start().max(*target.start());
+        let max = bounds[i].end().min(*target.end());
+        water[i] = rng.gen_range(min..=max);
+        fixed[i] = true;
+        satisfied += 1;
+    }
+
+    satisfied >= count
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UnsatisfiableConstraint {
+    OceanWorlds,
+    DesertWorlds,
+}
+
+impl Display for UnsatisfiableConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnsatisfiableConstraint::OceanWorlds => {
+                write!(f, "not enough planets can be ocean worlds")
+            }
+            UnsatisfiableConstraint::DesertWorlds => {
+                write!(f, "not enough planets can be desert worlds")
+            }
+        }
     }
 }
 
+impl std::error::Error for UnsatisfiableConstraint {}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum ContinentType {
     Land,
@@ -52,7 +306,7 @@ pub fn generate_terrain_from_radius<R: Rng>(
     water_fraction: f64,
     adjacency: &Adjacency,
     rng: &mut R,
-) -> Vec<Terrain> {
+) -> Result<Vec<Terrain>, TileGenError> {
     let tiles = get_tile_count(radius);
     generate_terrain(tiles, water_fraction, adjacency, rng)
 }
@@ -62,51 +316,122 @@ pub fn generate_terrain<R: Rng>(
     water_fraction: f64,
     adjacency: &Adjacency,
     rng: &mut R,
-) -> Vec<Terrain> {
+) -> Result<Vec<Terrain>, TileGenError> {
+    let mut terrain = vec![Terrain::default(); nodes];
+    let scratch = &mut TileGenScratch::default();
+    generate_terrain_into(&mut terrain, water_fraction, adjacency, scratch, rng)?;
+    Ok(terrain)
+}
+
+/// Working memory for terrain generation, which can be reused to avoid allocating for each planet
+#[derive(Debug, Default, Clone)]
+pub struct TileGenScratch {
+    tiles: Vec<Option<Continent>>,
+    frontier: Frontier,
+    continent_types: Vec<ContinentType>,
+    erosion: Vec<u8>,
+    drainage: Vec<f64>,
+    order: Vec<usize>,
+}
+
+/// Generates terrain for each tile in `terrain`, which must be a registered size in `adjacency`.
+///
+/// Continents are drawn until their water fraction is within a tolerance of `water_fraction`.
+/// The tolerance widens with each attempt, since few tiles or continents can't match it closely,
+/// and an error with the closest fraction drawn is returned if every attempt misses.
+pub fn generate_terrain_into<R: Rng>(
+    terrain: &mut [Terrain],
+    water_fraction: f64,
+    adjacency: &Adjacency,
+    scratch: &mut TileGenScratch,
+    rng: &mut R,
+) -> Result<(), TileGenError> {
+    TileGen::new(water_fraction).generate_into(terrain, adjacency, scratch, rng)
+}
+
+fn generate_with<R: Rng, P: Progress>(
+    params: &TileGen,
+    terrain: &mut [Terrain],
+    adjacency: &Adjacency,
+    scratch: &mut TileGenScratch,
+    rng: &mut R,
+    progress: &mut P,
+) -> Result<(), TileGenError> {
+    let water_fraction = params.water_fraction;
     let plate_type = WaterFraction::new(water_fraction);
 
+    let nodes = terrain.len();
+    let positions = adjacency.positions(nodes);
     let adjacency = adjacency.get(nodes);
 
-    loop {
-        let continent_count = rng.gen_range(10.min(nodes)..14.min(nodes));
+    let continents = &params.continents;
+    if continents.start.min(nodes) >= continents.end.min(nodes) {
+        return Err(TileGenError::Continents {
+            start: continents.start,
+            end: continents.end,
+            tiles: nodes,
+        });
+    }
+
+    let TileGenScratch {
+        tiles,
+        frontier,
+        continent_types,
+        ..
+    } = scratch;
+
+    let mut closest = f64::INFINITY;
+
+    for attempt in 0..MAX_ATTEMPTS {
+        let tolerance = TOLERANCE * (1.0 + attempt as f64 / 10.0);
+        let continent_count = rng.gen_range(continents.start.min(nodes)..continents.end.min(nodes));
         let iter_continents = || (0..continent_count).map(Continent);
-        let mut neighbours = HashSet::<usize>::new();
+
+        frontier.reset(nodes);
 
         let mut unassigned_count = nodes;
-        let mut tiles = vec![Option::<Continent>::None; nodes];
+        tiles.clear();
+        tiles.resize(nodes, None);
 
         for continent in iter_continents() {
-            let tile = random_none(rng, &tiles);
+            let tile = random_none(rng, tiles);
             assign_tile(
-                &mut tiles,
+                tiles,
                 &mut unassigned_count,
-                &mut neighbours,
+                frontier,
                 adjacency,
                 tile,
                 continent,
             );
         }
 
+        let report_interval = (nodes / 64).max(1);
         while unassigned_count > 0 {
-            if let Some(tile) = random_adjacent_tile(rng, &neighbours) {
-                if let Some(continent) = random_adjacent_continent(rng, tile, &tiles, adjacency) {
+            if let Some(tile) = frontier.choose(rng) {
+                if let Some(continent) = random_adjacent_continent(rng, tile, tiles, adjacency) {
                     assign_tile(
-                        &mut tiles,
+                        tiles,
                         &mut unassigned_count,
-                        &mut neighbours,
+                        frontier,
                         adjacency,
                         tile,
                         continent,
                     );
+
+                    if unassigned_count % report_interval == 0 {
+                        let grown = 1.0 - unassigned_count as f64 / nodes as f64;
+                        if !progress.report(0.8 * grown) {
+                            return Err(TileGenError::Cancelled);
+                        }
+                    }
                 }
             }
         }
 
         // loop many times to make these continents
         for _ in 0..20 {
-            let continent_types = iter_continents()
-                .map(|_| plate_type.sample(rng))
-                .collect::<Vec<_>>();
+            continent_types.clear();
+            continent_types.extend(iter_continents().map(|_| plate_type.sample(rng)));
 
             let water_tiles = tiles
                 .iter()
@@ -115,16 +440,24 @@ pub fn generate_terrain<R: Rng>(
                 .count();
 
             let result_fraction = water_tiles as f64 / nodes as f64;
-            if (result_fraction - water_fraction).abs() < 0.03 {
-                return tiles
-                    .iter()
-                    .enumerate()
-                    .map(|(i, t)| match continent_types[t.unwrap().0] {
-                        ContinentType::Land => Terrain::new_fraction(
-                            rng.gen_range(0.0..0.05),
-                            rng.gen_range(0.1..0.25),
-                            0.0,
-                        ),
+            let error = (result_fraction - water_fraction).abs();
+            if error < (closest - water_fraction).abs() {
+                closest = result_fraction;
+            }
+
+            if error < tolerance {
+                for (i, (terrain, t)) in terrain.iter_mut().zip(tiles.iter()).enumerate() {
+                    *terrain = match continent_types[t.unwrap().0] {
+                        ContinentType::Land => {
+                            // drawn in the same order as before `mountains` scaled them,
+                            // so the default parameters give the same terrain for each seed
+                            let ocean = rng.gen_range(0.0..0.05);
+                            let mountains = rng.gen_range(0.1..0.25) * params.mountains;
+                            let terrain = Terrain::new_fraction(ocean, mountains.min(1.0), 0.0);
+                            let elevation =
+                                rng.gen_range(100.0..600.0) + 4000.0 * terrain.mountains.f64();
+                            terrain.with_elevation(Elevation::in_m(elevation * params.roughness))
+                        }
                         ContinentType::Ocean => {
                             let (ocean, count) = adjacency[i]
                                 .iter()
@@ -139,9 +472,14 @@ pub fn generate_terrain<R: Rng>(
                                 });
 
                             let ocean_fraction = ocean as f64 / count as f64;
-                            let island_chance = 0.4 - 0.2 * ocean_fraction;
+                            let island_chance = (params.island_chance
+                                * (1.0 - 0.5 * ocean_fraction))
+                                .clamp(0.0, 1.0);
                             let has_island = rng.gen_bool(island_chance);
 
+                            // the sea floor deepens away from the coast
+                            let depth = 1000.0 + 3000.0 * ocean_fraction;
+
                             if has_island {
                                 let non_zero_ratio = (ocean + 1) as f64 / (count + 1) as f64;
                                 let ocean_min = 1.0 - non_zero_ratio * 0.025;
@@ -150,14 +488,254 @@ pub fn generate_terrain<R: Rng>(
                                     rng.gen_range(0.4..0.8),
                                     0.0,
                                 )
+                                .with_elevation(Elevation::in_m(-0.5 * depth))
                             } else {
-                                Terrain::new(255, 0, 0)
+                                Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-depth))
                             }
                         }
-                    })
-                    .collect();
+                    };
+                }
+
+                // ice caps from the latitude of each tile
+                let polar = params.glacier_latitude.value.sin();
+                for (terrain, position) in terrain.iter_mut().zip(positions) {
+                    if position.z.abs() > polar {
+                        terrain.glacier = FractionalU8::new(u8::MAX);
+                    }
+                }
+
+                if !progress.report(0.9) {
+                    return Err(TileGenError::Cancelled);
+                }
+
+                erode(terrain, adjacency, EROSION_PASSES, scratch);
+
+                if let Some(cratering) = &params.cratering {
+                    cratering.apply(terrain, adjacency, rng);
+                }
+
+                progress.report(1.0);
+                return Ok(());
+            }
+        }
+    }
+
+    Err(TileGenError::WaterFraction {
+        requested: water_fraction,
+        closest,
+    })
+}
+
+/// Times the continents are regrown before `generate_terrain_into` gives up
+const MAX_ATTEMPTS: usize = 50;
+
+/// Largest difference from the requested water fraction accepted on the first attempt
+const TOLERANCE: f64 = 0.03;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileGenError {
+    /// No continents drawn came within the tolerance of the requested water fraction
+    WaterFraction { requested: f64, closest: f64 },
+    /// Stopped by `Progress::report`
+    Cancelled,
+    /// The range of continents is empty once limited to one continent per tile
+    Continents {
+        start: usize,
+        end: usize,
+        tiles: usize,
+    },
+}
+
+impl Display for TileGenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TileGenError::WaterFraction { requested, closest } => write!(
+                f,
+                "could not generate a water fraction of {:.3}, the closest was {:.3}",
+                requested, closest
+            ),
+            TileGenError::Cancelled => write!(f, "generation was cancelled"),
+            TileGenError::Continents { start, end, tiles } => write!(
+                f,
+                "cannot draw {}..{} continents on {} tiles",
+                start, end, tiles
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TileGenError {}
+
+/// Receives the progress of a long generation step, such as a large world's terrain
+/// or `geology::evolve_with_progress`, and can cancel it
+pub trait Progress {
+    /// Called with the fraction of the work done, from 0 to 1, which may fall back
+    /// when terrain generation starts over. Returning false cancels the work.
+    fn report(&mut self, fraction: f64) -> bool;
+}
+
+impl<F: FnMut(f64) -> bool> Progress for F {
+    fn report(&mut self, fraction: f64) -> bool {
+        self(fraction)
+    }
+}
+
+/// Passes of `erode` applied to newly generated terrain
+pub const EROSION_PASSES: usize = 3;
+
+/// Fraction of a tile's mountains worn down per pass when all of its neighbours are lower
+const EROSION_RATE: f64 = 0.1;
+
+/// Upstream land tiles needed for a tile to be fully drained by rivers
+const RIVER_SOURCES: f64 = 8.0;
+
+/// Wears down mountains bordering lower ground, then traces rivers downhill toward the ocean
+pub fn erode(
+    terrain: &mut [Terrain],
+    adjacency: &[AdjArray],
+    passes: usize,
+    scratch: &mut TileGenScratch,
+) {
+    let TileGenScratch {
+        erosion,
+        drainage,
+        order,
+        ..
+    } = scratch;
+
+    for _ in 0..passes {
+        erosion.clear();
+        erosion.extend(terrain.iter().zip(adjacency).map(|(tile, adj)| {
+            let exposed = adj.iter().filter(|n| is_lower(&terrain[*n], tile)).count();
+            let fraction = EROSION_RATE * exposed as f64 / adj.len().max(1) as f64;
+            (tile.mountains.u8() as f64 * fraction).round() as u8
+        }));
+
+        for (tile, eroded) in terrain.iter_mut().zip(erosion.iter().copied()) {
+            tile.mountains = FractionalU8::new(tile.mountains.u8() - eroded);
+            tile.plains = FractionalU8::new(tile.plains.u8() + eroded);
+
+            // mountains account for up to 4000 m of a tile's elevation
+            if tile.elevation.m() > 0.0 {
+                let lowered = tile.elevation.m() - 4000.0 * eroded as f64 / 255.0;
+                tile.elevation = Elevation::in_m(lowered.max(0.0));
+            }
+        }
+    }
+
+    // drain each land tile into its lowest neighbour, from the highest tiles down
+    order.clear();
+    order.extend(0..terrain.len());
+    order.sort_by_key(|i| std::cmp::Reverse(terrain[*i].elevation));
+
+    drainage.clear();
+    drainage.resize(terrain.len(), 0.0);
+
+    for &i in order.iter() {
+        if terrain[i].ocean.u8() == u8::MAX {
+            continue;
+        }
+
+        if let Some(n) = downhill(terrain, adjacency, i) {
+            drainage[n] += drainage[i] + 1.0;
+        }
+    }
+
+    for (tile, drainage) in terrain.iter_mut().zip(drainage.iter()) {
+        tile.river = if tile.ocean.u8() == u8::MAX {
+            FractionalU8::default()
+        } else {
+            FractionalU8::new_f64((drainage / RIVER_SOURCES).min(1.0))
+        };
+    }
+}
+
+/// Ocean or less mountainous ground that a tile's mountains erode toward
+fn is_lower(neighbour: &Terrain, tile: &Terrain) -> bool {
+    neighbour.ocean.u8() > tile.ocean.u8() || neighbour.mountains.u8() < tile.mountains.u8()
+}
+
+/// Impact craters gathered over the age of a surface, for moons and Mercury-like worlds.
+/// Follows the lunar chronology of Neukum et al. (2001), where the early heavy bombardment
+/// leaves surfaces older than about 4 Gyr saturated with craters.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cratering {
+    /// Surface pressure of the atmosphere, which burns up smaller impactors before they land
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub pressure: Pressure,
+    /// Time since the surface was last resurfaced
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    pub age: Duration,
+}
+
+impl Cratering {
+    /// Craters per km² at which the density reaches 1 - 1/e, about that of the lunar highlands
+    const SATURATION: f64 = 0.06;
+
+    /// Pressure in atm that screens out all but 1/e of the craters
+    const SCREENING: f64 = 0.5;
+
+    /// Tiles per large basin on a saturated surface
+    const TILES_PER_BASIN: f64 = 200.0;
+
+    /// Depth of a basin floor below the surrounding terrain in m
+    const BASIN_DEPTH: f64 = 3000.0;
+
+    /// Density of craters of any size from the age of the surface alone, from 0 to 1
+    fn impacts(&self) -> f64 {
+        let gyr = self.age.value / Duration::in_yr(1.0e9).value;
+        // cumulative craters larger than 1 km per km²
+        let craters = 5.44e-14 * ((6.93 * gyr).exp() - 1.0) + 8.38e-4 * gyr;
+        1.0 - (-craters / Self::SATURATION).exp()
+    }
+
+    /// Mean density of craters that reach the surface, from 0 for none to 1 for saturated
+    pub fn density(&self) -> f64 {
+        let atm = self.pressure / Pressure::in_atm(1.0);
+        self.impacts() * (-atm / Self::SCREENING).exp()
+    }
+
+    /// Lowers and flattens the floors of large basins and raises their rims,
+    /// returning the crater density of each tile. Basins are large enough to reach the surface
+    /// through any atmosphere, and the density of their floors starts over from when they formed.
+    pub fn apply<R: Rng>(
+        &self,
+        terrain: &mut [Terrain],
+        adjacency: &[AdjArray],
+        rng: &mut R,
+    ) -> Vec<f64> {
+        let mean = self.density();
+        let mut density = terrain
+            .iter()
+            .map(|tile| {
+                let land = 1.0 - tile.ocean.f64();
+                (mean * rng.gen_range(0.7..1.3)).min(1.0) * land
+            })
+            .collect::<Vec<_>>();
+
+        let expected = self.impacts() * terrain.len() as f64 / Self::TILES_PER_BASIN;
+        let basins = expected.floor() as usize + rng.gen_bool(expected.fract()) as usize;
+
+        for _ in 0..basins {
+            let centre = rng.gen_range(0..terrain.len());
+
+            let floor = &mut terrain[centre];
+            floor.plains = FractionalU8::new(floor.plains.u8() + floor.mountains.u8());
+            floor.mountains = FractionalU8::default();
+            floor.elevation = Elevation::in_m(floor.elevation.m() - Self::BASIN_DEPTH);
+            density[centre] *= rng.gen_range(0.0..1.0);
+
+            for n in adjacency[centre].iter() {
+                let rim = &mut terrain[n];
+                let raised = rim.plains.u8() / 2;
+                rim.plains = FractionalU8::new(rim.plains.u8() - raised);
+                rim.mountains = FractionalU8::new(rim.mountains.u8() + raised);
+                rim.elevation = Elevation::in_m(rim.elevation.m() + 0.25 * Self::BASIN_DEPTH);
             }
         }
+
+        density
     }
 }
 
@@ -174,12 +752,40 @@ fn random_none<R: Rng, T>(rng: &mut R, slice: &[Option<T>]) -> usize {
     }
 }
 
-fn random_adjacent_tile<R: Rng + ?Sized>(
-    rng: &mut R,
-    neighbours: &HashSet<usize>,
-) -> Option<usize> {
-    use rand::prelude::IteratorRandom;
-    neighbours.iter().choose(rng).copied()
+/// Unassigned tiles that are adjacent to assigned tiles,
+/// with constant-time insertion, removal, and random selection
+#[derive(Debug, Default, Clone)]
+struct Frontier {
+    tiles: Vec<usize>,
+    index: Vec<Option<usize>>,
+}
+
+impl Frontier {
+    fn reset(&mut self, nodes: usize) {
+        self.tiles.clear();
+        self.index.clear();
+        self.index.resize(nodes, None);
+    }
+
+    fn insert(&mut self, tile: usize) {
+        if self.index[tile].is_none() {
+            self.index[tile] = Some(self.tiles.len());
+            self.tiles.push(tile);
+        }
+    }
+
+    fn remove(&mut self, tile: usize) {
+        if let Some(i) = self.index[tile].take() {
+            self.tiles.swap_remove(i);
+            if let Some(moved) = self.tiles.get(i) {
+                self.index[*moved] = Some(i);
+            }
+        }
+    }
+
+    fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<usize> {
+        self.tiles.choose(rng).copied()
+    }
 }
 
 fn random_adjacent_continent<R: Rng>(
@@ -188,27 +794,24 @@ fn random_adjacent_continent<R: Rng>(
     tiles: &[Option<Continent>],
     adjacency: &[AdjArray],
 ) -> Option<Continent> {
-    let adjacent = adjacency[tile]
-        .iter()
-        .filter_map(|t| tiles[t])
-        .collect::<Vec<_>>();
-    adjacent.choose(rng).copied()
+    use rand::prelude::IteratorRandom;
+    adjacency[tile].iter().filter_map(|t| tiles[t]).choose(rng)
 }
 
 fn assign_tile(
     tiles: &mut [Option<Continent>],
     unassigned_count: &mut usize,
-    neighbours: &mut HashSet<usize>,
+    frontier: &mut Frontier,
     adjacency: &[AdjArray],
     tile: usize,
     continent: Continent,
 ) {
     for n in adjacency[tile].iter() {
         if tiles[n].is_none() {
-            neighbours.insert(n);
+            frontier.insert(n);
         }
     }
-    neighbours.remove(&tile);
+    frontier.remove(tile);
 
     tiles[tile] = Some(continent);
     *unassigned_count -= 1;
@@ -217,7 +820,9 @@ fn assign_tile(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::constants::EARTH_RADIUS;
     use rand::thread_rng;
+    use std::iter::FromIterator;
 
     #[test]
     fn tiles_test() {
@@ -229,7 +834,7 @@ mod test {
 
         use std::time::Instant;
         let start = Instant::now();
-        generate_terrain(N, 0.5, &adj, rng);
+        generate_terrain(N, 0.5, &adj, rng).unwrap();
         let end = Instant::now();
 
         println!("done: {} us", (end - start).as_micros());
@@ -243,7 +848,7 @@ mod test {
         let rng = &mut thread_rng();
         let mut adj = Adjacency::default();
         adj.register(N);
-        generate_terrain(N, 0.0, &adj, rng);
+        generate_terrain(N, 0.0, &adj, rng).unwrap();
     }
 
     #[test]
@@ -252,7 +857,7 @@ mod test {
         let rng = &mut thread_rng();
         let mut adj = Adjacency::default();
         adj.register(N);
-        generate_terrain(N, 1.0, &adj, rng);
+        generate_terrain(N, 1.0, &adj, rng).unwrap();
     }
 
     #[test]
@@ -262,7 +867,438 @@ mod test {
         let rng = &mut thread_rng();
         let mut adj = Adjacency::default();
         adj.register(N);
-        generate_terrain(N, 1.1, &adj, rng);
+        generate_terrain(N, 1.1, &adj, rng).unwrap();
+    }
+
+    #[test]
+    fn unreachable_water_fraction_is_an_error() {
+        const N: usize = 12;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+
+        // a single continent is either all land or all ocean
+        let tile_gen = TileGen {
+            continents: 1..2,
+            ..TileGen::new(0.5)
+        };
+        let mut terrain = vec![Terrain::default(); N];
+        let scratch = &mut TileGenScratch::default();
+        let result = tile_gen.generate_into(&mut terrain, &adj, scratch, &mut crate::rng(0));
+
+        match result {
+            Err(TileGenError::WaterFraction { requested, closest }) => {
+                assert_eq!(0.5, requested);
+                assert!(closest == 0.0 || closest == 1.0);
+            }
+            other => panic!("one continent can't be half ocean: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn more_continents_than_tiles_is_an_error() {
+        const N: usize = 8;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+
+        let mut terrain = vec![Terrain::default(); N];
+        let scratch = &mut TileGenScratch::default();
+        let result =
+            TileGen::new(0.7).generate_into(&mut terrain, &adj, scratch, &mut crate::rng(0));
+
+        assert_eq!(
+            Err(TileGenError::Continents {
+                start: 10,
+                end: 14,
+                tiles: N
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn progress_and_cancellation() {
+        let radius = Length::in_m(EARTH_RADIUS);
+        let mut adj = Adjacency::default();
+        adj.register(get_tile_count(radius));
+        let tile_gen = TileGen::new(0.7);
+
+        let mut last = 0.0;
+        let terrain =
+            tile_gen.generate_with_progress(radius, &adj, &mut crate::rng(1), &mut |f: f64| {
+                last = f;
+                true
+            });
+        assert_eq!(tile_gen.generate_seeded(radius, &adj, 1), terrain);
+        assert_eq!(1.0, last);
+
+        let cancelled =
+            tile_gen
+                .generate_with_progress(radius, &adj, &mut crate::rng(1), &mut |f: f64| f < 0.5);
+        assert_eq!(Err(TileGenError::Cancelled), cancelled);
+    }
+
+    #[test]
+    fn generate_seeded_is_deterministic() {
+        let radius = Length::in_m(EARTH_RADIUS);
+        let mut adj = Adjacency::default();
+        adj.register(get_tile_count(radius));
+
+        let tile_gen = TileGen::new(0.7);
+
+        let a = tile_gen.generate_seeded(radius, &adj, 42).unwrap();
+        let b = tile_gen.generate_seeded(radius, &adj, 42).unwrap();
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn generate_seeded_draws_are_stable() {
+        let radius = Length::in_m(EARTH_RADIUS);
+        let mut adj = Adjacency::default();
+        adj.register(get_tile_count(radius));
+
+        let terrain = TileGen::new(0.7).generate_seeded(radius, &adj, 7).unwrap();
+
+        // the ocean share is the first draw on each continental tile,
+        // so reordering the draws moves these by far more than a rounding step
+        let land = [
+            35, 36, 41, 48, 49, 50, 51, 53, 54, 56, 57, 58, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72,
+            78, 80, 81, 82, 83, 84, 90, 91, 92,
+        ];
+        let ocean = [
+            6, 4, 4, 3, 3, 6, 7, 2, 8, 0, 1, 2, 8, 2, 5, 9, 5, 1, 10, 3, 11, 1, 6, 8, 4, 4, 7, 12,
+            4, 9, 3,
+        ];
+
+        let continental = (0..terrain.len())
+            .filter(|&i| terrain[i].ocean.u8() < 128)
+            .collect::<Vec<_>>();
+        assert_eq!(&land[..], &continental[..]);
+
+        for (&i, &ocean) in land.iter().zip(ocean.iter()) {
+            let actual = terrain[i].ocean.u8() as i32;
+            assert!(
+                (actual - ocean).abs() <= 1,
+                "tile {}: {} != {}",
+                i,
+                actual,
+                ocean
+            );
+        }
+    }
+
+    #[test]
+    fn generate_seeded_golden() {
+        let radius = Length::in_m(EARTH_RADIUS);
+        let tile_gen = TileGen {
+            glacier_latitude: Angle::in_deg(60.0),
+            density: TileDensity::Scaled { earth: 24.0 },
+            ..TileGen::new(0.7)
+        };
+        let mut adj = Adjacency::default();
+        adj.register(24);
+
+        let terrain = tile_gen.generate_seeded(radius, &adj, 7).unwrap();
+
+        // ocean, mountains, plains, glacier, and elevation in metres of each tile
+        let expected: [(u8, u8, u8, u8, f64); 24] = [
+            (255, 0, 0, 255, -4000.0),
+            (255, 0, 0, 255, -4000.0),
+            (255, 0, 0, 0, -3500.0),
+            (255, 0, 0, 0, -3500.0),
+            (255, 0, 0, 0, -3500.0),
+            (254, 1, 0, 0, -1750.0),
+            (255, 0, 0, 0, -4000.0),
+            (255, 0, 0, 0, -4000.0),
+            (255, 0, 0, 0, -3500.0),
+            (3, 30, 222, 0, 663.0),
+            (255, 0, 0, 0, -2500.0),
+            (255, 0, 0, 0, -3000.0),
+            (2, 44, 209, 0, 928.0),
+            (255, 0, 0, 0, -3500.0),
+            (255, 0, 0, 0, -3500.0),
+            (255, 0, 0, 0, -3000.0),
+            (255, 0, 0, 0, -2000.0),
+            (1, 28, 226, 0, 745.0),
+            (12, 35, 208, 0, 941.0),
+            (255, 0, 0, 0, -2500.0),
+            (255, 0, 0, 0, -2500.0),
+            (12, 39, 204, 0, 870.0),
+            (12, 42, 201, 255, 1217.0),
+            (7, 24, 224, 255, 707.0),
+        ];
+
+        assert_eq!(24, terrain.len());
+        for (i, (tile, &(ocean, mountains, plains, glacier, elevation))) in
+            terrain.iter().zip(expected.iter()).enumerate()
+        {
+            // fractions may round either way by one step, which moves the elevation by a few metres
+            let near = |actual: FractionalU8, expected: u8| {
+                (actual.u8() as i32 - expected as i32).abs() <= 1
+            };
+            assert!(near(tile.ocean, ocean), "tile {}: {:?}", i, tile);
+            assert!(near(tile.mountains, mountains), "tile {}: {:?}", i, tile);
+            assert!(near(tile.plains, plains), "tile {}: {:?}", i, tile);
+            assert!(near(tile.glacier, glacier), "tile {}: {:?}", i, tile);
+            assert!(
+                (tile.elevation.m() - elevation).abs() <= 10.0,
+                "tile {}: {:?}",
+                i,
+                tile
+            );
+        }
+    }
+
+    #[test]
+    fn resources_leave_terrain_unchanged() {
+        let radius = Length::in_m(EARTH_RADIUS);
+        let mut adj = Adjacency::default();
+        adj.register(get_tile_count(radius));
+
+        let tile_gen = TileGen::new(0.7);
+
+        let (terrain, deposits) = tile_gen.generate_with_resources(radius, &adj, 42).unwrap();
+
+        assert_eq!(tile_gen.generate_seeded(radius, &adj, 42).unwrap(), terrain);
+        assert_eq!(terrain.len(), deposits.len());
+    }
+
+    #[test]
+    fn cratering_by_age_and_atmosphere() {
+        let crater = |atm: f64, gyr: f64| Cratering {
+            pressure: Pressure::in_atm(atm),
+            age: Duration::in_yr(gyr * 1.0e9),
+        };
+
+        assert!(crater(0.0, 4.5).density() > 0.99);
+        assert!(crater(0.0, 3.5).density() < 0.2);
+        assert!(crater(1.0, 4.5).density() < 0.2);
+        assert!(crater(90.0, 4.5).density() < 1e-12);
+    }
+
+    #[test]
+    fn basins_on_old_airless_worlds() {
+        const N: usize = 256;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+
+        let plains = Terrain::new(0, 50, 0).with_elevation(Elevation::in_m(1000.0));
+        let mut terrain = vec![plains; N];
+        let cratering = Cratering {
+            pressure: Pressure::zero(),
+            age: Duration::in_yr(4.5e9),
+        };
+
+        let density = cratering.apply(&mut terrain, adj.get(N), &mut crate::rng(3));
+
+        assert_eq!(N, density.len());
+        assert!(terrain.iter().all(Terrain::is_valid));
+        assert!(terrain.iter().any(|t| t.elevation < plains.elevation));
+        assert!(terrain.iter().any(|t| t.mountains > plains.mountains));
+    }
+
+    #[test]
+    fn generated_airless_worlds_have_basins() {
+        let radius = Length::in_m(1737.4e3);
+        let mut adj = Adjacency::default();
+
+        let tile_gen = TileGen {
+            density: TileDensity::Scaled { earth: 1000.0 },
+            ..TileGen::new(0.0)
+        };
+        adj.register(tile_gen.density.tile_count(radius));
+
+        let cratered = TileGen {
+            cratering: Some(Cratering {
+                pressure: Pressure::zero(),
+                age: Duration::in_yr(4.5e9),
+            }),
+            ..tile_gen.clone()
+        };
+
+        let smooth = tile_gen.generate_seeded(radius, &adj, 0).unwrap();
+        let basins = cratered.generate_seeded(radius, &adj, 0).unwrap();
+
+        // the basins are drawn after the rest of the terrain, which is otherwise unchanged
+        let below_datum = |terrain: &[Terrain]| {
+            terrain
+                .iter()
+                .filter(|t| t.elevation < Elevation::default())
+                .count()
+        };
+        assert_eq!(0, below_datum(&smooth));
+        assert!(below_datum(&basins) > 0);
+        assert_eq!(smooth.len(), basins.len());
+    }
+
+    #[test]
+    fn generation_constraints_satisfied() {
+        use OrbitalZone::*;
+        let constraints = GenerationConstraints::default();
+        let zones = [Inner, Inner, Habitable, Outer, Outer];
+
+        for seed in 0..100 {
+            let rng = &mut crate::rng(seed);
+            let tile_gens = constraints.tile_gens(&zones, rng).unwrap();
+
+            let is = |range: RangeInclusive<f64>| {
+                tile_gens
+                    .iter()
+                    .filter(|t| range.contains(&t.water_fraction))
+                    .count()
+            };
+
+            assert!(is(GenerationConstraints::OCEAN_WORLD) >= 1);
+            assert!(is(GenerationConstraints::DESERT_WORLD) >= 1);
+        }
+    }
+
+    #[test]
+    fn generation_constraints_unsatisfiable() {
+        let constraints = GenerationConstraints::default();
+        let zones = [OrbitalZone::Inner, OrbitalZone::Inner];
+
+        assert_eq!(
+            Err(UnsatisfiableConstraint::OceanWorlds),
+            constraints.tile_gens(&zones, &mut thread_rng())
+        );
+    }
+
+    #[test]
+    fn land_is_above_sea_level() {
+        const N: usize = 64;
+        let rng = &mut crate::rng(0);
+        let mut adj = Adjacency::default();
+        adj.register(N);
+
+        for terrain in generate_terrain(N, 0.5, &adj, rng).unwrap() {
+            if terrain.ocean == FractionalU8::new(255) {
+                assert!(terrain.elevation < Elevation::default());
+            }
+            if terrain.ocean.f64() < 0.5 {
+                assert!(terrain.elevation > Elevation::default());
+            }
+        }
+    }
+
+    #[test]
+    fn generate_into_reuses_scratch() {
+        const N: usize = 64;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+
+        let tile_gen = TileGen::new(0.5);
+        let mut scratch = TileGenScratch::default();
+        let mut a = vec![Terrain::default(); N];
+        let mut b = vec![Terrain::default(); N];
+
+        tile_gen
+            .generate_into(&mut a, &adj, &mut scratch, &mut crate::rng(3))
+            .unwrap();
+        tile_gen
+            .generate_into(&mut b, &adj, &mut scratch, &mut crate::rng(3))
+            .unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(
+            a,
+            generate_terrain(N, 0.5, &adj, &mut crate::rng(3)).unwrap()
+        );
+    }
+
+    #[test]
+    fn archipelagos_and_ice_caps() {
+        const N: usize = 256;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+
+        let land = |terrain: &[Terrain]| {
+            let islands = terrain.iter().filter(|t| t.ocean.u8() < u8::MAX);
+            islands.count()
+        };
+
+        let generate = |tile_gen: TileGen| {
+            let mut terrain = vec![Terrain::default(); N];
+            let scratch = &mut TileGenScratch::default();
+            tile_gen
+                .generate_into(&mut terrain, &adj, scratch, &mut crate::rng(5))
+                .unwrap();
+            terrain
+        };
+
+        let earth = generate(TileGen::new(0.8));
+        let terrain = generate(TileGen {
+            island_chance: 1.0,
+            glacier_latitude: Angle::in_deg(60.0),
+            ..TileGen::new(0.8)
+        });
+
+        assert!(land(&terrain) > land(&earth));
+        assert!(earth.iter().all(|t| t.glacier.u8() == 0));
+        assert!(terrain[0].glacier.u8() == u8::MAX);
+        assert!(terrain[N / 2].glacier.u8() == 0);
+    }
+
+    #[test]
+    fn erosion_wears_down_mountains() {
+        let adjacency = [
+            AdjArray::from_iter(vec![1]),
+            AdjArray::from_iter(vec![0, 2]),
+            AdjArray::from_iter(vec![1]),
+        ];
+        let mut terrain = [
+            Terrain::new(0, 200, 0).with_elevation(Elevation::in_m(3000.0)),
+            Terrain::new(0, 20, 0).with_elevation(Elevation::in_m(500.0)),
+            Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-1000.0)),
+        ];
+
+        erode(&mut terrain, &adjacency, 5, &mut TileGenScratch::default());
+
+        assert!(terrain[0].mountains.u8() < 200);
+        assert!(terrain[0].elevation.m() < 3000.0);
+        for tile in &terrain {
+            assert_eq!(
+                255,
+                tile.ocean.u8() + tile.mountains.u8() + tile.plains.u8()
+            );
+        }
+    }
+
+    #[test]
+    fn rivers_flow_downhill_to_the_ocean() {
+        let adjacency = [
+            AdjArray::from_iter(vec![1]),
+            AdjArray::from_iter(vec![0, 2]),
+            AdjArray::from_iter(vec![1, 3]),
+            AdjArray::from_iter(vec![2]),
+        ];
+        let mut terrain = [
+            Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(900.0)),
+            Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(600.0)),
+            Terrain::new(0, 0, 0).with_elevation(Elevation::in_m(300.0)),
+            Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-1000.0)),
+        ];
+
+        erode(&mut terrain, &adjacency, 0, &mut TileGenScratch::default());
+
+        assert!(!terrain[0].has_river());
+        assert!(terrain[1].has_river());
+        assert!(terrain[2].river.u8() > terrain[1].river.u8());
+        assert!(!terrain[3].has_river());
+    }
+
+    #[test]
+    fn frontier() {
+        let mut frontier = Frontier::default();
+        frontier.reset(4);
+        frontier.insert(1);
+        frontier.insert(3);
+        frontier.insert(1);
+        frontier.remove(1);
+
+        assert_eq!(vec![3], frontier.tiles);
+        assert_eq!(Some(0), frontier.index[3]);
+        assert_eq!(None, frontier.index[1]);
     }
 
     #[test]
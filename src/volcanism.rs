@@ -0,0 +1,182 @@
+use crate::adjacency::AdjArray;
+use crate::atmosphere::Atmosphere;
+use crate::geology::{internal_heat_flux, EARTH_HEAT_FLUX, HEAT_DECAY_TIME};
+use crate::solar_radiation::Gas;
+use crate::tectonics::Tectonics;
+use crate::terrain::{Elevation, Terrain};
+use physics_types::{Acceleration, Duration, Mass, Pressure};
+
+/// Volcanoes venting gas into the atmosphere and building up the ground around them,
+/// raising islands from the sea floor where they erupt under the ocean.
+///
+/// Activity fades as the planet's interior cools, at the same rate as `internal_heat_flux`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Volcanism {
+    /// Global activity relative to modern Earth's
+    pub activity: f64,
+    /// Relative activity of each tile, with a mean of 1
+    pub vents: Vec<f64>,
+}
+
+impl Volcanism {
+    /// CO2 outgassed by modern Earth in kg per year per m² of its surface
+    pub const CO2_RATE: f64 = 5.0e-4;
+    /// SO2 outgassed by modern Earth in kg per year per m² of its surface
+    pub const SO2_RATE: f64 = 4.0e-5;
+    /// Metres of lava and ash added per year to a tile of mean activity on modern Earth
+    pub const BUILD_RATE: f64 = 1.0e-3;
+    /// Activity of tiles on plate boundaries relative to hotspots within the plates
+    pub const BOUNDARY: f64 = 10.0;
+    /// Relief in metres over which a tile changes from sea floor to land
+    const ISLAND_RELIEF: f64 = 1000.0;
+
+    /// Volcanoes spread evenly over every tile, as hotspots without plate boundaries
+    pub fn new(tiles: usize, activity: f64) -> Self {
+        Self {
+            activity,
+            vents: vec![1.0; tiles],
+        }
+    }
+
+    /// Activity scaled from modern Earth's by the heat flowing out of the planet's interior
+    pub fn for_planet(tiles: usize, mass: Mass, age: Duration) -> Self {
+        let activity = internal_heat_flux(mass, age).value / EARTH_HEAT_FLUX;
+        Self::new(tiles, activity)
+    }
+
+    /// Concentrates the volcanoes on tiles that border another plate
+    pub fn with_tectonics(mut self, tectonics: &Tectonics, adjacency: &[AdjArray]) -> Self {
+        for (i, vent) in self.vents.iter_mut().enumerate() {
+            let plate = tectonics.plate[i];
+            let boundary = adjacency[i].iter().any(|n| tectonics.plate[n] != plate);
+            *vent = if boundary { Self::BOUNDARY } else { 1.0 };
+        }
+
+        let mean = self.vents.iter().sum::<f64>() / self.vents.len().max(1) as f64;
+        self.vents.iter_mut().for_each(|v| *v /= mean);
+        self
+    }
+
+    /// Vents CO2 and SO2 into the atmosphere and builds up each tile,
+    /// turning sea floor that reaches the surface into land
+    ///
+    /// # Arguments
+    ///
+    /// * `surface_gravity`: relates the atmosphere's mass to its pressure
+    pub fn step(
+        &mut self,
+        terrain: &mut [Terrain],
+        atmosphere: &mut Atmosphere,
+        surface_gravity: Acceleration,
+        dt: Duration,
+    ) {
+        let years = dt / Duration::in_yr(1.0);
+
+        // kg/m² over the whole planet, however the vents are arranged
+        let vented =
+            |rate: f64| Pressure::in_pa(self.activity * rate * years * surface_gravity.value);
+        atmosphere.add_partial_pressure(Gas::CarbonDioxide, vented(Self::CO2_RATE));
+        atmosphere.add_partial_pressure(Gas::SulfurDioxide, vented(Self::SO2_RATE));
+
+        for (tile, vent) in terrain.iter_mut().zip(&self.vents) {
+            let built = self.activity * vent * Self::BUILD_RATE * years;
+            let elevation = (tile.elevation.m() + built).min(Tectonics::MAX_ELEVATION);
+            *tile = tile.with_elevation(Elevation::in_m(elevation));
+
+            let ocean = tile.ocean.u8();
+            if ocean > 0 && elevation > 0.0 {
+                let emerged = (255.0 * built / Self::ISLAND_RELIEF)
+                    .round()
+                    .min(ocean as f64) as u8;
+                let mountains = tile.mountains.u8() + emerged;
+                *tile = Terrain::new(ocean - emerged, mountains, tile.glacier.u8())
+                    .with_elevation(tile.elevation);
+            }
+        }
+
+        self.activity *= (-years / HEAT_DECAY_TIME).exp();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::constants::EARTH_MASS;
+    use crate::tectonics::Plate;
+    use physics_types::Length;
+
+    const N: usize = 32;
+
+    fn gravity() -> Acceleration {
+        Acceleration::in_m_per_s2(9.81)
+    }
+
+    #[test]
+    fn activity_fades_with_age() {
+        let earth = Mass::in_kg(EARTH_MASS);
+        let young = Volcanism::for_planet(N, earth, Duration::in_yr(1.0e9));
+        let mut today = Volcanism::for_planet(N, earth, Duration::in_yr(4.5e9));
+
+        assert!((today.activity - 1.0).abs() < 1e-12);
+        assert!(young.activity > today.activity);
+
+        let mut terrain = vec![Terrain::new(0, 0, 0); N];
+        let mut atmosphere = Atmosphere::earth();
+        today.step(
+            &mut terrain,
+            &mut atmosphere,
+            gravity(),
+            Duration::in_yr(1.0e9),
+        );
+        assert!(today.activity < 1.0);
+    }
+
+    #[test]
+    fn outgassing_and_islands() {
+        let mut volcanism = Volcanism::new(N, 1.0);
+        let sea_floor = Terrain::new(255, 0, 0).with_elevation(Elevation::in_m(-100.0));
+        let mut terrain = vec![sea_floor; N];
+        let mut atmosphere = Atmosphere::earth();
+        let co2 = atmosphere.partial_pressure(Gas::CarbonDioxide);
+        let so2 = atmosphere.partial_pressure(Gas::SulfurDioxide);
+
+        volcanism.step(
+            &mut terrain,
+            &mut atmosphere,
+            gravity(),
+            Duration::in_yr(1.0e6),
+        );
+
+        assert!(atmosphere.partial_pressure(Gas::CarbonDioxide) > co2);
+        assert!(atmosphere.partial_pressure(Gas::SulfurDioxide) > so2);
+        assert!(terrain.iter().all(|t| t.ocean.u8() < sea_floor.ocean.u8()));
+        assert!(terrain.iter().all(Terrain::is_valid));
+    }
+
+    #[test]
+    fn vents_on_plate_boundaries() {
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        let adjacency = adj.get(N);
+
+        let plate = |rate| Plate {
+            pole: [0.0, 0.0, 1.0],
+            rate,
+        };
+        let plates = (0..N).map(|i| (i < N / 2) as u8).collect();
+        let positions = adj.positions(N).to_vec();
+        let tectonics = Tectonics::new(
+            vec![plate(0.0), plate(1.0)],
+            plates,
+            positions,
+            Length::in_m(6.4e6),
+        );
+        let volcanism = Volcanism::new(N, 1.0).with_tectonics(&tectonics, adjacency);
+
+        let mean = volcanism.vents.iter().sum::<f64>() / N as f64;
+        assert!((mean - 1.0).abs() < 1e-12);
+        assert!(volcanism.vents[0] < volcanism.vents[N / 2]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::adjacency::AdjArray;
+use crate::climate::ClimateSummary;
+use crate::terrain::{downhill, Terrain};
+
+/// The tiles draining into a common sink
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Basin {
+    /// The lowest tile of the basin, which has no lower neighbour
+    pub sink: usize,
+    /// Every tile draining into the sink, in ascending order
+    pub tiles: Vec<usize>,
+    /// True if the sink is dry land, so water only leaves by evaporation
+    pub endorheic: bool,
+}
+
+/// What collects at the sink of an endorheic basin
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Lake {
+    Salt,
+    DryLakebed,
+}
+
+impl Basin {
+    /// Mean annual precipitation below which an endorheic basin dries out, in kg/m²/yr
+    pub const ARID: f64 = 250.0;
+
+    /// The lake at the sink of an endorheic basin, which leaves evaporite deposits
+    pub fn lake(&self, climate: &ClimateSummary) -> Option<Lake> {
+        if !self.endorheic {
+            return None;
+        }
+
+        let total = self
+            .tiles
+            .iter()
+            .map(|i| climate.annual_precipitation(*i))
+            .sum::<f64>();
+
+        if total / self.tiles.len() as f64 >= Self::ARID {
+            Some(Lake::Salt)
+        } else {
+            Some(Lake::DryLakebed)
+        }
+    }
+
+    pub fn has_evaporites(&self) -> bool {
+        self.endorheic
+    }
+}
+
+/// The sink that each tile drains into by following the steepest descent
+pub fn drainage_sinks(terrain: &[Terrain], adjacency: &[AdjArray]) -> Vec<usize> {
+    let mut order = (0..terrain.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| terrain[*i].elevation);
+
+    // lower tiles are resolved first, so each downhill neighbour already knows its sink
+    let mut sink = (0..terrain.len()).collect::<Vec<_>>();
+    for i in order {
+        if let Some(n) = downhill(terrain, adjacency, i) {
+            sink[i] = sink[n];
+        }
+    }
+
+    sink
+}
+
+/// Divides the tiles into drainage basins, ordered by their sink.
+/// Flat ground with no lower neighbour forms its own basin.
+pub fn basins(terrain: &[Terrain], adjacency: &[AdjArray]) -> Vec<Basin> {
+    let sinks = drainage_sinks(terrain, adjacency);
+
+    let mut basins = sinks
+        .iter()
+        .enumerate()
+        .filter(|(i, sink)| i == *sink)
+        .map(|(sink, _)| Basin {
+            sink,
+            tiles: vec![],
+            endorheic: terrain[sink].ocean.u8() == 0,
+        })
+        .collect::<Vec<_>>();
+
+    for (i, sink) in sinks.iter().enumerate() {
+        let index = basins
+            .binary_search_by_key(sink, |b| b.sink)
+            .expect("sink without a basin");
+        basins[index].tiles.push(i);
+    }
+
+    basins
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::line;
+    use crate::terrain::Elevation;
+    use physics_types::{Duration, Temperature};
+
+    /// Ocean at tile 0, a ridge at tile 2, and a dry hollow at tile 4
+    fn valley() -> (Vec<Terrain>, Vec<AdjArray>) {
+        let terrain = [-100.0, 200.0, 800.0, 500.0, 300.0, 600.0]
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let ocean = if i == 0 { 255 } else { 0 };
+                Terrain::new(ocean, 0, 0).with_elevation(Elevation::in_m(*m))
+            })
+            .collect();
+
+        (terrain, line(6))
+    }
+
+    #[test]
+    fn basins_split_at_ridges() {
+        let (terrain, adjacency) = valley();
+        let basins = basins(&terrain, &adjacency);
+
+        assert_eq!(2, basins.len());
+        assert_eq!(vec![0, 1, 2], basins[0].tiles);
+        assert!(!basins[0].endorheic);
+        assert_eq!(4, basins[1].sink);
+        assert_eq!(vec![3, 4, 5], basins[1].tiles);
+        assert!(basins[1].endorheic);
+    }
+
+    #[test]
+    fn arid_basins_dry_out() {
+        let (terrain, adjacency) = valley();
+        let basins = basins(&terrain, &adjacency);
+        let temperature = vec![Temperature::in_c(15.0); 6];
+
+        let mut wet = ClimateSummary::new(6);
+        wet.record(&temperature, &[1.0; 6], Duration::in_d(1.0));
+
+        let mut dry = ClimateSummary::new(6);
+        dry.record(&temperature, &[0.1; 6], Duration::in_d(1.0));
+
+        assert_eq!(None, basins[0].lake(&wet));
+        assert_eq!(Some(Lake::Salt), basins[1].lake(&wet));
+        assert_eq!(Some(Lake::DryLakebed), basins[1].lake(&dry));
+    }
+}
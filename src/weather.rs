@@ -0,0 +1,263 @@
+use crate::adjacency::units::Position3;
+use crate::adjacency::{get_tile_spacing, AdjArray};
+use crate::terrain::Terrain;
+use physics_types::{Duration, Length};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Horizontal wind velocity in m/s
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wind {
+    pub east: f64,
+    pub north: f64,
+}
+
+impl Wind {
+    pub fn speed(self) -> f64 {
+        self.east.hypot(self.north)
+    }
+}
+
+/// Prevailing surface winds from an idealized circulation of alternating
+/// Hadley-like and Ferrel-like cells in each hemisphere
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weather {
+    pub wind: Vec<Wind>,
+    /// Wind speed toward each neighbour in m/s, in the order of the tile's `AdjArray`
+    flow: Vec<[f64; AdjArray::CAPACITY]>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::quantity"))]
+    spacing: Length,
+}
+
+impl Weather {
+    /// Peak speed of the prevailing winds in m/s
+    pub const SPEED: f64 = 7.0;
+
+    /// The winds over tiles centred on `positions`, see `Adjacency::positions`
+    pub fn new(
+        adjacency: &[AdjArray],
+        positions: &[Position3],
+        radius: Length,
+        rotation_period: Duration,
+    ) -> Self {
+        let nodes = adjacency.len();
+        let cells = Self::cells(rotation_period);
+
+        let wind = positions
+            .iter()
+            .map(|p| Self::prevailing(p.z.clamp(-1.0, 1.0).asin(), cells))
+            .collect::<Vec<_>>();
+
+        let flow = positions
+            .iter()
+            .zip(adjacency)
+            .zip(&wind)
+            .map(|((p, adj), wind)| {
+                let mut flow = [0.0; AdjArray::CAPACITY];
+                for (flow, n) in flow.iter_mut().zip(adj.iter()) {
+                    let (east, north) = tangent_direction(*p, positions[n]);
+                    *flow = (wind.east * east + wind.north * north).max(0.0);
+                }
+                flow
+            })
+            .collect();
+
+        let spacing = get_tile_spacing(radius, nodes);
+
+        Self {
+            wind,
+            flow,
+            spacing,
+        }
+    }
+
+    /// Circulation cells in each hemisphere: three for Earth, one for slow rotators like Venus
+    pub fn cells(rotation_period: Duration) -> usize {
+        let relative_rate = Duration::in_d(1.0) / rotation_period;
+        ((3.0 * relative_rate.abs().sqrt()).round() as usize).clamp(1, 5)
+    }
+
+    /// The surface wind at the given latitude in radians
+    pub fn prevailing(latitude: f64, cells: usize) -> Wind {
+        let width = FRAC_PI_2 / cells as f64;
+        let position = latitude.abs() / width;
+        let cell = (position as usize).min(cells - 1);
+        let strength = Self::SPEED * (PI * position.fract()).sin();
+
+        // surface flow alternates between equatorward and poleward from one cell to the next,
+        // and the Coriolis effect deflects it west and east respectively
+        let poleward = if cell % 2 == 0 { -1.0 } else { 1.0 };
+        let hemisphere = latitude.signum();
+
+        Wind {
+            east: poleward * strength,
+            north: poleward * hemisphere * strength * 0.3,
+        }
+    }
+
+    /// Moves a per-tile quantity downwind using a first-order upwind scheme, conserving its total
+    pub fn advect(
+        &self,
+        field: &mut [f64],
+        buffer: &mut Vec<f64>,
+        adjacency: &[AdjArray],
+        dt: Duration,
+    ) {
+        let scale = dt.value / self.spacing.value;
+
+        buffer.clear();
+        buffer.resize(field.len(), 0.0);
+
+        for (i, (flow, adj)) in self.flow.iter().zip(adjacency).enumerate() {
+            // a tile can't give away more than it holds
+            let total = flow.iter().sum::<f64>() * scale;
+            let limit = if total > 1.0 { total.recip() } else { 1.0 };
+
+            let mut moved = 0.0;
+            for (flow, n) in flow.iter().zip(adj.iter()) {
+                let amount = field[i] * flow * scale * limit;
+                buffer[n] += amount;
+                moved += amount;
+            }
+
+            buffer[i] += field[i] - moved;
+        }
+
+        field.copy_from_slice(buffer);
+    }
+
+    /// Height in m that air climbs on reaching the tile, averaged over the winds blowing in from its neighbours.
+    /// Zero on lee slopes, where the air descends. The sea surface counts as zero elevation.
+    pub fn windward_rise(&self, terrain: &[Terrain], adjacency: &[AdjArray], tile: usize) -> f64 {
+        let height = |i: usize| terrain[i].elevation.m().max(0.0);
+        let (mut rise, mut inflow) = (0.0, 0.0);
+
+        for n in adjacency[tile].iter() {
+            if let Some(k) = adjacency[n].iter().position(|m| m == tile) {
+                let flow = self.flow[n][k];
+                rise += flow * (height(tile) - height(n)).max(0.0);
+                inflow += flow;
+            }
+        }
+
+        if inflow > 0.0 {
+            rise / inflow
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Unit direction from `p` toward `q` as (east, north) components
+pub(crate) fn tangent_direction(p: Position3, q: Position3) -> (f64, f64) {
+    let horizontal = p.x.hypot(p.y);
+    if horizontal < 1e-9 {
+        return (0.0, 0.0);
+    }
+
+    let d = q - p;
+    let east = (-p.y * d.x + p.x * d.y) / horizontal;
+    let north = (-p.z * p.x * d.x - p.z * p.y * d.y + horizontal * horizontal * d.z) / horizontal;
+
+    let length = east.hypot(north);
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (east / length, north / length)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::constants::EARTH_RADIUS;
+    use crate::terrain::Elevation;
+
+    #[test]
+    fn earth_has_three_cells() {
+        assert_eq!(3, Weather::cells(Duration::in_d(0.997)));
+        assert_eq!(1, Weather::cells(Duration::in_d(243.0)));
+    }
+
+    #[test]
+    fn trade_winds_and_westerlies() {
+        let cells = 3;
+        let trade_winds = Weather::prevailing(15f64.to_radians(), cells);
+        let westerlies = Weather::prevailing(45f64.to_radians(), cells);
+        let south_trade_winds = Weather::prevailing(-15f64.to_radians(), cells);
+
+        assert!(trade_winds.east < 0.0);
+        assert!(trade_winds.north < 0.0);
+        assert!(westerlies.east > 0.0);
+        assert!(south_trade_winds.north > 0.0);
+    }
+
+    #[test]
+    fn no_wind_at_cell_boundaries() {
+        assert!(Weather::prevailing(0.0, 3).speed() < 1e-9);
+        assert!(Weather::prevailing(FRAC_PI_2, 3).speed() < 1e-9);
+    }
+
+    #[test]
+    fn advection_conserves_total() {
+        const N: usize = 64;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        let adjacency = adj.get(N);
+        let positions = adj.positions(N);
+
+        let weather = Weather::new(
+            adjacency,
+            positions,
+            Length::in_m(EARTH_RADIUS),
+            Duration::in_d(1.0),
+        );
+        let mut field = (0..N).map(|i| i as f64).collect::<Vec<_>>();
+        let before = field.iter().sum::<f64>();
+
+        let mut buffer = vec![];
+        for _ in 0..100 {
+            weather.advect(&mut field, &mut buffer, adjacency, Duration::in_d(1.0));
+        }
+
+        let after = field.iter().sum::<f64>();
+        assert!((before - after).abs() < 1e-6);
+        assert!(field.iter().all(|v| *v >= 0.0));
+    }
+
+    #[test]
+    fn air_rises_on_windward_slopes() {
+        const N: usize = 64;
+        let mut adj = Adjacency::default();
+        adj.register(N);
+        let adjacency = adj.get(N);
+        let positions = adj.positions(N);
+        let weather = Weather::new(
+            adjacency,
+            positions,
+            Length::in_m(EARTH_RADIUS),
+            Duration::in_d(1.0),
+        );
+
+        let windiest = (0..N)
+            .max_by(|a, b| {
+                weather.wind[*a]
+                    .speed()
+                    .total_cmp(&weather.wind[*b].speed())
+            })
+            .unwrap();
+        let mut terrain = vec![Terrain::new(0, 0, 0); N];
+        terrain[windiest] = Terrain::new(0, 200, 0).with_elevation(Elevation::in_m(2000.0));
+
+        for i in 0..N {
+            let rise = weather.windward_rise(&terrain, adjacency, i);
+            if i == windiest {
+                assert!(rise > 0.0 && rise <= 2000.0, "{}", rise);
+            } else {
+                assert_eq!(0.0, rise);
+            }
+        }
+    }
+}